@@ -0,0 +1,73 @@
+use geo_types::{polygon, Geometry};
+use serde::Serialize;
+use std::convert::TryFrom;
+use vega_lite_3::*;
+
+#[derive(Serialize)]
+struct Parcel {
+    name: &'static str,
+    area: f64,
+}
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // geometries computed in rust, with their properties
+    let parcels: Vec<(Geometry<f64>, Parcel)> = ["north", "east", "south", "west"]
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let x = i as f64;
+            let parcel = polygon![
+                (x: x, y: 0.0),
+                (x: x + 1.0, y: 0.0),
+                (x: x + 1.0, y: 1.0 + x),
+                (x: x, y: 1.0 + x),
+            ];
+            (
+                parcel.into(),
+                Parcel {
+                    name,
+                    area: 1.0 + x,
+                },
+            )
+        })
+        .collect();
+
+    // the chart
+    let chart = VegaliteBuilder::default()
+        .title("Parcels")
+        .width(500.0)
+        .height(300.0)
+        .data(UrlData::try_from(parcels)?)
+        .mark(Mark::Geoshape)
+        .projection(
+            ProjectionBuilder::default()
+                .projection_type(ProjectionType::Identity)
+                .reflect_y(true)
+                .build()?,
+        )
+        .encoding(
+            EncodingBuilder::default()
+                .color(
+                    DefWithConditionMarkPropFieldDefStringNullBuilder::default()
+                        .field("properties.area")
+                        .def_with_condition_mark_prop_field_def_string_null_type(
+                            StandardType::Quantitative,
+                        )
+                        .build()?,
+                )
+                .tooltip(vec![TextFieldDefBuilder::default()
+                    .field("properties.name")
+                    .text_field_def_type(StandardType::Nominal)
+                    .build()?])
+                .build()?,
+        )
+        .build()?;
+
+    // display the chart using `showata`
+    chart.show()?;
+
+    // print the vega lite spec
+    eprint!("{}", chart.to_string()?);
+
+    Ok(())
+}
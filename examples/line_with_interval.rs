@@ -36,12 +36,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 )
                 .build()?,
             LayerSpecBuilder::default()
-                .mark(
-                    MarkDefClassBuilder::default()
-                        .def_type(Mark::Errorband)
-                        .extent(ExtentExtent::Ci)
-                        .build()?,
-                )
+                .mark(ErrorBand::new().extent(ErrorExtent::Ci))
                 .encoding(
                     LayerEncodingBuilder::default()
                         .y(YClassBuilder::default()
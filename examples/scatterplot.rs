@@ -1,6 +1,6 @@
-use csv;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
+use vega_lite_3::trend::Trend;
 use vega_lite_3::*;
 
 #[derive(Serialize, Deserialize)]
@@ -15,11 +15,10 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     let mut rdr = csv::Reader::from_path(Path::new("examples/res/data/clustered_data.csv"))?;
     let values = rdr
         .deserialize()
-        .into_iter()
         .collect::<Result<Vec<Item>, csv::Error>>()?;
 
     // the chart
-    let chart = VegaliteBuilder::default()
+    let mut chart = VegaliteBuilder::default()
         .title("Clusters")
         .description("Dots colored by their cluster.")
         .data(&values)
@@ -43,6 +42,9 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         )
         .build()?;
 
+    // overlay a smoothed trend of all the dots, computed from the inline data
+    chart.add_trend(&Trend::loess(0.3).confidence(0.95).color("black"))?;
+
     // display the chart using `showata`
     chart.show()?;
 
@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use vega_lite_3::predicate::selection;
 use vega_lite_3::*;
 
 fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -30,11 +31,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
       SpecBuilder::default()
         .selection(selector_1)
         .transform(vec![TransformBuilder::default()
-          .filter(PurpleLogicalOperandPredicate::Predicate(
-            PredicateBuilder::default()
-              .selection(PurpleSelectionOperand::String("click".to_string()))
-              .build()?,
-          ))
+          .filter(selection("click"))
           .build()?])
         .mark(Mark::Point)
         .width(600)
@@ -45,7 +42,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
               DefWithConditionMarkPropFieldDefStringNullBuilder::default()
                 .condition(
                   ConditionalPredicateStringValueDefClassBuilder::default()
-                    .selection(PurpleSelectionOperand::String("brush".to_string()))
+                    .selection(selection("brush"))
                     .conditional_def_type(StandardType::Nominal)
                     .field("weather")
                     .title("Weather")
@@ -122,11 +119,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         .mark(Mark::Bar)
         .selection(selector_2)
         .transform(vec![TransformBuilder::default()
-          .filter(PurpleLogicalOperandPredicate::Predicate(
-            PredicateBuilder::default()
-              .selection(PurpleSelectionOperand::String("brush".to_string()))
-              .build()?,
-          ))
+          .filter(selection("brush"))
           .build()?])
         .encoding(
           EncodingBuilder::default()
@@ -134,7 +127,7 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
               DefWithConditionMarkPropFieldDefStringNullBuilder::default()
                 .condition(
                   ConditionalPredicateStringValueDefClassBuilder::default()
-                    .selection(PurpleSelectionOperand::String("click".to_string()))
+                    .selection(selection("click"))
                     .conditional_def_type(StandardType::Nominal)
                     .field("weather")
                     .title("Weather")
@@ -32,7 +32,7 @@ cat << EOF > tmp_schema.rs
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-#![allow(missing_docs, clippy::large_enum_variant)]
+#![allow(missing_docs, clippy::large_enum_variant, clippy::doc_lazy_continuation)]
 
 EOF
 cat $file >> tmp_schema.rs
@@ -89,3 +89,6 @@ sed -i 's/types#datetime/struct.DateTime.html/' $file
 
 cargo fmt -- $file
 
+echo '-- merge implementations'
+python3 scripts/merge_impls.py $file src/merge/schema.rs
+
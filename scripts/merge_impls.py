@@ -0,0 +1,59 @@
+import sys
+import re
+
+# Generate the `Merge` implementations for the structs and enums of the schema
+
+LICENSE = """// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+"""
+
+structs = []
+enums = []
+current = None
+
+with open(sys.argv[1], 'r', encoding="utf-8", errors='replace') as f:
+    for line in f:
+        match = re.search(r"^pub struct (\w+) \{", line)
+        if match:
+            current = (match.group(1), [])
+            structs.append(current)
+            continue
+        match = re.search(r"^pub enum (\w+) \{", line)
+        if match:
+            current = (match.group(1), [])
+            enums.append(current)
+            continue
+        if line.startswith("}"):
+            current = None
+            continue
+        if current is None:
+            continue
+        match = re.search(r"^    pub (\w+):", line) or re.search(r"^    (\w+)\(", line)
+        if match:
+            current[1].append(match.group(1))
+
+
+def block(name, members):
+    if not members:
+        return "    " + name + " {}\n"
+    return "    " + name + " {\n" + "".join("        " + m + ",\n" for m in members) + "    }\n"
+
+
+with open(sys.argv[2], 'w+', encoding="utf-8", errors='replace') as t:
+    t.write(LICENSE)
+    t.write("//! `Merge` for the types of the schema, generated by `scripts/merge_impls.py`\n\n")
+    t.write("use super::Merge;\nuse crate::schema::*;\n\n")
+    t.write("merge_structs! {\n")
+    t.write("".join(block(name, fields) for (name, fields) in structs))
+    t.write("}\n\nmerge_enums! {\n")
+    t.write("".join(block(name, variants) for (name, variants) in enums))
+    t.write("}\n")
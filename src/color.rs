@@ -0,0 +1,689 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Typed, validated colors, converting into the color strings of a specification, and the
+//! color schemes of Vega as palettes.
+//!
+//! ```
+//! use vega_lite_3::color::{Categorical, Color, Sequential};
+//! use vega_lite_3::*;
+//!
+//! let axis = AxisBuilder::default()
+//!     .domain_color("#4c78a8".parse::<Color>().unwrap())
+//!     .grid_color(Color::hsl(210.0, 0.3, 0.8))
+//!     .build()
+//!     .unwrap();
+//! assert!("#ff00".parse::<Color>().is_err());
+//!
+//! let categories = ScaleBuilder::default()
+//!     .range(Categorical::Tableau10.range())
+//!     .build()
+//!     .unwrap();
+//! let heat = ScaleBuilder::default()
+//!     .scheme(Sequential::Viridis.steps(5))
+//!     .build()
+//!     .unwrap();
+//! ```
+
+use crate::error::Error;
+use crate::schema::*;
+use std::fmt;
+use std::str::FromStr;
+
+/// A color, with an opacity between 0 and 1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Color {
+    r: u8,
+    g: u8,
+    b: u8,
+    a: f64,
+}
+
+/// The CSS named colors
+const NAMED: &[(&str, u32)] = &[
+    ("aliceblue", 0xf0f8ff),
+    ("antiquewhite", 0xfaebd7),
+    ("aqua", 0x00ffff),
+    ("aquamarine", 0x7fffd4),
+    ("azure", 0xf0ffff),
+    ("beige", 0xf5f5dc),
+    ("bisque", 0xffe4c4),
+    ("black", 0x000000),
+    ("blanchedalmond", 0xffebcd),
+    ("blue", 0x0000ff),
+    ("blueviolet", 0x8a2be2),
+    ("brown", 0xa52a2a),
+    ("burlywood", 0xdeb887),
+    ("cadetblue", 0x5f9ea0),
+    ("chartreuse", 0x7fff00),
+    ("chocolate", 0xd2691e),
+    ("coral", 0xff7f50),
+    ("cornflowerblue", 0x6495ed),
+    ("cornsilk", 0xfff8dc),
+    ("crimson", 0xdc143c),
+    ("cyan", 0x00ffff),
+    ("darkblue", 0x00008b),
+    ("darkcyan", 0x008b8b),
+    ("darkgoldenrod", 0xb8860b),
+    ("darkgray", 0xa9a9a9),
+    ("darkgreen", 0x006400),
+    ("darkgrey", 0xa9a9a9),
+    ("darkkhaki", 0xbdb76b),
+    ("darkmagenta", 0x8b008b),
+    ("darkolivegreen", 0x556b2f),
+    ("darkorange", 0xff8c00),
+    ("darkorchid", 0x9932cc),
+    ("darkred", 0x8b0000),
+    ("darksalmon", 0xe9967a),
+    ("darkseagreen", 0x8fbc8f),
+    ("darkslateblue", 0x483d8b),
+    ("darkslategray", 0x2f4f4f),
+    ("darkslategrey", 0x2f4f4f),
+    ("darkturquoise", 0x00ced1),
+    ("darkviolet", 0x9400d3),
+    ("deeppink", 0xff1493),
+    ("deepskyblue", 0x00bfff),
+    ("dimgray", 0x696969),
+    ("dimgrey", 0x696969),
+    ("dodgerblue", 0x1e90ff),
+    ("firebrick", 0xb22222),
+    ("floralwhite", 0xfffaf0),
+    ("forestgreen", 0x228b22),
+    ("fuchsia", 0xff00ff),
+    ("gainsboro", 0xdcdcdc),
+    ("ghostwhite", 0xf8f8ff),
+    ("gold", 0xffd700),
+    ("goldenrod", 0xdaa520),
+    ("gray", 0x808080),
+    ("green", 0x008000),
+    ("greenyellow", 0xadff2f),
+    ("grey", 0x808080),
+    ("honeydew", 0xf0fff0),
+    ("hotpink", 0xff69b4),
+    ("indianred", 0xcd5c5c),
+    ("indigo", 0x4b0082),
+    ("ivory", 0xfffff0),
+    ("khaki", 0xf0e68c),
+    ("lavender", 0xe6e6fa),
+    ("lavenderblush", 0xfff0f5),
+    ("lawngreen", 0x7cfc00),
+    ("lemonchiffon", 0xfffacd),
+    ("lightblue", 0xadd8e6),
+    ("lightcoral", 0xf08080),
+    ("lightcyan", 0xe0ffff),
+    ("lightgoldenrodyellow", 0xfafad2),
+    ("lightgray", 0xd3d3d3),
+    ("lightgreen", 0x90ee90),
+    ("lightgrey", 0xd3d3d3),
+    ("lightpink", 0xffb6c1),
+    ("lightsalmon", 0xffa07a),
+    ("lightseagreen", 0x20b2aa),
+    ("lightskyblue", 0x87cefa),
+    ("lightslategray", 0x778899),
+    ("lightslategrey", 0x778899),
+    ("lightsteelblue", 0xb0c4de),
+    ("lightyellow", 0xffffe0),
+    ("lime", 0x00ff00),
+    ("limegreen", 0x32cd32),
+    ("linen", 0xfaf0e6),
+    ("magenta", 0xff00ff),
+    ("maroon", 0x800000),
+    ("mediumaquamarine", 0x66cdaa),
+    ("mediumblue", 0x0000cd),
+    ("mediumorchid", 0xba55d3),
+    ("mediumpurple", 0x9370db),
+    ("mediumseagreen", 0x3cb371),
+    ("mediumslateblue", 0x7b68ee),
+    ("mediumspringgreen", 0x00fa9a),
+    ("mediumturquoise", 0x48d1cc),
+    ("mediumvioletred", 0xc71585),
+    ("midnightblue", 0x191970),
+    ("mintcream", 0xf5fffa),
+    ("mistyrose", 0xffe4e1),
+    ("moccasin", 0xffe4b5),
+    ("navajowhite", 0xffdead),
+    ("navy", 0x000080),
+    ("oldlace", 0xfdf5e6),
+    ("olive", 0x808000),
+    ("olivedrab", 0x6b8e23),
+    ("orange", 0xffa500),
+    ("orangered", 0xff4500),
+    ("orchid", 0xda70d6),
+    ("palegoldenrod", 0xeee8aa),
+    ("palegreen", 0x98fb98),
+    ("paleturquoise", 0xafeeee),
+    ("palevioletred", 0xdb7093),
+    ("papayawhip", 0xffefd5),
+    ("peachpuff", 0xffdab9),
+    ("peru", 0xcd853f),
+    ("pink", 0xffc0cb),
+    ("plum", 0xdda0dd),
+    ("powderblue", 0xb0e0e6),
+    ("purple", 0x800080),
+    ("rebeccapurple", 0x663399),
+    ("red", 0xff0000),
+    ("rosybrown", 0xbc8f8f),
+    ("royalblue", 0x4169e1),
+    ("saddlebrown", 0x8b4513),
+    ("salmon", 0xfa8072),
+    ("sandybrown", 0xf4a460),
+    ("seagreen", 0x2e8b57),
+    ("seashell", 0xfff5ee),
+    ("sienna", 0xa0522d),
+    ("silver", 0xc0c0c0),
+    ("skyblue", 0x87ceeb),
+    ("slateblue", 0x6a5acd),
+    ("slategray", 0x708090),
+    ("slategrey", 0x708090),
+    ("snow", 0xfffafa),
+    ("springgreen", 0x00ff7f),
+    ("steelblue", 0x4682b4),
+    ("tan", 0xd2b48c),
+    ("teal", 0x008080),
+    ("thistle", 0xd8bfd8),
+    ("tomato", 0xff6347),
+    ("turquoise", 0x40e0d0),
+    ("violet", 0xee82ee),
+    ("wheat", 0xf5deb3),
+    ("white", 0xffffff),
+    ("whitesmoke", 0xf5f5f5),
+    ("yellow", 0xffff00),
+    ("yellowgreen", 0x9acd32),
+];
+
+impl Color {
+    /// An opaque color from its red, green and blue components
+    pub fn rgb(r: u8, g: u8, b: u8) -> Self {
+        Color { r, g, b, a: 1.0 }
+    }
+
+    /// A color from its red, green and blue components and its opacity
+    pub fn rgba(r: u8, g: u8, b: u8, a: f64) -> Self {
+        Color::rgb(r, g, b).alpha(a)
+    }
+
+    /// An opaque color from its hue in degrees, and its saturation and lightness between 0
+    /// and 1
+    pub fn hsl(h: f64, s: f64, l: f64) -> Self {
+        let (s, l) = (s.clamp(0.0, 1.0), l.clamp(0.0, 1.0));
+        let h = h.rem_euclid(360.0);
+        let m2 = if l <= 0.5 {
+            l * (1.0 + s)
+        } else {
+            l + s - l * s
+        };
+        let m1 = 2.0 * l - m2;
+        let channel = |h: f64| {
+            let h = h.rem_euclid(360.0);
+            let value = if h < 60.0 {
+                m1 + (m2 - m1) * h / 60.0
+            } else if h < 180.0 {
+                m2
+            } else if h < 240.0 {
+                m1 + (m2 - m1) * (240.0 - h) / 60.0
+            } else {
+                m1
+            };
+            (value * 255.0).round() as u8
+        };
+        Color::rgb(channel(h + 120.0), channel(h), channel(h - 120.0))
+    }
+
+    /// The CSS color with this name
+    pub fn named(name: &str) -> Option<Self> {
+        let name = name.to_lowercase();
+        if name == "transparent" {
+            return Some(Color::rgba(0, 0, 0, 0.0));
+        }
+        NAMED
+            .iter()
+            .find(|(named, _)| *named == name)
+            .map(|(_, hex)| Color::rgb((hex >> 16) as u8, (hex >> 8) as u8, *hex as u8))
+    }
+
+    /// The same color with another opacity, between 0 and 1
+    pub fn alpha(self, a: f64) -> Self {
+        Color {
+            a: if a.is_nan() { 1.0 } else { a.clamp(0.0, 1.0) },
+            ..self
+        }
+    }
+
+    /// The red, green and blue components and the opacity of the color
+    pub fn components(&self) -> (u8, u8, u8, f64) {
+        (self.r, self.g, self.b, self.a)
+    }
+}
+
+fn invalid(color: &str) -> Error {
+    Error::Validation(format!("invalid color `{}`", color))
+}
+
+/// The arguments of a functional notation such as `rgb(0, 0, 0)`
+fn arguments<'a>(color: &'a str, function: &str) -> Option<Vec<&'a str>> {
+    let arguments = color.strip_prefix(function)?.trim_start();
+    let arguments = arguments.strip_prefix('(')?.strip_suffix(')')?;
+    Some(arguments.split(',').map(str::trim).collect())
+}
+
+/// A number, or a percentage of `max`
+fn number(value: &str, max: f64) -> Option<f64> {
+    let number = match value.strip_suffix('%') {
+        Some(percentage) => percentage.trim().parse::<f64>().ok()? * max / 100.0,
+        None => value.parse::<f64>().ok()?,
+    };
+    Some(number).filter(|number| number.is_finite())
+}
+
+fn hex(color: &str) -> Option<Color> {
+    let digits = color.strip_prefix('#')?;
+    if !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let value = u32::from_str_radix(digits, 16).ok()?;
+    let short = |value: u32| (value & 0xf) as u8 * 17;
+    match digits.len() {
+        3 => Some(Color::rgb(
+            short(value >> 8),
+            short(value >> 4),
+            short(value),
+        )),
+        6 => Some(Color::rgb(
+            (value >> 16) as u8,
+            (value >> 8) as u8,
+            value as u8,
+        )),
+        _ => None,
+    }
+}
+
+fn functional(color: &str) -> Option<Color> {
+    let channel = |value: &str| number(value, 255.0).map(|n| n.round().clamp(0.0, 255.0) as u8);
+    let alpha = |value: &str| number(value, 1.0);
+    let (rgb, alpha_argument) = match arguments(color, "rgba").or_else(|| arguments(color, "rgb")) {
+        Some(args) if args.len() == 3 || args.len() == 4 => (
+            Color::rgb(channel(args[0])?, channel(args[1])?, channel(args[2])?),
+            args.get(3).cloned(),
+        ),
+        Some(_) => return None,
+        None => {
+            let args = arguments(color, "hsla").or_else(|| arguments(color, "hsl"))?;
+            if args.len() != 3 && args.len() != 4 {
+                return None;
+            }
+            let percentage = |value: &str| {
+                value
+                    .strip_suffix('%')
+                    .and_then(|value| number(value.trim(), 1.0))
+                    .map(|percentage| percentage / 100.0)
+            };
+            (
+                Color::hsl(
+                    number(args[0], 360.0)?,
+                    percentage(args[1])?,
+                    percentage(args[2])?,
+                ),
+                args.get(3).cloned(),
+            )
+        }
+    };
+    match alpha_argument {
+        Some(value) => Some(rgb.alpha(alpha(value)?)),
+        None => Some(rgb),
+    }
+}
+
+impl FromStr for Color {
+    type Err = Error;
+
+    /// Parse a CSS color: `#rgb`, `#rrggbb`, `rgb()`, `rgba()`, `hsl()`, `hsla()` or a named
+    /// color
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let color = s.trim().to_lowercase();
+        hex(&color)
+            .or_else(|| functional(&color))
+            .or_else(|| Color::named(&color))
+            .ok_or_else(|| invalid(s))
+    }
+}
+
+impl fmt::Display for Color {
+    /// `#rrggbb` for opaque colors, `rgba(r, g, b, a)` otherwise
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.a >= 1.0 {
+            write!(f, "#{:02x}{:02x}{:02x}", self.r, self.g, self.b)
+        } else {
+            write!(f, "rgba({}, {}, {}, {})", self.r, self.g, self.b, self.a)
+        }
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        color.to_string()
+    }
+}
+
+impl From<Color> for RangeRange {
+    fn from(color: Color) -> Self {
+        RangeRange::String(color.to_string())
+    }
+}
+
+impl From<Vec<Color>> for ScaleRange {
+    fn from(colors: Vec<Color>) -> Self {
+        ScaleRange::UnionArray(colors.into_iter().map(RangeRange::from).collect())
+    }
+}
+
+impl From<Vec<Color>> for RangeValue {
+    fn from(colors: Vec<Color>) -> Self {
+        RangeValue::UnionArray(colors.into_iter().map(RangeRange::from).collect())
+    }
+}
+
+/// Palettes of a fixed list of colors, for nominal data
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Categorical {
+    /// `accent`, 8 colors
+    Accent,
+    /// `category10`, 10 colors
+    Category10,
+    /// `category20`, 20 colors
+    Category20,
+    /// `category20b`, 20 colors
+    Category20b,
+    /// `category20c`, 20 colors
+    Category20c,
+    /// `dark2`, 8 colors
+    Dark2,
+    /// `paired`, 12 colors
+    Paired,
+    /// `pastel1`, 9 colors
+    Pastel1,
+    /// `pastel2`, 8 colors
+    Pastel2,
+    /// `set1`, 9 colors
+    Set1,
+    /// `set2`, 8 colors
+    Set2,
+    /// `set3`, 12 colors
+    Set3,
+    /// `tableau10`, 10 colors, the default of Vega-Lite
+    Tableau10,
+    /// `tableau20`, 20 colors
+    Tableau20,
+}
+
+impl Categorical {
+    /// The name of the scheme in Vega
+    pub fn name(self) -> &'static str {
+        match self {
+            Categorical::Accent => "accent",
+            Categorical::Category10 => "category10",
+            Categorical::Category20 => "category20",
+            Categorical::Category20b => "category20b",
+            Categorical::Category20c => "category20c",
+            Categorical::Dark2 => "dark2",
+            Categorical::Paired => "paired",
+            Categorical::Pastel1 => "pastel1",
+            Categorical::Pastel2 => "pastel2",
+            Categorical::Set1 => "set1",
+            Categorical::Set2 => "set2",
+            Categorical::Set3 => "set3",
+            Categorical::Tableau10 => "tableau10",
+            Categorical::Tableau20 => "tableau20",
+        }
+    }
+
+    fn hex(self) -> &'static str {
+        match self {
+            Categorical::Accent => "7fc97fbeaed4fdc086ffff99386cb0f0027fbf5b17666666",
+            Categorical::Category10 => {
+                "1f77b4ff7f0e2ca02cd627289467bd8c564be377c27f7f7fbcbd2217becf"
+            }
+            Categorical::Category20 => {
+                "1f77b4aec7e8ff7f0effbb782ca02c98df8ad62728ff98969467bdc5b0d58c564bc49c94e377c2f7b6d27f7f7fc7c7c7bcbd22dbdb8d17becf9edae5"
+            }
+            Categorical::Category20b => {
+                "393b795254a36b6ecf9c9ede6379398ca252b5cf6bcedb9c8c6d31bd9e39e7ba52e7cb94843c39ad494ad6616be7969c7b4173a55194ce6dbdde9ed6"
+            }
+            Categorical::Category20c => {
+                "3182bd6baed69ecae1c6dbefe6550dfd8d3cfdae6bfdd0a231a35474c476a1d99bc7e9c0756bb19e9ac8bcbddcdadaeb636363969696bdbdbdd9d9d9"
+            }
+            Categorical::Dark2 => "1b9e77d95f027570b3e7298a66a61ee6ab02a6761d666666",
+            Categorical::Paired => {
+                "a6cee31f78b4b2df8a33a02cfb9a99e31a1cfdbf6fff7f00cab2d66a3d9affff99b15928"
+            }
+            Categorical::Pastel1 => "fbb4aeb3cde3ccebc5decbe4fed9a6ffffcce5d8bdfddaecf2f2f2",
+            Categorical::Pastel2 => "b3e2cdfdcdaccbd5e8f4cae4e6f5c9fff2aef1e2cccccccc",
+            Categorical::Set1 => "e41a1c377eb84daf4a984ea3ff7f00ffff33a65628f781bf999999",
+            Categorical::Set2 => "66c2a5fc8d628da0cbe78ac3a6d854ffd92fe5c494b3b3b3",
+            Categorical::Set3 => {
+                "8dd3c7ffffb3bebadafb807280b1d3fdb462b3de69fccde5d9d9d9bc80bdccebc5ffed6f"
+            }
+            Categorical::Tableau10 => {
+                "4c78a8f58518e4575672b7b254a24beeca3bb279a2ff9da69d755dbab0ac"
+            }
+            Categorical::Tableau20 => {
+                "4c78a89ecae9f58518ffbf7954a24b88d27ab79a20f2cf5b43989483bcb6e45756ff9d9879706ebab0acd67195fcbfd2b279a2d6a5c99e765fd8b5a5"
+            }
+        }
+    }
+
+    /// The colors of the palette
+    pub fn colors(self) -> Vec<Color> {
+        let hex = self.hex();
+        (0..hex.len() / 6)
+            .map(|i| {
+                let value = u32::from_str_radix(&hex[6 * i..6 * i + 6], 16).unwrap_or(0);
+                Color::rgb((value >> 16) as u8, (value >> 8) as u8, value as u8)
+            })
+            .collect()
+    }
+
+    /// The colors of the palette, as the explicit range of a scale
+    pub fn range(self) -> ScaleRange {
+        self.colors().into()
+    }
+
+    /// The palette, as the scheme of a scale
+    pub fn scheme(self) -> Scheme {
+        Scheme::String(self.name().to_string())
+    }
+}
+
+/// Define enums of color schemes interpolated by Vega, with their names
+macro_rules! interpolated {
+    ($(#[$doc:meta] $name:ident { $($(#[$variant_doc:meta])* $variant:ident => $scheme:expr,)* })*) => {
+        $(
+            #[$doc]
+            #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+            pub enum $name {
+                $($(#[$variant_doc])* $variant,)*
+            }
+
+            impl $name {
+                /// The name of the scheme in Vega
+                pub fn name(self) -> &'static str {
+                    match self {
+                        $($name::$variant => $scheme,)*
+                    }
+                }
+
+                /// The scheme of a scale
+                pub fn scheme(self) -> Scheme {
+                    Scheme::String(self.name().to_string())
+                }
+
+                /// The scheme of a scale, with `count` colors for discretizing scales
+                pub fn steps(self, count: usize) -> Scheme {
+                    Scheme::SchemeParams(SchemeParams {
+                        name: Some(self.name().to_string()),
+                        count: Some(count as f64),
+                        ..Default::default()
+                    })
+                }
+
+                /// The scheme of a scale, restricted to a part of its colors, `0.0` being its
+                /// first color and `1.0` its last
+                pub fn extent(self, start: f64, end: f64) -> Scheme {
+                    Scheme::SchemeParams(SchemeParams {
+                        name: Some(self.name().to_string()),
+                        extent: Some(vec![start, end]),
+                        ..Default::default()
+                    })
+                }
+            }
+        )*
+    };
+}
+
+interpolated! {
+    /// Schemes going from light to dark colors, for ordered data
+    Sequential {
+        /// `blues`
+        Blues => "blues",
+        /// `tealblues`
+        TealBlues => "tealblues",
+        /// `teals`
+        Teals => "teals",
+        /// `greens`
+        Greens => "greens",
+        /// `browns`
+        Browns => "browns",
+        /// `oranges`
+        Oranges => "oranges",
+        /// `reds`
+        Reds => "reds",
+        /// `purples`
+        Purples => "purples",
+        /// `warmgreys`
+        WarmGreys => "warmgreys",
+        /// `greys`
+        Greys => "greys",
+        /// `viridis`
+        Viridis => "viridis",
+        /// `magma`
+        Magma => "magma",
+        /// `inferno`
+        Inferno => "inferno",
+        /// `plasma`
+        Plasma => "plasma",
+        /// `bluegreen`
+        BlueGreen => "bluegreen",
+        /// `bluepurple`
+        BluePurple => "bluepurple",
+        /// `goldgreen`
+        GoldGreen => "goldgreen",
+        /// `goldorange`
+        GoldOrange => "goldorange",
+        /// `goldred`
+        GoldRed => "goldred",
+        /// `greenblue`
+        GreenBlue => "greenblue",
+        /// `orangered`
+        OrangeRed => "orangered",
+        /// `purplebluegreen`
+        PurpleBlueGreen => "purplebluegreen",
+        /// `purpleblue`
+        PurpleBlue => "purpleblue",
+        /// `purplered`
+        PurpleRed => "purplered",
+        /// `redpurple`
+        RedPurple => "redpurple",
+        /// `yellowgreenblue`
+        YellowGreenBlue => "yellowgreenblue",
+        /// `yellowgreen`
+        YellowGreen => "yellowgreen",
+        /// `yelloworangebrown`
+        YellowOrangeBrown => "yelloworangebrown",
+        /// `yelloworangered`
+        YellowOrangeRed => "yelloworangered",
+    }
+
+    /// Schemes going through a neutral color between two hues, for data around a midpoint
+    Diverging {
+        /// `blueorange`
+        BlueOrange => "blueorange",
+        /// `brownbluegreen`
+        BrownBlueGreen => "brownbluegreen",
+        /// `purplegreen`
+        PurpleGreen => "purplegreen",
+        /// `pinkyellowgreen`
+        PinkYellowGreen => "pinkyellowgreen",
+        /// `purpleorange`
+        PurpleOrange => "purpleorange",
+        /// `redblue`
+        RedBlue => "redblue",
+        /// `redgrey`
+        RedGrey => "redgrey",
+        /// `redyellowblue`
+        RedYellowBlue => "redyellowblue",
+        /// `redyellowgreen`
+        RedYellowGreen => "redyellowgreen",
+        /// `spectral`
+        Spectral => "spectral",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse() {
+        let parse = |color: &str| color.parse::<Color>().map(|color| color.to_string());
+        assert_eq!(parse("#F0a").unwrap(), "#ff00aa");
+        assert_eq!(parse("#4c78a8").unwrap(), "#4c78a8");
+        assert_eq!(parse(" rgb(255, 0, 10) ").unwrap(), "#ff000a");
+        assert_eq!(
+            parse("rgba(100%, 0%, 0%, 0.5)").unwrap(),
+            "rgba(255, 0, 0, 0.5)"
+        );
+        assert_eq!(parse("hsl(120, 100%, 25%)").unwrap(), "#008000");
+        assert_eq!(parse("SteelBlue").unwrap(), "#4682b4");
+        assert_eq!(parse("transparent").unwrap(), "rgba(0, 0, 0, 0)");
+        for invalid in &[
+            "#ff00",
+            "#4c78a880",
+            "#ggg",
+            "rgb(1, 2)",
+            "hsl(120, 1, 1)",
+            "bleu",
+            "",
+        ] {
+            assert!(parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_palettes() {
+        assert_eq!(Categorical::Tableau20.colors().len(), 20);
+        assert_eq!(
+            Categorical::Category10.colors()[0],
+            Color::rgb(0x1f, 0x77, 0xb4)
+        );
+        assert_eq!(
+            serde_json::to_value(Categorical::Set1.range()).unwrap()[8],
+            json!("#999999")
+        );
+        assert_eq!(
+            serde_json::to_value(Sequential::Viridis.steps(5)).unwrap(),
+            json!({"name": "viridis", "count": 5.0})
+        );
+        assert_eq!(
+            serde_json::to_value(Diverging::RedBlue.scheme()).unwrap(),
+            json!("redblue")
+        );
+    }
+}
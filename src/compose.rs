@@ -0,0 +1,1181 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::error::Error;
+use crate::schema::*;
+use crate::RemovableValue;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use std::ops;
+
+/// Move the channels shared by `Encoding` and `LayerEncoding`
+macro_rules! layer_channels {
+    ($from:ident, $to:ident { $($extra:tt)* }) => {
+        $to {
+            color: $from.color,
+            detail: $from.detail,
+            fill: $from.fill,
+            fill_opacity: $from.fill_opacity,
+            href: $from.href,
+            key: $from.key,
+            latitude: $from.latitude,
+            latitude2: $from.latitude2,
+            longitude: $from.longitude,
+            longitude2: $from.longitude2,
+            opacity: $from.opacity,
+            order: $from.order,
+            shape: $from.shape,
+            size: $from.size,
+            stroke: $from.stroke,
+            stroke_opacity: $from.stroke_opacity,
+            stroke_width: $from.stroke_width,
+            text: $from.text,
+            tooltip: $from.tooltip,
+            x: $from.x,
+            x2: $from.x2,
+            x_error: $from.x_error,
+            x_error2: $from.x_error2,
+            y: $from.y,
+            y2: $from.y2,
+            y_error: $from.y_error,
+            y_error2: $from.y_error2,
+            $($extra)*
+        }
+    };
+}
+
+/// Call a macro with the channels shared by `Encoding` and `LayerEncoding`
+macro_rules! with_layer_channels {
+    ($callback:ident) => {
+        $callback!(
+            color,
+            detail,
+            fill,
+            fill_opacity,
+            href,
+            key,
+            latitude,
+            latitude2,
+            longitude,
+            longitude2,
+            opacity,
+            order,
+            shape,
+            size,
+            stroke,
+            stroke_opacity,
+            stroke_width,
+            text,
+            tooltip,
+            x,
+            x2,
+            x_error,
+            x_error2,
+            y,
+            y2,
+            y_error,
+            y_error2
+        )
+    };
+}
+
+impl From<LayerEncoding> for Encoding {
+    fn from(v: LayerEncoding) -> Self {
+        layer_channels!(
+            v,
+            Encoding {
+                column: None,
+                facet: None,
+                row: None,
+            }
+        )
+    }
+}
+
+impl TryFrom<Encoding> for LayerEncoding {
+    type Error = Error;
+
+    /// Fails when the encoding has `row`, `column` or `facet` channels, that layers can't have
+    fn try_from(v: Encoding) -> Result<Self, Self::Error> {
+        let facets = [("row", &v.row), ("column", &v.column), ("facet", &v.facet)];
+        if let Some((channel, _)) = facets.iter().find(|(_, def)| def.is_some()) {
+            return Err(Error::Validation(format!(
+                "a layer can't have a `{}` channel",
+                channel
+            )));
+        }
+        Ok(layer_channels!(v, LayerEncoding {}))
+    }
+}
+
+fn json(value: &impl serde::Serialize) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+/// The properties of a chart that can only be set at the root of a specification
+#[derive(Debug, Default)]
+pub(crate) struct TopLevel {
+    schema: Option<String>,
+    autosize: Option<Autosize>,
+    background: Option<String>,
+    config: Option<Config>,
+    datasets: Option<HashMap<String, InlineDatasetValue>>,
+    padding: Option<Padding>,
+    usermeta: Option<HashMap<String, Option<Value>>>,
+}
+
+/// Keep a property set by several charts, as long as they agree on its value
+fn merge_property<T: serde::Serialize>(
+    name: &str,
+    property: &mut Option<T>,
+    other: Option<T>,
+) -> Result<(), Error> {
+    match (property.as_ref(), other) {
+        (_, None) => {}
+        (None, other) => *property = other,
+        (Some(value), Some(other)) if json(value) == json(&other) => {}
+        (Some(_), Some(_)) => {
+            return Err(Error::Validation(format!(
+                "charts with different `{}` can't be composed",
+                name
+            )))
+        }
+    }
+    Ok(())
+}
+
+impl TopLevel {
+    /// Remove the root properties of a chart
+    pub(crate) fn take(chart: &mut Vegalite) -> Self {
+        TopLevel {
+            schema: chart.schema.take(),
+            autosize: chart.autosize.take(),
+            background: chart.background.take(),
+            config: chart.config.take(),
+            datasets: chart.datasets.take(),
+            padding: chart.padding.take(),
+            usermeta: chart.usermeta.take(),
+        }
+    }
+
+    /// Merge the root properties of another chart, that must agree with the current ones;
+    /// datasets and metadata are merged by name
+    pub(crate) fn merge(&mut self, other: TopLevel) -> Result<(), Error> {
+        merge_property("$schema", &mut self.schema, other.schema)?;
+        merge_property("autosize", &mut self.autosize, other.autosize)?;
+        merge_property("background", &mut self.background, other.background)?;
+        merge_property("config", &mut self.config, other.config)?;
+        merge_property("padding", &mut self.padding, other.padding)?;
+        for (name, dataset) in other.datasets.unwrap_or_default() {
+            let datasets = self.datasets.get_or_insert_with(HashMap::new);
+            merge_property(
+                &format!("datasets.{}", name),
+                &mut datasets.get(&name).cloned(),
+                Some(dataset.clone()),
+            )?;
+            datasets.insert(name, dataset);
+        }
+        for (name, value) in other.usermeta.unwrap_or_default() {
+            let usermeta = self.usermeta.get_or_insert_with(HashMap::new);
+            merge_property(
+                &format!("usermeta.{}", name),
+                &mut usermeta.get(&name).cloned(),
+                Some(value.clone()),
+            )?;
+            usermeta.insert(name, value);
+        }
+        Ok(())
+    }
+
+    /// Set the root properties of a chart
+    pub(crate) fn apply(self, chart: &mut Vegalite) {
+        chart.schema = self.schema;
+        chart.autosize = self.autosize;
+        chart.background = self.background;
+        chart.config = self.config;
+        chart.datasets = self.datasets;
+        chart.padding = self.padding;
+        chart.usermeta = self.usermeta;
+    }
+}
+
+/// Fail when one of `properties` is set
+fn check_unset(kind: &str, properties: &[(&str, bool)]) -> Result<(), Error> {
+    match properties.iter().find(|(_, set)| *set) {
+        Some((name, _)) => Err(Error::Validation(format!(
+            "a {} can't have a `{}`",
+            kind, name
+        ))),
+        None => Ok(()),
+    }
+}
+
+/// Fail when a chart has properties that can only be set at the root of a specification
+fn check_root_unset(kind: &str, v: &Vegalite) -> Result<(), Error> {
+    check_unset(
+        kind,
+        &[
+            ("$schema", v.schema.is_some()),
+            ("autosize", v.autosize.is_some()),
+            ("background", v.background.is_some()),
+            ("config", v.config.is_some()),
+            ("datasets", v.datasets.is_some()),
+            ("padding", v.padding.is_some()),
+            ("usermeta", v.usermeta.is_some()),
+        ],
+    )
+}
+
+/// Move the properties shared by `Vegalite`, `Spec` and `SpecClass` from one to another, or
+/// `take` them out of a mutable reference
+macro_rules! view_properties {
+    (take $from:ident => $to:ident) => {
+        view_properties!(@ taken $from => $to)
+    };
+    ($from:ident => $to:ident) => {
+        view_properties!(@ moved $from => $to)
+    };
+    (@ taken $from:ident . $name:ident) => {
+        std::mem::take(&mut $from.$name)
+    };
+    (@ moved $from:ident . $name:ident) => {
+        $from.$name
+    };
+    (@ $access:ident $from:ident => $to:ident) => {
+        view_properties!(
+            @ $access $from => $to;
+            align, bounds, center, columns, data, description, encoding, height, mark, name,
+            projection, resolve, selection, spacing, title, transform, view, width, layer, facet,
+            repeat, concat, vconcat, hconcat
+        )
+    };
+    (@ $access:ident $from:ident => $to:ident; $($name:ident),*) => {
+        $($to.$name = view_properties!(@ $access $from.$name);)*
+    };
+}
+
+impl TryFrom<Vegalite> for Spec {
+    type Error = Error;
+
+    /// Fails for charts with properties that can only be set at the root of a specification
+    fn try_from(mut v: Vegalite) -> Result<Self, Self::Error> {
+        take_spec(&mut v)
+    }
+}
+
+/// Move the properties of a chart to a composed spec, without moving the whole chart
+fn take_spec(v: &mut Vegalite) -> Result<Spec, Error> {
+    check_root_unset("composed chart", v)?;
+    let mut spec = Spec::default();
+    view_properties!(take v => spec);
+    spec.spec = v.spec.take();
+    Ok(spec)
+}
+
+impl TryFrom<Vegalite> for SpecClass {
+    type Error = Error;
+
+    /// Fails for charts with properties that can only be set at the root of a specification
+    fn try_from(mut v: Vegalite) -> Result<Self, Self::Error> {
+        take_spec_class(&mut v)
+    }
+}
+
+/// Move the properties of a chart to a faceted or repeated spec, without moving the whole chart
+fn take_spec_class(v: &mut Vegalite) -> Result<SpecClass, Error> {
+    check_root_unset("composed chart", v)?;
+    let mut spec = SpecClass::default();
+    view_properties!(take v => spec);
+    spec.spec = v.spec.take().map(Box::new);
+    Ok(spec)
+}
+
+impl From<Spec> for Vegalite {
+    fn from(v: Spec) -> Self {
+        let mut chart = Vegalite::default();
+        view_properties!(v => chart);
+        chart.spec = v.spec;
+        chart
+    }
+}
+
+impl From<SpecClass> for Vegalite {
+    fn from(v: SpecClass) -> Self {
+        let mut chart = Vegalite::default();
+        view_properties!(v => chart);
+        chart.spec = v.spec.map(|spec| *spec);
+        chart
+    }
+}
+
+impl TryFrom<Vegalite> for LayerSpec {
+    type Error = Error;
+
+    /// Fails for charts that are not single views or layers, or that have properties that can
+    /// only be set at the root of a specification
+    fn try_from(mut v: Vegalite) -> Result<Self, Self::Error> {
+        take_layer_spec(&mut v)
+    }
+}
+
+/// Move the properties of a chart to a layer, without moving the whole chart: the schema
+/// structs are large, and copying them around can overflow the stack of debug builds
+fn take_layer_spec(v: &mut Vegalite) -> Result<LayerSpec, Error> {
+    check_root_unset("layer", v)?;
+    check_unset(
+        "layer",
+        &[
+            ("align", v.align.is_some()),
+            ("bounds", v.bounds.is_some()),
+            ("center", v.center.is_some()),
+            ("columns", v.columns.is_some()),
+            ("spacing", v.spacing.is_some()),
+            ("facet", v.facet.is_some()),
+            ("spec", v.spec.is_some()),
+            ("repeat", v.repeat.is_some()),
+            ("concat", v.concat.is_some()),
+            ("hconcat", v.hconcat.is_some()),
+            ("vconcat", v.vconcat.is_some()),
+        ],
+    )?;
+    Ok(LayerSpec {
+        data: std::mem::take(&mut v.data),
+        description: v.description.take(),
+        encoding: v.encoding.take().map(LayerEncoding::try_from).transpose()?,
+        height: v.height.take(),
+        layer: v.layer.take(),
+        name: v.name.take(),
+        projection: v.projection.take(),
+        resolve: v.resolve.take(),
+        title: v.title.take(),
+        transform: v.transform.take(),
+        view: v.view.take(),
+        width: v.width.take(),
+        mark: v.mark.take(),
+        selection: v.selection.take(),
+    })
+}
+
+/// Whether an encoding sets no channel
+fn is_empty(encoding: &impl serde::Serialize) -> bool {
+    json(encoding).as_object().map_or(true, Map::is_empty)
+}
+
+/// Remove the encoding channels that all the layers share, to set them on their parent
+fn hoist_channels(layers: &mut [LayerSpec]) -> Option<Encoding> {
+    if layers.len() < 2 {
+        return None;
+    }
+    let mut shared = LayerEncoding::default();
+    macro_rules! hoist {
+        ($($channel:ident),*) => {$(
+            let def = |layer: &LayerSpec| {
+                layer.encoding.as_ref().map_or(Value::Null, |encoding| json(&encoding.$channel))
+            };
+            let first = def(&layers[0]);
+            if !first.is_null() && layers[1..].iter().all(|layer| def(layer) == first) {
+                for layer in layers.iter_mut() {
+                    if let Some(encoding) = &mut layer.encoding {
+                        shared.$channel = std::mem::take(&mut encoding.$channel);
+                    }
+                }
+            }
+        )*};
+    }
+    with_layer_channels!(hoist);
+    if is_empty(&shared) {
+        return None;
+    }
+    for layer in layers.iter_mut() {
+        if layer.encoding.as_ref().is_some_and(is_empty) {
+            layer.encoding = None;
+        }
+    }
+    Some(shared.into())
+}
+
+/// Whether all the layers have the same value
+fn shared(layers: &[LayerSpec], value: impl Fn(&LayerSpec) -> Value) -> bool {
+    let first = value(&layers[0]);
+    layers.iter().all(|layer| value(layer) == first)
+}
+
+/// Convert charts with `take`, moving their root properties to `top_level`; the errors name
+/// the chart that failed
+fn convert<T>(
+    kind: &str,
+    charts: &mut [Vegalite],
+    top_level: &mut TopLevel,
+    take: fn(&mut Vegalite) -> Result<T, Error>,
+) -> Result<Vec<T>, Error> {
+    let mut converted = Vec::with_capacity(charts.len());
+    for (i, chart) in charts.iter_mut().enumerate() {
+        let context = |err| match err {
+            Error::Validation(reason) => Error::Validation(format!("{} {}: {}", kind, i, reason)),
+            err => err,
+        };
+        top_level.merge(TopLevel::take(chart)).map_err(context)?;
+        match take(chart) {
+            Ok(spec) => converted.push(spec),
+            Err(err) => return Err(context(err)),
+        }
+    }
+    Ok(converted)
+}
+
+/// Build a composed chart with `set`, and set its root properties. This is kept apart from
+/// the conversion of the charts, so that their large temporaries don't share a stack frame in
+/// debug builds
+fn assemble(top_level: TopLevel, set: impl FnOnce(&mut Vegalite)) -> Result<Vegalite, Error> {
+    let mut chart = Vegalite::default();
+    set(&mut chart);
+    top_level.apply(&mut chart);
+    Ok(chart)
+}
+
+/// Layer charts on top of each other.
+///
+/// The charts must be single views or layers. The data, transforms and encoding channels that
+/// all the charts share are set once on the layered chart, the layers inheriting them, and the
+/// root properties like `config` are moved to the layered chart.
+pub fn layer(mut charts: Vec<Vegalite>) -> Result<Vegalite, Error> {
+    if charts.is_empty() {
+        return Err(Error::Validation(
+            "a layer needs at least one chart".to_string(),
+        ));
+    }
+    let mut top_level = TopLevel::default();
+    let mut layers = convert("layer", &mut charts, &mut top_level, take_layer_spec)?;
+    assemble(top_level, |chart| {
+        if layers.len() > 1
+            && matches!(layers[0].data, RemovableValue::Specified(_))
+            && shared(&layers, |layer| json(&layer.data))
+        {
+            let transform =
+                layers[0].transform.is_some() && shared(&layers, |layer| json(&layer.transform));
+            chart.data = std::mem::take(&mut layers[0].data);
+            if transform {
+                chart.transform = layers[0].transform.take();
+            }
+            for layer in &mut layers {
+                layer.data = RemovableValue::Default;
+                if transform {
+                    layer.transform = None;
+                }
+            }
+        }
+        chart.encoding = hoist_channels(&mut layers);
+        chart.layer = Some(layers);
+    })
+}
+
+impl From<LayerSpec> for Vegalite {
+    fn from(mut v: LayerSpec) -> Self {
+        let mut chart = Vegalite::default();
+        take_layer_properties(&mut v, &mut chart);
+        chart
+    }
+}
+
+/// Move the properties of a layer to a chart
+fn take_layer_properties(v: &mut LayerSpec, chart: &mut Vegalite) {
+    chart.data = std::mem::take(&mut v.data);
+    chart.description = v.description.take();
+    chart.encoding = v.encoding.take().map(Encoding::from);
+    chart.height = v.height.take();
+    chart.layer = v.layer.take();
+    chart.name = v.name.take();
+    chart.projection = v.projection.take();
+    chart.resolve = v.resolve.take();
+    chart.title = v.title.take();
+    chart.transform = v.transform.take();
+    chart.view = v.view.take();
+    chart.width = v.width.take();
+    chart.mark = v.mark.take();
+    chart.selection = v.selection.take();
+}
+
+/// The json names of the properties of `TopLevel`
+const ROOT_PROPERTIES: &[&str] = &[
+    "$schema",
+    "autosize",
+    "background",
+    "config",
+    "datasets",
+    "padding",
+    "usermeta",
+];
+
+/// Whether a chart only sets `properties`, besides its root properties
+fn only_sets(chart: &Vegalite, properties: &[&str]) -> bool {
+    match json(chart) {
+        Value::Object(set) => set.keys().all(|property| {
+            properties.contains(&property.as_str()) || ROOT_PROPERTIES.contains(&property.as_str())
+        }),
+        _ => false,
+    }
+}
+
+/// Replace the first chart by its layers when it only layers charts, with the properties they
+/// inherit, converting them in place
+fn expand_layers(charts: &mut Vec<Vegalite>) {
+    let chart = &mut charts[0];
+    if !only_sets(chart, &["layer", "data", "transform", "encoding"]) {
+        return;
+    }
+    let mut layers = match chart.layer.take() {
+        Some(layers) => layers,
+        None => return,
+    };
+    let shared = chart.encoding.take().filter(|encoding| !is_empty(encoding));
+    let data = std::mem::take(&mut chart.data);
+    let transform = chart.transform.take();
+    let top_level = TopLevel::take(chart);
+    let mut others = charts.split_off(1);
+    charts.clear();
+    for layer in &mut layers {
+        charts.push(Vegalite::default());
+        let chart = charts.last_mut().unwrap();
+        take_layer_properties(layer, chart);
+        if let RemovableValue::Default = chart.data {
+            chart.data = data.clone();
+            chart.transform = match (&transform, chart.transform.take()) {
+                (Some(shared), Some(own)) => Some(shared.iter().cloned().chain(own).collect()),
+                (shared, own) => own.or_else(|| shared.clone()),
+            };
+        }
+        if let Some(shared) = &shared {
+            let encoding = chart.encoding.get_or_insert_with(Default::default);
+            macro_rules! inherit {
+                ($($channel:ident),*) => {$(
+                    if json(&encoding.$channel).is_null() {
+                        encoding.$channel = shared.$channel.clone();
+                    }
+                )*};
+            }
+            with_layer_channels!(inherit);
+            inherit!(column, facet, row);
+        }
+    }
+    top_level.apply(&mut charts[0]);
+    charts.append(&mut others);
+}
+
+/// The charts composed by an operator, its first operand being expanded by `expand`
+fn operands(
+    first: Result<Vegalite, Error>,
+    second: Vegalite,
+    expand: fn(&mut Vec<Vegalite>),
+) -> Result<Vec<Vegalite>, Error> {
+    let mut charts = vec![];
+    match first {
+        Ok(first) => charts.push(first),
+        Err(err) => return Err(err),
+    }
+    expand(&mut charts);
+    charts.push(second);
+    Ok(charts)
+}
+
+impl ops::Add for Vegalite {
+    type Output = Result<Vegalite, Error>;
+
+    /// Layer two charts, see [`layer`](fn.layer.html); the layers of a chart that only layers
+    /// charts are layered with the other chart instead of the chart itself
+    fn add(self, other: Vegalite) -> Self::Output {
+        layer(operands(Ok(self), other, expand_layers)?)
+    }
+}
+
+impl ops::Add<Vegalite> for Result<Vegalite, Error> {
+    type Output = Result<Vegalite, Error>;
+
+    /// Add a layer to a chart that could be layered, to write `(a + b + c)?`
+    fn add(self, other: Vegalite) -> Self::Output {
+        layer(operands(self, other, expand_layers)?)
+    }
+}
+
+/// Compose charts, their root properties being moved to the composed chart
+fn compose(
+    kind: &str,
+    mut charts: Vec<Vegalite>,
+    set: impl FnOnce(&mut Vegalite, Vec<Spec>),
+) -> Result<Vegalite, Error> {
+    if charts.is_empty() {
+        return Err(Error::Validation(format!(
+            "{} needs at least one chart",
+            kind
+        )));
+    }
+    let mut top_level = TopLevel::default();
+    let specs = convert(kind, &mut charts, &mut top_level, take_spec)?;
+    assemble(top_level, |chart| set(chart, specs))
+}
+
+/// Place charts side by side
+pub fn hconcat(charts: Vec<Vegalite>) -> Result<Vegalite, Error> {
+    compose("hconcat", charts, |chart, specs| {
+        chart.hconcat = Some(specs)
+    })
+}
+
+/// Place charts on top of each other
+pub fn vconcat(charts: Vec<Vegalite>) -> Result<Vegalite, Error> {
+    compose("vconcat", charts, |chart, specs| {
+        chart.vconcat = Some(specs)
+    })
+}
+
+/// Place charts in a grid, wrapping rows after `columns` charts, or in a single row
+pub fn concat(charts: Vec<Vegalite>, columns: Option<usize>) -> Result<Vegalite, Error> {
+    compose("concat", charts, |chart, specs| {
+        chart.concat = Some(specs);
+        chart.columns = columns.map(|columns| columns as f64);
+    })
+}
+
+/// Replace the first chart by the charts it concatenates when it only concatenates charts,
+/// converting them in place
+fn expand_concatenated(charts: &mut Vec<Vegalite>, hconcat: bool) {
+    let chart = &mut charts[0];
+    let property = if hconcat { "hconcat" } else { "vconcat" };
+    if !only_sets(chart, &[property]) {
+        return;
+    }
+    let mut specs = if hconcat {
+        chart.hconcat.take()
+    } else {
+        chart.vconcat.take()
+    };
+    let top_level = TopLevel::take(chart);
+    let mut others = charts.split_off(1);
+    charts.clear();
+    for spec in specs.iter_mut().flatten() {
+        charts.push(Vegalite::default());
+        let chart = charts.last_mut().unwrap();
+        view_properties!(take spec => chart);
+        chart.spec = spec.spec.take();
+    }
+    if let Some(first) = charts.first_mut() {
+        top_level.apply(first);
+    }
+    charts.append(&mut others);
+}
+
+impl ops::BitOr for Vegalite {
+    type Output = Result<Vegalite, Error>;
+
+    /// Place two charts side by side, see [`hconcat`](fn.hconcat.html); the charts of a chart
+    /// that only places charts side by side are kept in the same row
+    fn bitor(self, other: Vegalite) -> Self::Output {
+        hconcat(operands(Ok(self), other, |charts| {
+            expand_concatenated(charts, true)
+        })?)
+    }
+}
+
+impl ops::BitOr<Vegalite> for Result<Vegalite, Error> {
+    type Output = Result<Vegalite, Error>;
+
+    /// Place a chart next to a composed chart, to write `(a | b | c)?`
+    fn bitor(self, other: Vegalite) -> Self::Output {
+        hconcat(operands(self, other, |charts| {
+            expand_concatenated(charts, true)
+        })?)
+    }
+}
+
+impl ops::BitAnd for Vegalite {
+    type Output = Result<Vegalite, Error>;
+
+    /// Place two charts on top of each other, see [`vconcat`](fn.vconcat.html); the charts
+    /// of a chart that only places charts on top of each other are kept in the same column
+    fn bitand(self, other: Vegalite) -> Self::Output {
+        vconcat(operands(Ok(self), other, |charts| {
+            expand_concatenated(charts, false)
+        })?)
+    }
+}
+
+impl ops::BitAnd<Vegalite> for Result<Vegalite, Error> {
+    type Output = Result<Vegalite, Error>;
+
+    /// Place a chart under a composed chart, to write `(a & b & c)?`
+    fn bitand(self, other: Vegalite) -> Self::Output {
+        vconcat(operands(self, other, |charts| {
+            expand_concatenated(charts, false)
+        })?)
+    }
+}
+
+/// Fail when the encoding of a chart, or of one of its layers, uses one of `channels`
+fn check_channels(kind: &str, chart: &Value, channels: &[&str]) -> Result<(), Error> {
+    if let Some(channel) = channels
+        .iter()
+        .find(|channel| !chart["encoding"][**channel].is_null())
+    {
+        return Err(Error::Validation(format!(
+            "a {} can't use the `{}` channel",
+            kind, channel
+        )));
+    }
+    for layer in chart["layer"].as_array().into_iter().flatten() {
+        check_channels(kind, layer, channels)?;
+    }
+    Ok(())
+}
+
+/// Wrap a unit or layered chart into a trellis of charts, one for each value of the `row`
+/// and `column` fields; the data and transforms of the chart are moved to the faceted chart
+pub fn facet(
+    mut chart: Vegalite,
+    row: Option<FacetFieldDef>,
+    column: Option<FacetFieldDef>,
+) -> Result<Vegalite, Error> {
+    if row.is_none() && column.is_none() {
+        return Err(Error::Validation(
+            "a facet needs a row or a column field".to_string(),
+        ));
+    }
+    check_unset(
+        "faceted chart",
+        &[
+            ("facet", chart.facet.is_some()),
+            ("spec", chart.spec.is_some()),
+            ("repeat", chart.repeat.is_some()),
+            ("concat", chart.concat.is_some()),
+            ("hconcat", chart.hconcat.is_some()),
+            ("vconcat", chart.vconcat.is_some()),
+        ],
+    )?;
+    check_channels("faceted chart", &json(&chart), &["row", "column", "facet"])?;
+    let top_level = TopLevel::take(&mut chart);
+    let data = std::mem::take(&mut chart.data);
+    let transform = chart.transform.take();
+    let spec = take_spec_class(&mut chart)?;
+    assemble(top_level, |faceted| {
+        faceted.data = data;
+        faceted.transform = transform;
+        faceted.facet = Some(Facet {
+            row,
+            column,
+            ..Default::default()
+        });
+        faceted.spec = Some(spec);
+    })
+}
+
+/// Templates of field names standing for the repeated fields, and the repeat they refer to
+const REPEAT_TEMPLATES: &[(&str, &str)] = &[("{row}", "row"), ("{column}", "column")];
+
+/// Replace the templated field names of an encoding by references to the repeated fields,
+/// collecting the templates found
+fn substitute_fields(value: &mut Value, found: &mut Vec<&'static str>) {
+    match value {
+        Value::Object(object) => {
+            for (name, value) in object.iter_mut() {
+                let template = REPEAT_TEMPLATES
+                    .iter()
+                    .find(|(template, _)| name == "field" && value == template);
+                match template {
+                    Some((template, repeat)) => {
+                        found.push(template);
+                        *value = serde_json::json!({ "repeat": repeat });
+                    }
+                    None => substitute_fields(value, found),
+                }
+            }
+        }
+        Value::Array(values) => values
+            .iter_mut()
+            .for_each(|value| substitute_fields(value, found)),
+        _ => {}
+    }
+}
+
+/// Replace the templated field names of the channels of an encoding, collecting the templates
+/// found
+fn substitute_encoding<T>(
+    encoding: &mut Option<T>,
+    found: &mut Vec<&'static str>,
+) -> Result<(), Error>
+where
+    T: serde::Serialize + serde::de::DeserializeOwned,
+{
+    if let Some(encoding) = encoding {
+        let mut channels = json(encoding);
+        substitute_fields(&mut channels, found);
+        *encoding = serde_json::from_value(channels)?;
+    }
+    Ok(())
+}
+
+/// Repeat a chart for each of the `row` and `column` fields, the fields of its encoding
+/// named `{row}` and `{column}` being replaced by the repeated fields
+pub fn repeat(
+    mut chart: Vegalite,
+    row: Vec<String>,
+    column: Vec<String>,
+) -> Result<Vegalite, Error> {
+    let top_level = TopLevel::take(&mut chart);
+    let mut found = vec![];
+    substitute_encoding(&mut chart.encoding, &mut found)?;
+    for layer in chart.layer.iter_mut().flatten() {
+        substitute_encoding(&mut layer.encoding, &mut found)?;
+    }
+    for ((template, name), fields) in REPEAT_TEMPLATES.iter().zip(&[&row, &column]) {
+        match (found.contains(template), fields.is_empty()) {
+            (true, true) => {
+                return Err(Error::Validation(format!(
+                    "`{}` is used but no {} fields are repeated",
+                    template, name
+                )))
+            }
+            (false, false) => {
+                return Err(Error::Validation(format!(
+                    "{} fields are repeated but `{}` isn't used",
+                    name, template
+                )))
+            }
+            _ => {}
+        }
+    }
+    if row.is_empty() && column.is_empty() {
+        return Err(Error::Validation(
+            "a repeat needs row or column fields".to_string(),
+        ));
+    }
+    let channels = [("row", &row), ("column", &column)]
+        .iter()
+        .filter(|(_, fields)| !fields.is_empty())
+        .map(|(channel, _)| *channel)
+        .chain(Some("facet"))
+        .collect::<Vec<_>>();
+    check_channels("repeated chart", &json(&chart), &channels)?;
+    let some = |fields: Vec<String>| Some(fields).filter(|fields| !fields.is_empty());
+    let spec = take_spec_class(&mut chart)?;
+    assemble(top_level, |repeated| {
+        repeated.repeat = Some(RepeatUnion::RepeatMapping(RepeatMapping {
+            row: some(row),
+            column: some(column),
+        }));
+        repeated.spec = Some(spec);
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn chart(spec: Value) -> Vegalite {
+        crate::with_big_stack(move || serde_json::from_value(spec).unwrap())
+    }
+
+    /// The json of a composed chart
+    fn composed(chart: Result<Vegalite, Error>) -> Value {
+        json(&chart.unwrap())
+    }
+
+    #[test]
+    fn test_encoding_conversions() {
+        let encoding: Encoding =
+            serde_json::from_value(json!({"x": {"field": "a", "type": "quantitative"}})).unwrap();
+        let layer_encoding = LayerEncoding::try_from(encoding).unwrap();
+        assert_eq!(
+            json(&Encoding::from(layer_encoding)),
+            json!({"x": {"field": "a", "type": "quantitative"}})
+        );
+        let faceted: Encoding =
+            serde_json::from_value(json!({"row": {"field": "b", "type": "nominal"}})).unwrap();
+        assert!(LayerEncoding::try_from(faceted).is_err());
+    }
+
+    fn line() -> Vegalite {
+        chart(json!({
+            "$schema": "https://vega.github.io/schema/vega-lite/v3.json",
+            "data": {"values": [{"a": 1, "b": 2}, {"a": 2, "b": 4}]},
+            "mark": "line",
+            "encoding": {
+                "x": {"field": "a", "type": "quantitative"},
+                "y": {"field": "b", "type": "quantitative"}
+            }
+        }))
+    }
+
+    fn points() -> Vegalite {
+        chart(json!({
+            "config": {"background": "white"},
+            "data": {"values": [{"a": 1, "b": 2}, {"a": 2, "b": 4}]},
+            "mark": "point",
+            "encoding": {
+                "x": {"field": "a", "type": "quantitative"},
+                "y": {"field": "b", "type": "quantitative"},
+                "color": {"value": "red"}
+            }
+        }))
+    }
+
+    #[test]
+    fn test_layer() {
+        assert_eq!(
+            composed(layer(vec![line(), points()])),
+            json!({
+                "$schema": "https://vega.github.io/schema/vega-lite/v3.json",
+                "config": {"background": "white"},
+                "data": {"values": [{"a": 1, "b": 2}, {"a": 2, "b": 4}]},
+                "encoding": {
+                    "x": {"field": "a", "type": "quantitative"},
+                    "y": {"field": "b", "type": "quantitative"}
+                },
+                "layer": [
+                    {"mark": "line"},
+                    {"mark": "point", "encoding": {"color": {"value": "red"}}}
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn test_add_layers() {
+        let layered = composed(line() + points());
+        assert_eq!(
+            layered["layer"],
+            json!([
+                {"mark": "line"},
+                {"mark": "point", "encoding": {"color": {"value": "red"}}}
+            ])
+        );
+    }
+
+    /// Layers that could be layered, as an operand
+    fn layered() -> Result<Vegalite, Error> {
+        Ok(chart(json!({
+            "data": {"values": [{"a": 1, "b": 2}, {"a": 2, "b": 4}]},
+            "encoding": {"x": {"field": "a", "type": "quantitative"}},
+            "layer": [{"mark": "line"}, {"mark": "point"}]
+        })))
+    }
+
+    fn rule() -> Vegalite {
+        chart(json!({
+            "data": {"values": [{"b": 3}]},
+            "mark": "rule",
+            "encoding": {"y": {"field": "b", "type": "quantitative"}}
+        }))
+    }
+
+    #[test]
+    fn test_add_to_layers() {
+        let with_rule = composed(layered() + rule());
+        assert_eq!(with_rule["layer"].as_array().unwrap().len(), 3);
+        assert_eq!(with_rule["layer"][2]["data"], json!({"values": [{"b": 3}]}));
+        assert!(with_rule["layer"][0]["data"].is_object());
+    }
+
+    #[test]
+    fn test_layer_shared_encoding() {
+        let point = chart(json!({
+            "data": {"values": [{"a": 1}]},
+            "mark": "point",
+            "encoding": {"x": {"field": "a", "type": "quantitative"}}
+        }));
+        let layered = composed(layer(vec![point.clone(), point]));
+        assert_eq!(
+            layered["encoding"],
+            json!({"x": {"field": "a", "type": "quantitative"}})
+        );
+        assert_eq!(
+            layered["layer"],
+            json!([{"mark": "point"}, {"mark": "point"}])
+        );
+    }
+
+    #[test]
+    fn test_layer_errors() {
+        let faceted = chart(json!({
+            "facet": {"row": {"field": "a", "type": "nominal"}},
+            "spec": {"mark": "point"}
+        }));
+        assert!(layer(vec![points(), faceted]).is_err());
+        assert!(layer(vec![]).is_err());
+    }
+
+    #[test]
+    fn test_layer_different_config() {
+        let other = chart(json!({"mark": "line", "config": {"background": "black"}}));
+        assert_eq!(
+            layer(vec![points(), other]).unwrap_err().to_string(),
+            "invalid specification: layer 1: charts with different `config` can't be composed"
+        );
+    }
+
+    #[test]
+    fn test_spec_conversions() {
+        let point = chart(json!({"mark": "point", "width": 100.0}));
+        let spec = Spec::try_from(point.clone()).unwrap();
+        assert_eq!(json(&Vegalite::from(spec)), json(&point));
+        let spec_class = SpecClass::try_from(point).unwrap();
+        assert_eq!(json(&spec_class), json!({"mark": "point", "width": 100.0}));
+    }
+
+    #[test]
+    fn test_spec_conversion_errors() {
+        let configured = chart(json!({"mark": "point", "config": {"background": "white"}}));
+        assert!(Spec::try_from(configured).is_err());
+    }
+
+    fn point() -> Vegalite {
+        chart(json!({
+            "$schema": "https://vega.github.io/schema/vega-lite/v3.json",
+            "mark": "point"
+        }))
+    }
+
+    fn bar() -> Vegalite {
+        chart(json!({"mark": "bar", "datasets": {"cars": [{"a": 1}]}}))
+    }
+
+    #[test]
+    fn test_concat() {
+        assert_eq!(
+            composed(point() | bar()),
+            json!({
+                "$schema": "https://vega.github.io/schema/vega-lite/v3.json",
+                "datasets": {"cars": [{"a": 1}]},
+                "hconcat": [{"mark": "point"}, {"mark": "bar"}]
+            })
+        );
+        assert!(hconcat(vec![]).is_err());
+    }
+
+    #[test]
+    fn test_concat_row() {
+        let row = chart(json!({"hconcat": [{"mark": "point"}, {"mark": "bar"}]}));
+        assert_eq!(
+            composed(Ok(row) | chart(json!({"mark": "line"}))),
+            json!({"hconcat": [{"mark": "point"}, {"mark": "bar"}, {"mark": "line"}]})
+        );
+    }
+
+    #[test]
+    fn test_concat_grid() {
+        let row = chart(json!({"hconcat": [{"mark": "point"}, {"mark": "bar"}]}));
+        let grid = composed(row & chart(json!({"mark": "line"})));
+        assert_eq!(grid["vconcat"][0]["hconcat"][1], json!({"mark": "bar"}));
+        assert_eq!(grid["vconcat"][1], json!({"mark": "line"}));
+    }
+
+    #[test]
+    fn test_concat_columns() {
+        let wrapped = composed(concat(
+            vec![point(), bar(), chart(json!({"mark": "line"}))],
+            Some(2),
+        ));
+        assert_eq!(wrapped["columns"], json!(2.0));
+        assert_eq!(wrapped["concat"].as_array().unwrap().len(), 3);
+    }
+
+    fn origin() -> FacetFieldDef {
+        serde_json::from_value(json!({"field": "Origin", "type": "nominal"})).unwrap()
+    }
+
+    #[test]
+    fn test_facet() {
+        let point = chart(json!({
+            "config": {"background": "white"},
+            "data": {"url": "data/cars.json"},
+            "transform": [{"filter": "datum.Horsepower > 100"}],
+            "mark": "point",
+            "encoding": {"x": {"field": "Horsepower", "type": "quantitative"}}
+        }));
+        assert_eq!(
+            composed(facet(point.clone(), Some(origin()), None)),
+            json!({
+                "config": {"background": "white"},
+                "data": {"url": "data/cars.json"},
+                "transform": [{"filter": "datum.Horsepower > 100"}],
+                "facet": {"row": {"field": "Origin", "type": "nominal"}},
+                "spec": {
+                    "mark": "point",
+                    "encoding": {"x": {"field": "Horsepower", "type": "quantitative"}}
+                }
+            })
+        );
+        assert!(facet(point, None, None).is_err());
+    }
+
+    #[test]
+    fn test_facet_errors() {
+        let colored = chart(json!({
+            "mark": "point",
+            "encoding": {"column": {"field": "Cylinders", "type": "ordinal"}}
+        }));
+        assert!(facet(colored, None, Some(origin())).is_err());
+    }
+
+    fn histogram() -> Vegalite {
+        chart(json!({
+            "datasets": {"cars": [{"a": 1, "b": 2}]},
+            "data": {"name": "cars"},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "{column}", "type": "quantitative", "bin": true},
+                "y": {"aggregate": "count", "type": "quantitative"}
+            }
+        }))
+    }
+
+    #[test]
+    fn test_repeat() {
+        let repeated = repeat(histogram(), vec![], vec!["a".to_string(), "b".to_string()]);
+        assert_eq!(
+            composed(repeated),
+            json!({
+                "datasets": {"cars": [{"a": 1, "b": 2}]},
+                "repeat": {"column": ["a", "b"]},
+                "spec": {
+                    "data": {"name": "cars"},
+                    "mark": "bar",
+                    "encoding": {
+                        "x": {"field": {"repeat": "column"}, "type": "quantitative", "bin": true},
+                        "y": {"aggregate": "count", "type": "quantitative"}
+                    }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_repeat_unused_fields() {
+        assert!(repeat(histogram(), vec!["a".to_string()], vec![]).is_err());
+    }
+
+    #[test]
+    fn test_repeat_no_fields() {
+        assert!(repeat(histogram(), vec![], vec![]).is_err());
+    }
+
+    #[test]
+    fn test_repeat_repeated_channel() {
+        let columns = chart(json!({
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "{column}", "type": "quantitative"},
+                "column": {"field": "c", "type": "nominal"}
+            }
+        }));
+        assert!(repeat(columns, vec![], vec!["a".to_string()]).is_err());
+    }
+}
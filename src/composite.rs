@@ -0,0 +1,354 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::removable_value::RemovableValue;
+use crate::schema::*;
+
+/// How far the whiskers of a box plot extend
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BoxPlotExtent {
+    /// To the minimum and maximum values, without outliers
+    MinMax,
+    /// To the furthest values within this many interquartile ranges from the box
+    Iqr(f64),
+}
+
+/// The interval drawn by an error bar or an error band
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorExtent {
+    /// The 95% bootstrapped confidence interval of the mean
+    Ci,
+    /// The interquartile range, from the first to the third quartile
+    Iqr,
+    /// The standard error around the mean
+    Stderr,
+    /// The standard deviation around the mean
+    Stdev,
+}
+
+impl From<BoxPlotExtent> for BoxPlotDefExtent {
+    fn from(v: BoxPlotExtent) -> Self {
+        match v {
+            BoxPlotExtent::MinMax => BoxPlotDefExtent::Enum(ExtentExtent::MinMax),
+            BoxPlotExtent::Iqr(iqr) => BoxPlotDefExtent::Double(iqr),
+        }
+    }
+}
+
+impl From<ErrorExtent> for BoxPlotDefExtent {
+    fn from(v: ErrorExtent) -> Self {
+        BoxPlotDefExtent::Enum(match v {
+            ErrorExtent::Ci => ExtentExtent::Ci,
+            ErrorExtent::Iqr => ExtentExtent::Iqr,
+            ErrorExtent::Stderr => ExtentExtent::Stderr,
+            ErrorExtent::Stdev => ExtentExtent::Stdev,
+        })
+    }
+}
+
+/// Setters shared by the composite marks
+macro_rules! composite_mark {
+    ($(#[$doc:meta])* $name:ident, $mark:ident { $($(#[$part_doc:meta])* $part:ident: $field:ident,)* }) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone)]
+        pub struct $name(MarkDefClass);
+
+        impl $name {
+            /// The mark with its default options
+            pub fn new() -> Self {
+                $name(MarkDefClass {
+                    def_type: Some(Mark::$mark),
+                    ..Default::default()
+                })
+            }
+
+            /// The orientation of the mark, guessed from the types of the `x` and `y` fields by
+            /// default
+            pub fn orient(mut self, orient: Orientation) -> Self {
+                self.0.orient = Some(orient);
+                self
+            }
+
+            /// The color of the mark
+            pub fn color(mut self, color: impl Into<String>) -> Self {
+                self.0.color = Some(color.into());
+                self
+            }
+
+            /// The opacity of the mark, between 0 and 1
+            pub fn opacity(mut self, opacity: f64) -> Self {
+                self.0.opacity = Some(opacity);
+                self
+            }
+
+            /// Whether the mark is clipped to the enclosing group
+            pub fn clip(mut self, clip: bool) -> Self {
+                self.0.clip = Some(clip);
+                self
+            }
+
+            $(
+                $(#[$part_doc])*
+                pub fn $part(mut self, part: impl Into<DefBox>) -> Self {
+                    self.0.$field = Some(part.into());
+                    self
+                }
+            )*
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl From<$name> for MarkDefClass {
+            fn from(v: $name) -> Self {
+                v.0
+            }
+        }
+
+        impl From<$name> for AnyMark {
+            fn from(v: $name) -> Self {
+                AnyMark::MarkDefClass(v.0)
+            }
+        }
+    };
+}
+
+composite_mark!(
+    /// A [`Mark::Boxplot`](enum.Mark.html#variant.Boxplot), with only the options of box plots
+    BoxPlot,
+    Boxplot {
+        /// Show the box, or its mark properties
+        body: def_box,
+        /// Show the median tick, or its mark properties
+        median: median,
+        /// Show the outliers, or their mark properties
+        outliers: outliers,
+        /// Show the whiskers, or their mark properties
+        rule: rule,
+        /// Show ticks at the ends of the whiskers, or their mark properties
+        ticks: ticks,
+    }
+);
+
+composite_mark!(
+    /// A [`Mark::Errorbar`](enum.Mark.html#variant.Errorbar), with only the options of error
+    /// bars
+    ErrorBar,
+    Errorbar {
+        /// Show the bar, or its mark properties
+        rule: rule,
+        /// Show ticks at the ends of the bar, or their mark properties
+        ticks: ticks,
+    }
+);
+
+composite_mark!(
+    /// A [`Mark::Errorband`](enum.Mark.html#variant.Errorband), with only the options of error
+    /// bands
+    ErrorBand,
+    Errorband {
+        /// Show the band, or its mark properties
+        band: band,
+        /// Show the borders of the band, or their mark properties
+        borders: borders,
+    }
+);
+
+impl BoxPlot {
+    /// How far the whiskers extend, 1.5 interquartile ranges by default
+    pub fn extent(mut self, extent: BoxPlotExtent) -> Self {
+        self.0.extent = Some(extent.into());
+        self
+    }
+
+    /// The width of the box and of the median tick
+    pub fn size(mut self, size: f64) -> Self {
+        self.0.size = Some(size);
+        self
+    }
+}
+
+impl ErrorBar {
+    /// The interval drawn, the standard error by default
+    pub fn extent(mut self, extent: ErrorExtent) -> Self {
+        self.0.extent = Some(extent.into());
+        self
+    }
+}
+
+impl ErrorBand {
+    /// The interval drawn, the standard error by default
+    pub fn extent(mut self, extent: ErrorExtent) -> Self {
+        self.0.extent = Some(extent.into());
+        self
+    }
+
+    /// How the borders of the band are interpolated
+    pub fn interpolate(mut self, interpolate: Interpolate) -> Self {
+        self.0.interpolate = Some(interpolate);
+        self
+    }
+
+    /// The tension of the `bundle`, `cardinal` and `catmull-rom` interpolations
+    pub fn tension(mut self, tension: f64) -> Self {
+        self.0.tension = Some(tension);
+        self
+    }
+}
+
+/// A layer of a mean with confidence interval chart
+fn mean_layer(mark: impl Into<AnyMark>, y: &str, aggregate: bool) -> LayerSpec {
+    LayerSpec {
+        mark: Some(mark.into()),
+        encoding: Some(LayerEncoding {
+            y: Some(YClass {
+                field: Some(Field::String(y.to_string())),
+                def_type: Some(StandardType::Quantitative),
+                aggregate: if aggregate {
+                    Some(Aggregate::Enum(AggregateOp::Mean))
+                } else {
+                    None
+                },
+                title: if aggregate {
+                    RemovableValue::Default
+                } else {
+                    RemovableValue::Specified(format!("Mean of {} (95% CIs)", y))
+                },
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// A chart of the mean of the `y` field for each value of `x`, as a line within its 95%
+/// confidence interval band
+pub fn mean_with_ci_band(data: impl Into<RemovableValue<UrlData>>, x: XClass, y: &str) -> Vegalite {
+    mean_with_ci(
+        data,
+        x,
+        Mark::Line,
+        ErrorBand::new().extent(ErrorExtent::Ci),
+        y,
+    )
+}
+
+/// A chart of the mean of the `y` field for each value of `x`, as a point within its 95%
+/// confidence interval bar
+pub fn mean_with_ci_bars(data: impl Into<RemovableValue<UrlData>>, x: XClass, y: &str) -> Vegalite {
+    mean_with_ci(
+        data,
+        x,
+        Mark::Point,
+        ErrorBar::new().extent(ErrorExtent::Ci),
+        y,
+    )
+}
+
+fn mean_with_ci(
+    data: impl Into<RemovableValue<UrlData>>,
+    x: XClass,
+    mean: Mark,
+    interval: impl Into<AnyMark>,
+    y: &str,
+) -> Vegalite {
+    Vegalite {
+        data: data.into(),
+        encoding: Some(Encoding {
+            x: Some(x),
+            ..Default::default()
+        }),
+        layer: Some(vec![
+            mean_layer(mean, y, true),
+            mean_layer(interval, y, false),
+        ]),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_composite_marks() {
+        let boxplot = BoxPlot::new()
+            .extent(BoxPlotExtent::MinMax)
+            .median(MarkConfig {
+                color: Some("black".to_string()),
+                ..Default::default()
+            })
+            .outliers(false)
+            .size(20.0);
+        assert_eq!(
+            serde_json::to_value(AnyMark::from(boxplot)).unwrap(),
+            json!({
+                "type": "boxplot",
+                "extent": "min-max",
+                "median": {"color": "black"},
+                "outliers": false,
+                "size": 20.0
+            })
+        );
+        let errorbar = ErrorBar::new()
+            .extent(ErrorExtent::Stdev)
+            .orient(Orientation::Horizontal)
+            .ticks(true);
+        assert_eq!(
+            serde_json::to_value(AnyMark::from(errorbar)).unwrap(),
+            json!({"type": "errorbar", "extent": "stdev", "orient": "horizontal", "ticks": true})
+        );
+    }
+
+    #[test]
+    fn test_mean_with_ci() {
+        let data = UrlData {
+            url: Some("data/cars.json".to_string()),
+            ..Default::default()
+        };
+        let x = XClass {
+            field: Some(Field::String("Year".to_string())),
+            def_type: Some(StandardType::Temporal),
+            time_unit: Some(TimeUnit::Year),
+            ..Default::default()
+        };
+        assert_eq!(
+            serde_json::to_value(mean_with_ci_band(data, x, "Miles_per_Gallon")).unwrap(),
+            json!({
+                "data": {"url": "data/cars.json"},
+                "encoding": {"x": {"field": "Year", "type": "temporal", "timeUnit": "year"}},
+                "layer": [
+                    {
+                        "mark": "line",
+                        "encoding": {"y": {
+                            "field": "Miles_per_Gallon",
+                            "type": "quantitative",
+                            "aggregate": "mean"
+                        }}
+                    },
+                    {
+                        "mark": {"type": "errorband", "extent": "ci"},
+                        "encoding": {"y": {
+                            "field": "Miles_per_Gallon",
+                            "type": "quantitative",
+                            "title": "Mean of Miles_per_Gallon (95% CIs)"
+                        }}
+                    }
+                ]
+            })
+        );
+    }
+}
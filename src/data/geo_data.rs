@@ -0,0 +1,127 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::error::Error;
+use crate::DataFormatBuilder;
+use crate::DataFormatType;
+use crate::RemovableValue;
+use crate::UrlData;
+use crate::UrlDataBuilder;
+use crate::UrlDataInlineDataset;
+use geojson::{Feature, FeatureCollection, JsonObject};
+use serde::Serialize;
+use std::convert::TryFrom;
+
+/// Inline data for a GeoJSON feature collection, each feature being a datum
+impl From<FeatureCollection> for UrlData {
+    fn from(v: FeatureCollection) -> Self {
+        let collection = JsonObject::from(&v)
+            .into_iter()
+            .map(|(key, value)| (key, Some(value)))
+            .collect();
+        UrlDataBuilder::default()
+            .values(UrlDataInlineDataset::AnythingMap(collection))
+            .format(
+                DataFormatBuilder::default()
+                    .data_format_type(DataFormatType::Json)
+                    .property("features")
+                    .build()
+                    .unwrap(),
+            )
+            .build()
+            .unwrap()
+    }
+}
+
+impl From<FeatureCollection> for RemovableValue<UrlData> {
+    fn from(v: FeatureCollection) -> Self {
+        RemovableValue::Specified(v.into())
+    }
+}
+
+/// Inline data for a geometry, as a collection of a single feature without properties
+impl From<geo_types::Geometry<f64>> for UrlData {
+    fn from(v: geo_types::Geometry<f64>) -> Self {
+        Some(Feature::from(geojson::Value::from(&v)))
+            .into_iter()
+            .collect::<FeatureCollection>()
+            .into()
+    }
+}
+
+impl From<geo_types::Geometry<f64>> for RemovableValue<UrlData> {
+    fn from(v: geo_types::Geometry<f64>) -> Self {
+        RemovableValue::Specified(v.into())
+    }
+}
+
+/// Inline data for a geometry collection, each geometry being a feature without properties
+impl From<geo_types::GeometryCollection<f64>> for UrlData {
+    fn from(v: geo_types::GeometryCollection<f64>) -> Self {
+        FeatureCollection::from(&v).into()
+    }
+}
+
+impl From<geo_types::GeometryCollection<f64>> for RemovableValue<UrlData> {
+    fn from(v: geo_types::GeometryCollection<f64>) -> Self {
+        RemovableValue::Specified(v.into())
+    }
+}
+
+/// Inline data for geometries with their properties, available as `properties.<name>` fields;
+/// fails when the properties don't serialize to json objects
+impl<P> TryFrom<Vec<(geo_types::Geometry<f64>, P)>> for UrlData
+where
+    P: Serialize,
+{
+    type Error = Error;
+
+    fn try_from(v: Vec<(geo_types::Geometry<f64>, P)>) -> Result<Self, Self::Error> {
+        Ok(v.into_iter()
+            .map(|(geometry, properties)| {
+                let mut feature = Feature::from(geojson::Value::from(&geometry));
+                match serde_json::to_value(properties)? {
+                    serde_json::Value::Object(properties) => feature.properties = Some(properties),
+                    _ => {
+                        return Err(Error::Validation(
+                            "feature properties must serialize to a json object".to_string(),
+                        ))
+                    }
+                }
+                Ok(feature)
+            })
+            .collect::<Result<FeatureCollection, Error>>()?
+            .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geo_types::{point, Geometry};
+    use serde_json::json;
+
+    #[test]
+    fn test_geometries_with_properties() {
+        let point: Geometry<f64> = point!(x: 1.0, y: 2.0).into();
+        let data = UrlData::try_from(vec![(point.clone(), json!({"name": "a"}))]).unwrap();
+        let data = serde_json::to_value(&data).unwrap();
+        assert_eq!(
+            data["values"]["features"][0]["properties"],
+            json!({"name": "a"})
+        );
+
+        assert!(matches!(
+            UrlData::try_from(vec![(point, 3)]),
+            Err(Error::Validation(_))
+        ));
+    }
+}
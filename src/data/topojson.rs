@@ -0,0 +1,662 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Encoding of GeoJSON features as TopoJSON, where borders shared by several geometries are
+//! stored once, following [topojson-server](https://github.com/topojson/topojson-server).
+
+use crate::DataFormatBuilder;
+use crate::DataFormatType;
+use crate::UrlData;
+use crate::UrlDataBuilder;
+use crate::UrlDataInlineDataset;
+use geojson::{FeatureCollection, Geometry};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+
+type Point = (f64, f64);
+type Key = (u64, u64);
+
+fn key(point: Point) -> Key {
+    // `+ 0.0` turns -0 into 0
+    ((point.0 + 0.0).to_bits(), (point.1 + 0.0).to_bits())
+}
+
+/// An encoder of features into a TopoJSON object, used as inline data of a chart
+///
+/// ```
+/// use geojson::FeatureCollection;
+/// use vega_lite_3::*;
+///
+/// let counties: FeatureCollection =
+///     serde_json::from_str(r#"{"type": "FeatureCollection", "features": []}"#).unwrap();
+/// let data = TopoJson::new("counties")
+///     .quantization(10_000)
+///     .simplify(0.01)
+///     .encode(&counties);
+/// ```
+#[derive(Debug, Clone)]
+pub struct TopoJson {
+    object: String,
+    quantization: Option<u32>,
+    tolerance: Option<f64>,
+    mesh: bool,
+}
+
+/// A geometry whose lines and rings are replaced by their index in the list of lines
+enum Shape {
+    Null,
+    Point(Point),
+    MultiPoint(Vec<Point>),
+    LineString(usize),
+    MultiLineString(Vec<usize>),
+    Polygon(Vec<usize>),
+    MultiPolygon(Vec<Vec<usize>>),
+    GeometryCollection(Vec<Shape>),
+}
+
+/// A line, or a ring without its closing point
+struct Line {
+    points: Vec<Point>,
+    ring: bool,
+}
+
+/// The bounding box of the coordinates of a geometry
+fn extend_bbox(bbox: &mut [f64; 4], geometry: &Geometry) {
+    let mut extend = |position: &Vec<f64>| {
+        bbox[0] = bbox[0].min(position[0]);
+        bbox[1] = bbox[1].min(position[1]);
+        bbox[2] = bbox[2].max(position[0]);
+        bbox[3] = bbox[3].max(position[1]);
+    };
+    match &geometry.value {
+        geojson::Value::Point(point) => extend(point),
+        geojson::Value::MultiPoint(points) | geojson::Value::LineString(points) => {
+            points.iter().for_each(extend)
+        }
+        geojson::Value::MultiLineString(lines) | geojson::Value::Polygon(lines) => {
+            lines.iter().flatten().for_each(extend)
+        }
+        geojson::Value::MultiPolygon(polygons) => {
+            polygons.iter().flatten().flatten().for_each(extend)
+        }
+        geojson::Value::GeometryCollection(geometries) => geometries
+            .iter()
+            .for_each(|geometry| extend_bbox(bbox, geometry)),
+    }
+}
+
+/// The rounding of coordinates to a grid, from the origin of the bounding box
+struct Quantize {
+    scale: Option<(f64, f64)>,
+    origin: Point,
+}
+
+impl Quantize {
+    fn apply(&self, position: &[f64]) -> Point {
+        match self.scale {
+            Some((kx, ky)) => (
+                ((position[0] - self.origin.0) * kx).round(),
+                ((position[1] - self.origin.1) * ky).round(),
+            ),
+            None => (position[0], position[1]),
+        }
+    }
+
+    fn invert(&self, point: &Point) -> Point {
+        match self.scale {
+            Some((kx, ky)) => (point.0 / kx + self.origin.0, point.1 / ky + self.origin.1),
+            None => *point,
+        }
+    }
+}
+
+/// The lines and rings of the features
+struct Extractor {
+    quantize: Quantize,
+    lines: Vec<Line>,
+}
+
+impl Extractor {
+    fn line(&mut self, positions: &[Vec<f64>], ring: bool) -> usize {
+        let mut points = positions
+            .iter()
+            .map(|position| self.quantize.apply(position))
+            .collect::<Vec<_>>();
+        points.dedup_by_key(|point| key(*point));
+        if ring && points.len() > 1 && key(points[0]) == key(points[points.len() - 1]) {
+            points.pop();
+        } else if !ring && points.len() == 1 {
+            points.push(points[0]);
+        }
+        self.lines.push(Line { points, ring });
+        self.lines.len() - 1
+    }
+
+    fn shape(&mut self, geometry: Option<&Geometry>) -> Shape {
+        let geometry = match geometry {
+            Some(geometry) => geometry,
+            None => return Shape::Null,
+        };
+        match &geometry.value {
+            geojson::Value::Point(point) => Shape::Point(self.quantize.apply(point)),
+            geojson::Value::MultiPoint(points) => Shape::MultiPoint(
+                points
+                    .iter()
+                    .map(|point| self.quantize.apply(point))
+                    .collect(),
+            ),
+            geojson::Value::LineString(line) => Shape::LineString(self.line(line, false)),
+            geojson::Value::MultiLineString(lines) => {
+                Shape::MultiLineString(lines.iter().map(|line| self.line(line, false)).collect())
+            }
+            geojson::Value::Polygon(rings) => {
+                Shape::Polygon(rings.iter().map(|ring| self.line(ring, true)).collect())
+            }
+            geojson::Value::MultiPolygon(polygons) => Shape::MultiPolygon(
+                polygons
+                    .iter()
+                    .map(|rings| rings.iter().map(|ring| self.line(ring, true)).collect())
+                    .collect(),
+            ),
+            geojson::Value::GeometryCollection(geometries) => Shape::GeometryCollection(
+                geometries
+                    .iter()
+                    .map(|geometry| self.shape(Some(geometry)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// The points where lines start, end, or where lines sharing a point diverge
+fn junctions(lines: &[Line]) -> HashSet<Key> {
+    let mut neighbors = HashMap::new();
+    let mut junctions = HashSet::new();
+    for line in lines {
+        let points = &line.points;
+        let n = points.len();
+        if !line.ring {
+            junctions.insert(key(points[0]));
+            junctions.insert(key(points[n - 1]));
+        }
+        let interior = if line.ring { 0..n } else { 1..n - 1 };
+        for i in interior {
+            let (previous, next) = (key(points[(i + n - 1) % n]), key(points[(i + 1) % n]));
+            let pair = if previous <= next {
+                (previous, next)
+            } else {
+                (next, previous)
+            };
+            let point = key(points[i]);
+            match neighbors.get(&point) {
+                Some(seen) if *seen != pair => {
+                    junctions.insert(point);
+                }
+                Some(_) => {}
+                None => {
+                    neighbors.insert(point, pair);
+                }
+            }
+        }
+    }
+    junctions
+}
+
+/// Split a line at its junctions
+fn split(points: &[Point], junctions: &HashSet<Key>) -> Vec<Vec<Point>> {
+    let mut arcs = vec![];
+    let mut arc = vec![points[0]];
+    for point in &points[1..] {
+        arc.push(*point);
+        if junctions.contains(&key(*point)) {
+            arcs.push(std::mem::replace(&mut arc, vec![*point]));
+        }
+    }
+    if arc.len() > 1 {
+        arcs.push(arc);
+    }
+    arcs
+}
+
+/// The arcs of a topology, each arc being stored once whatever its direction
+#[derive(Default)]
+struct Arcs {
+    arcs: Vec<Vec<Point>>,
+    index: HashMap<(bool, Vec<Key>), usize>,
+}
+
+/// The keys of a ring without its closing point, starting from its smallest point
+fn ring_keys(ring: &[Point]) -> Vec<Key> {
+    let keys = ring[..ring.len() - 1]
+        .iter()
+        .map(|point| key(*point))
+        .collect::<Vec<_>>();
+    let start = (0..keys.len()).min_by_key(|i| keys[*i]).unwrap_or(0);
+    keys[start..]
+        .iter()
+        .chain(&keys[..start])
+        .cloned()
+        .collect()
+}
+
+impl Arcs {
+    /// The index of an arc, `!index` when the arc is stored in the other direction; a ring
+    /// without junctions is the same arc whatever its first point
+    fn add(&mut self, arc: Vec<Point>, free_ring: bool) -> i64 {
+        let reversed = arc.iter().rev().cloned().collect::<Vec<_>>();
+        let keys = |arc: &[Point]| {
+            if free_ring {
+                ring_keys(arc)
+            } else {
+                arc.iter().map(|point| key(*point)).collect()
+            }
+        };
+        let (forward, backward) = ((free_ring, keys(&arc)), (free_ring, keys(&reversed)));
+        if let Some(index) = self.index.get(&forward) {
+            return *index as i64;
+        }
+        if let Some(index) = self.index.get(&backward) {
+            return !(*index as i64);
+        }
+        self.index.insert(forward, self.arcs.len());
+        self.arcs.push(arc);
+        self.arcs.len() as i64 - 1
+    }
+
+    /// Cut a line into arcs at its junctions, rings starting at their first junction
+    fn cut(&mut self, line: &Line, junctions: &HashSet<Key>) -> Vec<i64> {
+        let points = &line.points;
+        if !line.ring {
+            return split(points, junctions)
+                .into_iter()
+                .map(|arc| self.add(arc, false))
+                .collect();
+        }
+        match points
+            .iter()
+            .position(|point| junctions.contains(&key(*point)))
+        {
+            Some(start) => {
+                let rotated = points[start..]
+                    .iter()
+                    .chain(&points[..=start])
+                    .cloned()
+                    .collect::<Vec<_>>();
+                split(&rotated, junctions)
+                    .into_iter()
+                    .map(|arc| self.add(arc, false))
+                    .collect()
+            }
+            None => {
+                let mut ring = points.clone();
+                ring.push(points[0]);
+                vec![self.add(ring, true)]
+            }
+        }
+    }
+}
+
+/// The distance of a point to the segment between two points
+fn distance(point: Point, start: Point, end: Point) -> f64 {
+    let (dx, dy) = (end.0 - start.0, end.1 - start.1);
+    let length = dx * dx + dy * dy;
+    let t = if length > 0.0 {
+        (((point.0 - start.0) * dx + (point.1 - start.1) * dy) / length).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    ((point.0 - start.0 - t * dx).powi(2) + (point.1 - start.1 - t * dy).powi(2)).sqrt()
+}
+
+/// The point between `start` and `end` farthest from the segment joining them, with its
+/// distance
+fn farthest(points: &[Point], start: usize, end: usize) -> Option<(usize, f64)> {
+    (start + 1..end)
+        .map(|i| (i, distance(points[i], points[start], points[end])))
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Mark the points to keep between `start` and `end` with the Douglas-Peucker algorithm
+fn douglas_peucker(points: &[Point], start: usize, end: usize, tolerance: f64, keep: &mut [bool]) {
+    if let Some((i, distance)) = farthest(points, start, end) {
+        if distance > tolerance {
+            keep[i] = true;
+            douglas_peucker(points, start, i, tolerance, keep);
+            douglas_peucker(points, i, end, tolerance, keep);
+        }
+    }
+}
+
+/// Simplify an arc, closed arcs keeping at least four distinct points to stay rings
+fn simplify(arc: &[Point], real: &[Point], tolerance: f64) -> Vec<Point> {
+    let n = arc.len();
+    let mut keep = vec![false; n];
+    keep[0] = true;
+    keep[n - 1] = true;
+    if key(arc[0]) == key(arc[n - 1]) {
+        // split the ring at its point farthest from the start, and keep the farthest point of
+        // each half whatever the tolerance
+        if let Some((far, _)) = farthest(real, 0, n - 1) {
+            keep[far] = true;
+            for (start, end) in [(0, far), (far, n - 1)] {
+                if let Some((i, _)) = farthest(real, start, end) {
+                    keep[i] = true;
+                    douglas_peucker(real, start, i, tolerance, &mut keep);
+                    douglas_peucker(real, i, end, tolerance, &mut keep);
+                }
+            }
+        }
+    } else {
+        douglas_peucker(real, 0, n - 1, tolerance, &mut keep);
+    }
+    arc.iter()
+        .zip(keep)
+        .filter(|(_, keep)| *keep)
+        .map(|(point, _)| *point)
+        .collect()
+}
+
+impl TopoJson {
+    /// An encoder of features into the TopoJSON object with this name
+    pub fn new(object: &str) -> Self {
+        TopoJson {
+            object: object.to_string(),
+            quantization: None,
+            tolerance: None,
+            mesh: false,
+        }
+    }
+
+    /// Round the coordinates to a grid of `quantization` by `quantization` points, and delta
+    /// encode the arcs; 10,000 to 100,000 keeps maps accurate
+    pub fn quantization(mut self, quantization: u32) -> Self {
+        self.quantization = Some(quantization.max(2));
+        self
+    }
+
+    /// Remove the points of the arcs closer than `tolerance` to the simplified arcs, in the
+    /// units of the coordinates; shared borders are simplified the same way for all features
+    pub fn simplify(mut self, tolerance: f64) -> Self {
+        self.tolerance = Some(tolerance);
+        self
+    }
+
+    /// Draw the mesh of the borders of the features, instead of the features
+    pub fn mesh(mut self, mesh: bool) -> Self {
+        self.mesh = mesh;
+        self
+    }
+
+    /// The TopoJSON topology of features
+    pub fn topology(&self, features: &FeatureCollection) -> Map<String, Value> {
+        let mut bbox = [
+            f64::INFINITY,
+            f64::INFINITY,
+            f64::NEG_INFINITY,
+            f64::NEG_INFINITY,
+        ];
+        for feature in &features.features {
+            if let Some(geometry) = &feature.geometry {
+                extend_bbox(&mut bbox, geometry);
+            }
+        }
+        if bbox[0] > bbox[2] {
+            bbox = [0.0; 4];
+        }
+        let scale = |n: u32, min: f64, max: f64| {
+            if max > min {
+                (n - 1) as f64 / (max - min)
+            } else {
+                1.0
+            }
+        };
+        let quantize = Quantize {
+            scale: self
+                .quantization
+                .map(|n| (scale(n, bbox[0], bbox[2]), scale(n, bbox[1], bbox[3]))),
+            origin: (bbox[0], bbox[1]),
+        };
+        let quantized = quantize.scale.is_some();
+        let mut extractor = Extractor {
+            quantize,
+            lines: vec![],
+        };
+        let shapes = features
+            .features
+            .iter()
+            .map(|feature| extractor.shape(feature.geometry.as_ref()))
+            .collect::<Vec<_>>();
+        let (quantize, lines) = (extractor.quantize, extractor.lines);
+
+        let junctions = junctions(&lines);
+        let mut arcs = Arcs::default();
+        let line_arcs = lines
+            .iter()
+            .map(|line| arcs.cut(line, &junctions))
+            .collect::<Vec<_>>();
+
+        let coordinates = |point: &Point| {
+            if quantized {
+                json!([point.0 as i64, point.1 as i64])
+            } else {
+                json!([point.0, point.1])
+            }
+        };
+        let encoded_arcs = arcs
+            .arcs
+            .iter()
+            .map(|arc| {
+                let arc = match self.tolerance {
+                    Some(tolerance) => {
+                        let real = arc
+                            .iter()
+                            .map(|point| quantize.invert(point))
+                            .collect::<Vec<_>>();
+                        simplify(arc, &real, tolerance)
+                    }
+                    None => arc.clone(),
+                };
+                let mut previous = (0.0, 0.0);
+                let points = arc.iter().map(|point| {
+                    if quantized {
+                        let delta = (point.0 - previous.0, point.1 - previous.1);
+                        previous = *point;
+                        coordinates(&delta)
+                    } else {
+                        coordinates(point)
+                    }
+                });
+                Value::Array(points.collect())
+            })
+            .collect::<Vec<_>>();
+
+        let geometries = shapes
+            .iter()
+            .zip(&features.features)
+            .map(|(shape, feature)| {
+                let mut object = geometry(shape, &line_arcs, &coordinates);
+                if let Some(properties) = &feature.properties {
+                    object.insert("properties".to_string(), Value::Object(properties.clone()));
+                }
+                if let Some(id) = &feature.id {
+                    object.insert("id".to_string(), json!(id));
+                }
+                Value::Object(object)
+            })
+            .collect::<Vec<_>>();
+
+        let mut topology = Map::new();
+        topology.insert("type".to_string(), json!("Topology"));
+        topology.insert("bbox".to_string(), json!(bbox));
+        if let Some((kx, ky)) = quantize.scale {
+            topology.insert(
+                "transform".to_string(),
+                json!({"scale": [1.0 / kx, 1.0 / ky], "translate": [bbox[0], bbox[1]]}),
+            );
+        }
+        topology.insert(
+            "objects".to_string(),
+            json!({&self.object: {"type": "GeometryCollection", "geometries": geometries}}),
+        );
+        topology.insert("arcs".to_string(), Value::Array(encoded_arcs));
+        topology
+    }
+
+    /// Inline TopoJSON data of features, along with the format extracting the features (or
+    /// their mesh) from the topology
+    pub fn encode(&self, features: &FeatureCollection) -> UrlData {
+        let mut format = DataFormatBuilder::default();
+        format.data_format_type(DataFormatType::Topojson);
+        if self.mesh {
+            format.mesh(self.object.as_str());
+        } else {
+            format.feature(self.object.as_str());
+        }
+        let topology = self
+            .topology(features)
+            .into_iter()
+            .map(|(key, value)| (key, Some(value)))
+            .collect();
+        UrlDataBuilder::default()
+            .values(UrlDataInlineDataset::AnythingMap(topology))
+            .format(format.build().unwrap())
+            .build()
+            .unwrap()
+    }
+}
+
+/// The TopoJSON object of a geometry
+fn geometry(
+    shape: &Shape,
+    line_arcs: &[Vec<i64>],
+    coordinates: &impl Fn(&Point) -> Value,
+) -> Map<String, Value> {
+    let arcs = |lines: &[usize]| {
+        lines
+            .iter()
+            .map(|line| json!(line_arcs[*line]))
+            .collect::<Vec<_>>()
+    };
+    let (kind, member, value) = match shape {
+        Shape::Null => {
+            let mut object = Map::new();
+            object.insert("type".to_string(), Value::Null);
+            return object;
+        }
+        Shape::Point(point) => ("Point", "coordinates", coordinates(point)),
+        Shape::MultiPoint(points) => (
+            "MultiPoint",
+            "coordinates",
+            points.iter().map(coordinates).collect(),
+        ),
+        Shape::LineString(line) => ("LineString", "arcs", json!(line_arcs[*line])),
+        Shape::MultiLineString(lines) => ("MultiLineString", "arcs", json!(arcs(lines))),
+        Shape::Polygon(rings) => ("Polygon", "arcs", json!(arcs(rings))),
+        Shape::MultiPolygon(polygons) => (
+            "MultiPolygon",
+            "arcs",
+            polygons.iter().map(|rings| json!(arcs(rings))).collect(),
+        ),
+        Shape::GeometryCollection(shapes) => (
+            "GeometryCollection",
+            "geometries",
+            shapes
+                .iter()
+                .map(|shape| Value::Object(geometry(shape, line_arcs, coordinates)))
+                .collect(),
+        ),
+    };
+    let mut object = Map::new();
+    object.insert("type".to_string(), json!(kind));
+    object.insert(member.to_string(), value);
+    object
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use geojson::Feature;
+
+    fn square(x: f64, name: &str) -> Feature {
+        let ring = vec![
+            vec![x, 0.0],
+            vec![x + 1.0, 0.0],
+            vec![x + 1.0, 1.0],
+            vec![x, 1.0],
+            vec![x, 0.0],
+        ];
+        let mut feature = Feature::from(geojson::Value::Polygon(vec![ring]));
+        feature.set_property("name", name);
+        feature
+    }
+
+    #[test]
+    fn test_shared_borders() {
+        let features = vec![square(0.0, "a"), square(1.0, "b")]
+            .into_iter()
+            .collect::<FeatureCollection>();
+        let topology = TopoJson::new("squares").topology(&features);
+        let arcs = topology["arcs"].as_array().unwrap();
+        // the shared border is stored once
+        assert_eq!(arcs.len(), 3);
+        let geometries = &topology["objects"]["squares"]["geometries"];
+        assert_eq!(geometries[0]["properties"], json!({"name": "a"}));
+        let rings = |i: usize| geometries[i]["arcs"][0].as_array().unwrap().clone();
+        let shared = rings(0)
+            .iter()
+            .filter(|arc| {
+                let arc = arc.as_i64().unwrap();
+                rings(1).contains(&json!(!arc))
+            })
+            .count();
+        assert_eq!(shared, 1);
+    }
+
+    #[test]
+    fn test_quantization() {
+        let features = vec![square(0.0, "a"), square(1.0, "b")]
+            .into_iter()
+            .collect::<FeatureCollection>();
+        let data = TopoJson::new("squares").quantization(3).encode(&features);
+        let data = serde_json::to_value(data).unwrap();
+        assert_eq!(
+            data["format"],
+            json!({"type": "topojson", "feature": "squares"})
+        );
+        let topology = &data["values"];
+        assert_eq!(
+            topology["transform"],
+            json!({"scale": [1.0, 0.5], "translate": [0.0, 0.0]})
+        );
+        // delta encoded, starting from the absolute position of the first point
+        for arc in topology["arcs"].as_array().unwrap() {
+            for delta in &arc.as_array().unwrap()[1..] {
+                assert!(delta[0].as_i64().unwrap().abs() <= 1);
+                assert!(delta[1].as_i64().unwrap().abs() <= 2);
+            }
+        }
+    }
+
+    #[test]
+    fn test_simplify() {
+        let line = (0..=10)
+            .map(|i| vec![i as f64, if i == 5 { 1.0 } else { 0.01 * (i % 2) as f64 }])
+            .collect::<Vec<_>>();
+        let features = Some(Feature::from(geojson::Value::LineString(line)))
+            .into_iter()
+            .collect::<FeatureCollection>();
+        let topology = TopoJson::new("line").simplify(0.1).topology(&features);
+        assert_eq!(
+            topology["arcs"],
+            json!([[[0.0, 0.0], [4.0, 0.0], [5.0, 1.0], [6.0, 0.0], [10.0, 0.0]]])
+        );
+    }
+}
@@ -0,0 +1,63 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use std::fmt;
+use std::path::PathBuf;
+
+/// Error returned when reading, writing or converting a Vega-Lite specification
+#[derive(Debug)]
+pub enum Error {
+    /// Reading or writing the specification failed
+    Io(std::io::Error),
+    /// The specification is not valid json, or doesn't match the Vega-Lite schema
+    Json(serde_json::Error),
+    /// The output format can't be guessed from the extension of this path
+    UnsupportedExtension(PathBuf),
+    /// The specification is well-formed but not valid for Vega-Lite
+    Validation(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(err) => write!(f, "io error: {}", err),
+            Error::Json(err) => write!(f, "json error: {}", err),
+            Error::UnsupportedExtension(path) => write!(
+                f,
+                "unsupported extension for {}, expected .json or .html",
+                path.display()
+            ),
+            Error::Validation(reason) => write!(f, "invalid specification: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(err) => Some(err),
+            Error::Json(err) => Some(err),
+            Error::UnsupportedExtension(_) | Error::Validation(_) => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Error::Json(err)
+    }
+}
@@ -0,0 +1,188 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Aggregate operations, as computed by Vega.
+
+use super::value::{js_compare, number, Val};
+use super::{get_path, GroupKey, Unsupported};
+use crate::schema::AggregateOp;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+/// The name of an operation, as serialized
+pub(crate) fn op_name(op: &impl serde::Serialize) -> String {
+    serde_json::to_value(op)
+        .ok()
+        .and_then(|name| name.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// The default name of the output of an operation on a field
+pub(crate) fn output_name(op: &impl serde::Serialize, field: Option<&str>) -> String {
+    match field {
+        Some(field) => format!("{}_{}", op_name(op), field),
+        None => op_name(op),
+    }
+}
+
+/// The `p`-quantile of sorted values, with linear interpolation
+pub(crate) fn quantile(sorted: &[f64], p: f64) -> Option<f64> {
+    let last = sorted.len().checked_sub(1)?;
+    let h = last as f64 * p;
+    let i = h.floor() as usize;
+    let low = sorted[i];
+    Some(match sorted.get(i + 1) {
+        Some(high) => low + (high - low) * (h - i as f64),
+        None => low,
+    })
+}
+
+/// Aggregate the values of a field over rows; `null`, `undefined` and empty strings are
+/// missing, `NaN` is ignored and other values are valid
+pub(crate) fn aggregate(
+    op: &AggregateOp,
+    rows: &[&Value],
+    path: Option<&[String]>,
+) -> Result<Value, Unsupported> {
+    let values = rows
+        .iter()
+        .map(|row| path.map_or(Val::Undefined, |path| Val::from(get_path(row, path))))
+        .collect::<Vec<_>>();
+    let is_missing = |value: &Val| value.is_nullish() || *value == Val::String(String::new());
+    let valid = values
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| !is_missing(value) && value.is_valid())
+        .collect::<Vec<_>>();
+    let numbers = || valid.iter().map(|(_, value)| value.to_number());
+    let count = valid.len() as f64;
+    let mean = numbers().sum::<f64>() / count;
+    let deviation = || numbers().map(|n| (n - mean) * (n - mean)).sum::<f64>();
+    let quantile = |p: f64| {
+        let mut sorted = numbers().filter(|n| !n.is_nan()).collect::<Vec<_>>();
+        sorted.sort_by(|a, b| a.partial_cmp(b).unwrap());
+        quantile(&sorted, p).map_or(Value::Null, number)
+    };
+    let extreme = |ordering: Ordering| {
+        valid
+            .iter()
+            .fold(None, |best: Option<&(usize, &Val)>, candidate| match best {
+                Some(best) if js_compare(candidate.1, best.1) != Some(ordering) => Some(best),
+                _ => Some(candidate),
+            })
+    };
+    Ok(match op {
+        AggregateOp::Count => number(rows.len() as f64),
+        AggregateOp::Valid => number(count),
+        AggregateOp::Missing => {
+            number(values.iter().filter(|value| is_missing(value)).count() as f64)
+        }
+        AggregateOp::Distinct => number(
+            rows.iter()
+                .map(|row| GroupKey::from(path.and_then(|path| get_path(row, path))))
+                .collect::<HashSet<_>>()
+                .len() as f64,
+        ),
+        AggregateOp::Sum => number(numbers().sum()),
+        AggregateOp::Mean | AggregateOp::Average if valid.is_empty() => Value::Null,
+        AggregateOp::Mean | AggregateOp::Average => number(mean),
+        // Vega leaves the spread of less than two values undefined
+        AggregateOp::Variance
+        | AggregateOp::Variancep
+        | AggregateOp::Stdev
+        | AggregateOp::Stdevp
+        | AggregateOp::Stderr
+            if valid.len() < 2 =>
+        {
+            Value::Null
+        }
+        AggregateOp::Variance => number(deviation() / (count - 1.0)),
+        AggregateOp::Variancep => number(deviation() / count),
+        AggregateOp::Stdev => number((deviation() / (count - 1.0)).sqrt()),
+        AggregateOp::Stdevp => number((deviation() / count).sqrt()),
+        AggregateOp::Stderr => number((deviation() / (count * (count - 1.0))).sqrt()),
+        AggregateOp::Median => quantile(0.5),
+        AggregateOp::Q1 => quantile(0.25),
+        AggregateOp::Q3 => quantile(0.75),
+        AggregateOp::Min => {
+            extreme(Ordering::Less).map_or(Value::Null, |(_, v)| (*v).clone().into_json())
+        }
+        AggregateOp::Max => {
+            extreme(Ordering::Greater).map_or(Value::Null, |(_, v)| (*v).clone().into_json())
+        }
+        AggregateOp::Argmin => {
+            extreme(Ordering::Less).map_or(Value::Null, |(i, _)| rows[*i].clone())
+        }
+        AggregateOp::Argmax => {
+            extreme(Ordering::Greater).map_or(Value::Null, |(i, _)| rows[*i].clone())
+        }
+        AggregateOp::Values => Value::Array(rows.iter().map(|row| (*row).clone()).collect()),
+        AggregateOp::Ci0 | AggregateOp::Ci1 => {
+            return Err(Unsupported(format!(
+                "`{}`, a random bootstrap",
+                op_name(op)
+            )))
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_aggregate() {
+        let rows = [
+            json!({"a": 1}),
+            json!({"a": 2}),
+            json!({"a": null}),
+            json!({"a": 6}),
+            json!({}),
+        ];
+        let rows = rows.iter().collect::<Vec<_>>();
+        let path = vec!["a".to_string()];
+        let aggregate = |op| aggregate(&op, &rows, Some(&path)).unwrap();
+        assert_eq!(aggregate(AggregateOp::Count), json!(5));
+        assert_eq!(aggregate(AggregateOp::Valid), json!(3));
+        assert_eq!(aggregate(AggregateOp::Missing), json!(2));
+        assert_eq!(aggregate(AggregateOp::Distinct), json!(5));
+        assert_eq!(aggregate(AggregateOp::Sum), json!(9));
+        assert_eq!(aggregate(AggregateOp::Mean), json!(3));
+        assert_eq!(aggregate(AggregateOp::Variance), json!(7));
+        assert_eq!(aggregate(AggregateOp::Median), json!(2));
+        assert_eq!(aggregate(AggregateOp::Q3), json!(4));
+        assert_eq!(aggregate(AggregateOp::Max), json!(6));
+        assert_eq!(aggregate(AggregateOp::Argmin), json!({"a": 1}));
+        assert!(super::aggregate(&AggregateOp::Ci0, &rows, Some(&path)).is_err());
+
+        let single = |op| super::aggregate(&op, &rows[..1], Some(&path)).unwrap();
+        assert_eq!(single(AggregateOp::Variance), Value::Null);
+        assert_eq!(single(AggregateOp::Variancep), Value::Null);
+        assert_eq!(single(AggregateOp::Stdev), Value::Null);
+        assert_eq!(single(AggregateOp::Stdevp), Value::Null);
+        assert_eq!(single(AggregateOp::Stderr), Value::Null);
+        assert_eq!(single(AggregateOp::Mean), json!(1));
+
+        let numbers = [
+            json!({"a": 1}),
+            json!({"a": 1.0}),
+            json!({"a": -0.0}),
+            json!({"a": 0}),
+        ];
+        let numbers = numbers.iter().collect::<Vec<_>>();
+        assert_eq!(
+            super::aggregate(&AggregateOp::Distinct, &numbers, Some(&path)).unwrap(),
+            json!(2)
+        );
+    }
+}
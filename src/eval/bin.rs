@@ -0,0 +1,188 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Binning of numbers, as computed by Vega.
+
+use super::value::Val;
+use super::{get_path, Unsupported};
+use crate::schema::{BinParams, PurpleBin};
+use serde_json::Value;
+
+/// Vega-Lite's default maximum number of bins
+const DEFAULT_MAXBINS: f64 = 10.0;
+const EPSILON: f64 = 1e-14;
+
+/// The bins of an extent
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Bins {
+    pub(crate) start: f64,
+    pub(crate) stop: f64,
+    pub(crate) step: f64,
+}
+
+/// The bin parameters, unless binning is disabled or not supported
+pub(crate) fn params(bin: &PurpleBin) -> Result<BinParams, Unsupported> {
+    match bin {
+        PurpleBin::Bool(true) => Ok(BinParams::default()),
+        PurpleBin::Bool(false) => Err(Unsupported("disabled binning".to_string())),
+        PurpleBin::BinParams(params) if params.binned == Some(true) => {
+            Err(Unsupported("already binned data".to_string()))
+        }
+        PurpleBin::BinParams(params) if params.anchor.is_some() => {
+            Err(Unsupported("anchored bins".to_string()))
+        }
+        PurpleBin::BinParams(params) => Ok(params.clone()),
+    }
+}
+
+impl Bins {
+    /// Compute the bins of an extent, see `bin` in `vega-statistics`
+    pub(crate) fn new(params: &BinParams, extent: [f64; 2]) -> Self {
+        let maxbins = params.maxbins.unwrap_or(DEFAULT_MAXBINS);
+        let base = params.base.unwrap_or(10.0);
+        let log_base = base.ln();
+        let divide = params.divide.clone().unwrap_or_else(|| vec![5.0, 2.0]);
+        let [mut min, mut max] = params
+            .extent
+            .as_ref()
+            .and_then(|extent| Some([*extent.first()?, *extent.get(1)?]))
+            .unwrap_or(extent);
+        let span = Some(max - min)
+            .filter(|span| *span != 0.0)
+            .or(Some(min.abs()).filter(|span| *span != 0.0))
+            .unwrap_or(1.0);
+
+        let step = if let Some(step) = params.step {
+            step
+        } else if let Some(steps) = params.steps.as_ref().filter(|steps| !steps.is_empty()) {
+            let target = span / maxbins;
+            let i = steps.iter().take_while(|step| **step < target).count();
+            steps[i.saturating_sub(1).min(steps.len() - 1)]
+        } else {
+            let level = (maxbins.ln() / log_base).ceil();
+            let minstep = params.minstep.unwrap_or(0.0);
+            let mut step = minstep.max(base.powf((span.ln() / log_base).round() - level));
+            while (span / step).ceil() > maxbins {
+                step *= base;
+            }
+            for div in divide {
+                let candidate = step / div;
+                if candidate >= minstep && span / candidate <= maxbins {
+                    step = candidate;
+                }
+            }
+            step
+        };
+
+        let log_step = step.ln();
+        let precision = if log_step >= 0.0 {
+            0.0
+        } else {
+            (-log_step / log_base).trunc() + 1.0
+        };
+        let eps = base.powf(-precision - 1.0);
+        if params.nice != Some(false) {
+            let nice = (min / step + eps).floor() * step;
+            min = if min < nice { nice - step } else { nice };
+            max = (max / step).ceil() * step;
+        }
+        Bins {
+            start: min,
+            stop: if max == min { min + step } else { max },
+            step,
+        }
+    }
+
+    /// The start of the bin of a value, infinite for values out of the bins
+    pub(crate) fn bin(&self, value: f64) -> f64 {
+        if value < self.start {
+            f64::NEG_INFINITY
+        } else if value > self.stop {
+            f64::INFINITY
+        } else {
+            let value = value.min(self.stop - self.step).max(self.start);
+            self.start + self.step * (EPSILON + (value - self.start) / self.step).floor()
+        }
+    }
+}
+
+/// Bin a field of rows, giving the bins and the start of the bin of each row, `None` for missing
+/// values
+pub(crate) fn bin_rows(
+    params: &BinParams,
+    rows: &[Value],
+    path: &[String],
+) -> Result<(Bins, Vec<Option<f64>>), Unsupported> {
+    let numbers = rows
+        .iter()
+        .map(|row| {
+            let value = Val::from(get_path(row, path));
+            if value.is_nullish() || value == Val::String(String::new()) {
+                None
+            } else {
+                Some(value.to_number())
+            }
+        })
+        .collect::<Vec<_>>();
+    let valid = numbers.iter().flatten().filter(|n| !n.is_nan());
+    let extent = valid.fold(None, |extent: Option<[f64; 2]>, n| {
+        Some(extent.map_or([*n, *n], |[min, max]| [min.min(*n), max.max(*n)]))
+    });
+    let extent = match (extent, &params.extent) {
+        (Some(extent), _) => extent,
+        (None, Some(_)) => [0.0, 0.0],
+        (None, None) => return Err(Unsupported("binning of a field without values".to_string())),
+    };
+    let bins = Bins::new(params, extent);
+    let starts = numbers
+        .into_iter()
+        .map(|n| match n.map(|n| bins.bin(n)) {
+            Some(bin) if bin.is_infinite() => Err(Unsupported(
+                "values out of the extent of the bins".to_string(),
+            )),
+            bin => Ok(bin),
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((bins, starts))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bins() {
+        let bins = Bins::new(&BinParams::default(), [1.3, 47.2]);
+        assert_eq!(
+            bins,
+            Bins {
+                start: 0.0,
+                stop: 50.0,
+                step: 5.0
+            }
+        );
+        assert_eq!(bins.bin(47.2), 45.0);
+        assert_eq!(bins.bin(50.0), 45.0);
+        assert_eq!(bins.bin(5.0), 5.0);
+        let params = BinParams {
+            step: Some(0.5),
+            ..BinParams::default()
+        };
+        assert_eq!(Bins::new(&params, [0.2, 1.1]).start, 0.0);
+        assert_eq!(Bins::new(&params, [0.2, 1.1]).stop, 1.5);
+
+        let params = BinParams {
+            steps: Some(vec![]),
+            ..BinParams::default()
+        };
+        assert_eq!(Bins::new(&params, [1.3, 47.2]), bins);
+    }
+}
@@ -0,0 +1,632 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Dates and time units.
+//!
+//! The time zone of the browser that will render a chart is unknown, so a date is either a
+//! `Local` wall clock time, or a `Utc` instant. Both are stored as milliseconds since the epoch,
+//! a local time being stored as if it were in UTC. Local time units can only be computed on
+//! local times, and UTC time units on instants.
+
+use super::value::{number, Val};
+use super::Unsupported;
+use crate::schema::{DateTime, Day, Month, TimeUnit};
+use serde_json::Value;
+
+const MS_PER_DAY: f64 = 86_400_000.0;
+/// The year used by Vega-Lite for time units containing the day of the week but not the year,
+/// January 1st 2006 is a Sunday
+const SUNDAY_YEAR: f64 = 2006.0;
+/// The year of `new Date(0, ...)` in javascript
+const DEFAULT_YEAR: f64 = 1900.0;
+
+const MONTHS: [&str; 12] = [
+    "january",
+    "february",
+    "march",
+    "april",
+    "may",
+    "june",
+    "july",
+    "august",
+    "september",
+    "october",
+    "november",
+    "december",
+];
+const DAYS: [&str; 7] = [
+    "sunday",
+    "monday",
+    "tuesday",
+    "wednesday",
+    "thursday",
+    "friday",
+    "saturday",
+];
+
+/// A date, as milliseconds since the epoch
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Moment {
+    /// A wall clock time, in the time zone of the browser
+    Local(f64),
+    /// An instant
+    Utc(f64),
+}
+
+impl Moment {
+    pub(crate) fn utc(self) -> bool {
+        matches!(self, Moment::Utc(_))
+    }
+
+    pub(crate) fn ms(self) -> f64 {
+        match self {
+            Moment::Local(ms) | Moment::Utc(ms) => ms,
+        }
+    }
+
+    /// A local time as an ISO string without time zone, that javascript parses as a local
+    /// time, an instant as a timestamp
+    pub(crate) fn to_json(self) -> Value {
+        match self {
+            Moment::Utc(ms) => number(ms),
+            Moment::Local(ms) => {
+                let f = fields(ms);
+                let mut iso = format!(
+                    "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+                    f.year,
+                    f.month + 1,
+                    f.date,
+                    f.hours,
+                    f.minutes,
+                    f.seconds
+                );
+                if f.milliseconds != 0 {
+                    iso.push_str(&format!(".{:03}", f.milliseconds));
+                }
+                Value::String(iso)
+            }
+        }
+    }
+}
+
+/// The calendar fields of a date
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct Fields {
+    pub(crate) year: i64,
+    /// From 0 to 11
+    pub(crate) month: i64,
+    pub(crate) date: i64,
+    /// The day of the week, from 0 (Sunday) to 6
+    pub(crate) day: i64,
+    pub(crate) hours: i64,
+    pub(crate) minutes: i64,
+    pub(crate) seconds: i64,
+    pub(crate) milliseconds: i64,
+}
+
+fn days_from_civil(year: i64, month: i64, date: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let doy = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + date - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let date = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (yoe + era * 400 + i64::from(month <= 2), month, date)
+}
+
+/// Milliseconds since the epoch of a date given by its fields, out of range fields overflowing
+/// into the bigger ones like `new Date(year, month, ...)` does
+pub(crate) fn make(
+    year: f64,
+    month: f64,
+    date: f64,
+    hours: f64,
+    minutes: f64,
+    seconds: f64,
+    milliseconds: f64,
+) -> f64 {
+    let year = year + (month / 12.0).floor();
+    let month = month.rem_euclid(12.0);
+    let days = days_from_civil(year as i64, month as i64 + 1, 1) as f64 + date - 1.0;
+    days * MS_PER_DAY + ((hours * 60.0 + minutes) * 60.0 + seconds) * 1000.0 + milliseconds
+}
+
+/// The calendar fields of milliseconds since the epoch
+pub(crate) fn fields(ms: f64) -> Fields {
+    let ms = ms as i64;
+    let days = ms.div_euclid(MS_PER_DAY as i64);
+    let time = ms.rem_euclid(MS_PER_DAY as i64);
+    let (year, month, date) = civil_from_days(days);
+    Fields {
+        year,
+        month: month - 1,
+        date,
+        day: (days + 4).rem_euclid(7),
+        hours: time / 3_600_000,
+        minutes: time / 60_000 % 60,
+        seconds: time / 1000 % 60,
+        milliseconds: time % 1000,
+    }
+}
+
+struct Cursor<'a> {
+    input: &'a [u8],
+    position: usize,
+}
+
+impl Cursor<'_> {
+    fn done(&self) -> bool {
+        self.position == self.input.len()
+    }
+
+    fn eat(&mut self, c: u8) -> bool {
+        let found = self.input.get(self.position) == Some(&c);
+        if found {
+            self.position += 1;
+        }
+        found
+    }
+
+    fn spaces(&mut self) {
+        while self.eat(b' ') {}
+    }
+
+    fn digits(&mut self, min: usize, max: usize) -> Option<i64> {
+        let start = self.position;
+        while self.position - start < max
+            && self
+                .input
+                .get(self.position)
+                .is_some_and(u8::is_ascii_digit)
+        {
+            self.position += 1;
+        }
+        if self.position - start < min {
+            return None;
+        }
+        std::str::from_utf8(&self.input[start..self.position])
+            .ok()?
+            .parse()
+            .ok()
+    }
+
+    fn word(&mut self) -> String {
+        let start = self.position;
+        while self
+            .input
+            .get(self.position)
+            .is_some_and(u8::is_ascii_alphabetic)
+        {
+            self.position += 1;
+        }
+        String::from_utf8_lossy(&self.input[start..self.position]).to_lowercase()
+    }
+
+    /// `HH:MM[:SS[.sss]]`, as milliseconds
+    fn time(&mut self) -> Option<f64> {
+        let hours = self.digits(1, 2)?;
+        if !self.eat(b':') {
+            return None;
+        }
+        let minutes = self.digits(2, 2)?;
+        let mut seconds = 0;
+        let mut milliseconds = 0.0;
+        if self.eat(b':') {
+            seconds = self.digits(2, 2)?;
+            if self.eat(b'.') {
+                let start = self.position;
+                let fraction = self.digits(1, 9)?;
+                milliseconds = fraction as f64 * 10f64.powi(3 - (self.position - start) as i32);
+                milliseconds = milliseconds.floor();
+            }
+        }
+        if hours > 24 || minutes > 59 || seconds > 59 {
+            return None;
+        }
+        Some(((hours * 60 + minutes) * 60 + seconds) as f64 * 1000.0 + milliseconds)
+    }
+}
+
+fn day_ms(year: i64, month: i64, date: i64) -> Option<f64> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&date) {
+        return None;
+    }
+    Some(days_from_civil(year, month, date) as f64 * MS_PER_DAY)
+}
+
+/// `YYYY[-MM[-DD]][THH:MM[:SS[.sss]][Z|+HH:MM]]`, dates without time being instants and date
+/// times without time zone being local times, as in javascript
+fn parse_iso(cursor: &mut Cursor) -> Option<Moment> {
+    let year = cursor.digits(4, 4)?;
+    let (mut month, mut date) = (1, 1);
+    if cursor.eat(b'-') {
+        month = cursor.digits(2, 2)?;
+        if cursor.eat(b'-') {
+            date = cursor.digits(2, 2)?;
+        }
+    }
+    let day = day_ms(year, month, date)?;
+    if cursor.done() {
+        return Some(Moment::Utc(day));
+    }
+    if !cursor.eat(b'T') && !cursor.eat(b' ') {
+        return None;
+    }
+    let wall = day + cursor.time()?;
+    if cursor.done() {
+        return Some(Moment::Local(wall));
+    }
+    if cursor.eat(b'Z') {
+        return Some(Moment::Utc(wall)).filter(|_| cursor.done());
+    }
+    let sign = if cursor.eat(b'+') {
+        1.0
+    } else if cursor.eat(b'-') {
+        -1.0
+    } else {
+        return None;
+    };
+    let hours = cursor.digits(2, 2)?;
+    cursor.eat(b':');
+    let minutes = cursor.digits(2, 2)?;
+    Some(Moment::Utc(
+        wall - sign * ((hours * 60 + minutes) * 60_000) as f64,
+    ))
+    .filter(|_| cursor.done())
+}
+
+/// An optional time ending a local date
+fn local_time(cursor: &mut Cursor, day: f64) -> Option<Moment> {
+    cursor.spaces();
+    if cursor.done() {
+        return Some(Moment::Local(day));
+    }
+    let time = cursor.time()?;
+    Some(Moment::Local(day + time)).filter(|_| cursor.done())
+}
+
+/// `YYYY/MM/DD` or `MM/DD/YYYY`, with an optional time
+fn parse_slashes(cursor: &mut Cursor) -> Option<Moment> {
+    let first = cursor.digits(1, 4)?;
+    if !cursor.eat(b'/') {
+        return None;
+    }
+    let second = cursor.digits(1, 2)?;
+    if !cursor.eat(b'/') {
+        return None;
+    }
+    let third = cursor.digits(1, 4)?;
+    let day = if first >= 1000 {
+        day_ms(first, second, third)?
+    } else {
+        day_ms(third, first, second)?
+    };
+    local_time(cursor, day)
+}
+
+/// `Jan 1 2000` or `January 1, 2000`, with an optional time
+fn parse_month_name(cursor: &mut Cursor) -> Option<Moment> {
+    let name = cursor.word();
+    if name.len() < 3 {
+        return None;
+    }
+    let month = MONTHS.iter().position(|month| month.starts_with(&name))? as i64 + 1;
+    cursor.spaces();
+    let date = cursor.digits(1, 2)?;
+    cursor.eat(b',');
+    cursor.spaces();
+    let year = cursor.digits(4, 4)?;
+    local_time(cursor, day_ms(year, month, date)?)
+}
+
+/// Parse a date the way `new Date(value)` does for the formats that are unambiguous in
+/// javascript; a number is a timestamp
+pub(crate) fn parse(value: &Val) -> Option<Moment> {
+    match value {
+        Val::Number(ms) if ms.is_finite() => Some(Moment::Utc(*ms)),
+        Val::String(s) => {
+            let s = s.trim();
+            let parsers: [fn(&mut Cursor) -> Option<Moment>; 3] =
+                [parse_iso, parse_slashes, parse_month_name];
+            parsers.iter().find_map(|parser| {
+                parser(&mut Cursor {
+                    input: s.as_bytes(),
+                    position: 0,
+                })
+            })
+        }
+        _ => None,
+    }
+}
+
+/// The parts of a time unit
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Units {
+    pub(crate) utc: bool,
+    pub(crate) year: bool,
+    pub(crate) quarter: bool,
+    pub(crate) month: bool,
+    pub(crate) date: bool,
+    pub(crate) day: bool,
+    pub(crate) hours: bool,
+    pub(crate) minutes: bool,
+    pub(crate) seconds: bool,
+    pub(crate) milliseconds: bool,
+}
+
+impl Units {
+    pub(crate) fn of(time_unit: &TimeUnit) -> Self {
+        let name = serde_json::to_value(time_unit)
+            .ok()
+            .and_then(|name| name.as_str().map(str::to_string))
+            .unwrap_or_default();
+        let contains = |unit: &str| {
+            name.find(unit).is_some_and(|index| {
+                unit != "seconds" || index == 0 || name.as_bytes()[index - 1] != b'i'
+            })
+        };
+        Units {
+            utc: name.starts_with("utc"),
+            year: contains("year"),
+            quarter: contains("quarter"),
+            month: contains("month"),
+            date: contains("date"),
+            day: contains("day"),
+            hours: contains("hours"),
+            minutes: contains("minutes"),
+            seconds: contains("seconds"),
+            milliseconds: contains("milliseconds"),
+        }
+    }
+
+    /// The names of the parts of the time unit, from the biggest to the smallest
+    pub(crate) fn parts(&self) -> Vec<&'static str> {
+        [
+            (self.year, "year"),
+            (self.quarter, "quarter"),
+            (self.month, "month"),
+            (self.day, "day"),
+            (self.date, "date"),
+            (self.hours, "hours"),
+            (self.minutes, "minutes"),
+            (self.seconds, "seconds"),
+            (self.milliseconds, "milliseconds"),
+        ]
+        .iter()
+        .filter(|(contained, _)| *contained)
+        .map(|(_, part)| *part)
+        .collect()
+    }
+
+    /// Whether the time unit has a single part
+    pub(crate) fn single(&self) -> bool {
+        self.parts().len() == 1
+    }
+}
+
+/// Optional date parts, with a 0-based month, quarter and day of the week
+#[derive(Debug, Clone, Copy, Default)]
+struct Parts {
+    year: Option<f64>,
+    quarter: Option<f64>,
+    month: Option<f64>,
+    date: Option<f64>,
+    day: Option<f64>,
+    hours: Option<f64>,
+    minutes: Option<f64>,
+    seconds: Option<f64>,
+    milliseconds: Option<f64>,
+}
+
+impl Parts {
+    /// The date built by Vega-Lite from date parts, see `dateTimeExpr`
+    fn compose(&self) -> f64 {
+        let year = self.year.unwrap_or(if self.day.is_some() {
+            SUNDAY_YEAR
+        } else {
+            DEFAULT_YEAR
+        });
+        let month = self
+            .month
+            .or_else(|| self.quarter.map(|quarter| quarter * 3.0))
+            .unwrap_or(0.0);
+        let date = self
+            .date
+            .or_else(|| self.day.map(|day| day + 1.0))
+            .unwrap_or(1.0);
+        make(
+            year,
+            month,
+            date,
+            self.hours.unwrap_or(0.0),
+            self.minutes.unwrap_or(0.0),
+            self.seconds.unwrap_or(0.0),
+            self.milliseconds.unwrap_or(0.0),
+        )
+    }
+}
+
+/// Truncate a date to a time unit, `None` when the time unit and the date are not both local or
+/// both UTC
+pub(crate) fn truncate(time_unit: &TimeUnit, moment: Moment) -> Option<Moment> {
+    let units = Units::of(time_unit);
+    if units.utc != moment.utc() {
+        return None;
+    }
+    let f = fields(moment.ms());
+    let keep = |keep: bool, value: i64| Some(value as f64).filter(|_| keep);
+    let ms = Parts {
+        year: keep(units.year, f.year),
+        quarter: keep(units.quarter, f.month / 3),
+        month: keep(units.month, f.month),
+        date: keep(units.date, f.date),
+        day: keep(units.day, f.day),
+        hours: keep(units.hours, f.hours),
+        minutes: keep(units.minutes, f.minutes),
+        seconds: keep(units.seconds, f.seconds),
+        milliseconds: keep(units.milliseconds, f.milliseconds),
+    }
+    .compose();
+    Some(if units.utc {
+        Moment::Utc(ms)
+    } else {
+        Moment::Local(ms)
+    })
+}
+
+/// Parse and truncate a date to a time unit
+pub(crate) fn truncate_value(time_unit: &TimeUnit, value: &Val) -> Result<Moment, Unsupported> {
+    parse(value)
+        .and_then(|moment| truncate(time_unit, moment))
+        .ok_or_else(|| {
+            Unsupported(format!(
+                "time unit of `{}`, which depends on the time zone",
+                value.to_js_string()
+            ))
+        })
+}
+
+fn name_index(names: &[&str], name: &str) -> Option<f64> {
+    let name = name.to_lowercase();
+    names
+        .iter()
+        .position(|candidate| {
+            *candidate == name || (name.len() == 3 && candidate.starts_with(&name))
+        })
+        .map(|index| index as f64)
+}
+
+/// The date of a `DateTime` object, with a 1-based month and quarter as in Vega-Lite
+pub(crate) fn from_date_time(date_time: &DateTime) -> Option<Moment> {
+    let month = match &date_time.month {
+        Some(Month::Double(month)) => Some(month - 1.0),
+        Some(Month::String(name)) => Some(name_index(&MONTHS, name)?),
+        None => None,
+    };
+    let day = match &date_time.day {
+        Some(Day::Double(day)) => Some(day % 7.0),
+        Some(Day::String(name)) => Some(name_index(&DAYS, name)?),
+        None => None,
+    };
+    let ms = Parts {
+        year: date_time.year,
+        quarter: date_time.quarter.map(|quarter| quarter - 1.0),
+        month,
+        date: date_time.date,
+        day,
+        hours: date_time.hours,
+        minutes: date_time.minutes,
+        seconds: date_time.seconds,
+        milliseconds: date_time.milliseconds,
+    }
+    .compose();
+    Some(if date_time.utc == Some(true) {
+        Moment::Utc(ms)
+    } else {
+        Moment::Local(ms)
+    })
+}
+
+/// The date compared to a field with a single time unit, the value being the part of the date
+/// for that unit
+pub(crate) fn from_unit_value(time_unit: &TimeUnit, value: &Value) -> Option<Moment> {
+    let units = Units::of(time_unit);
+    if units.utc || !units.single() {
+        return None;
+    }
+    let mut date_time = DateTime::default();
+    let number = value.as_f64();
+    match value {
+        _ if units.month => {
+            date_time.month = Some(match value {
+                Value::String(name) => Month::String(name.clone()),
+                _ => Month::Double(number?),
+            })
+        }
+        _ if units.day => {
+            date_time.day = Some(match value {
+                Value::String(name) => Day::String(name.clone()),
+                _ => Day::Double(number?),
+            })
+        }
+        _ if units.year => date_time.year = Some(number?),
+        _ if units.quarter => date_time.quarter = Some(number?),
+        _ if units.date => date_time.date = Some(number?),
+        _ if units.hours => date_time.hours = Some(number?),
+        _ if units.minutes => date_time.minutes = Some(number?),
+        _ if units.seconds => date_time.seconds = Some(number?),
+        _ => date_time.milliseconds = Some(number?),
+    }
+    from_date_time(&date_time)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn string(s: &str) -> Val {
+        Val::String(s.to_string())
+    }
+
+    #[test]
+    fn test_parse() {
+        let day = make(2012.0, 0.0, 2.0, 0.0, 0.0, 0.0, 0.0);
+        assert_eq!(day, 1_325_462_400_000.0);
+        assert_eq!(parse(&string("2012-01-02")), Some(Moment::Utc(day)));
+        assert_eq!(
+            parse(&string("2012-01-02T01:00")),
+            Some(Moment::Local(day + 3_600_000.0))
+        );
+        assert_eq!(
+            parse(&string("2012-01-02T01:00:00+01:00")),
+            Some(Moment::Utc(day))
+        );
+        assert_eq!(parse(&string("2012/01/02")), Some(Moment::Local(day)));
+        assert_eq!(parse(&string("1/2/2012")), Some(Moment::Local(day)));
+        assert_eq!(parse(&string("Jan 2 2012")), Some(Moment::Local(day)));
+        assert_eq!(parse(&string("January 2, 2012")), Some(Moment::Local(day)));
+        assert_eq!(parse(&Val::Number(day)), Some(Moment::Utc(day)));
+        assert_eq!(parse(&string("yesterday")), None);
+        assert_eq!(fields(day).day, 1);
+    }
+
+    #[test]
+    fn test_truncate() {
+        let moment = parse(&string("2012-05-17T13:45:10.250")).unwrap();
+        let truncated = |unit| truncate(&unit, moment).unwrap().to_json();
+        assert_eq!(truncated(TimeUnit::Yearmonth), "2012-05-01T00:00:00");
+        assert_eq!(truncated(TimeUnit::Month), "1900-05-01T00:00:00");
+        assert_eq!(truncated(TimeUnit::Quarter), "1900-04-01T00:00:00");
+        assert_eq!(truncated(TimeUnit::Day), "2006-01-05T00:00:00");
+        assert_eq!(truncated(TimeUnit::Hoursminutes), "1900-01-01T13:45:00");
+        assert_eq!(truncated(TimeUnit::Milliseconds), "1900-01-01T00:00:00.250");
+        assert_eq!(truncate(&TimeUnit::Utcyear, moment), None);
+        assert_eq!(
+            from_unit_value(&TimeUnit::Month, &Value::from("May")),
+            truncate(&TimeUnit::Month, moment)
+        );
+        assert_eq!(
+            from_unit_value(&TimeUnit::Month, &Value::from(5)),
+            truncate(&TimeUnit::Month, moment)
+        );
+    }
+}
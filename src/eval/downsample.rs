@@ -0,0 +1,385 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Downsampling of series with more points than the chart has pixels.
+
+use super::datetime::{self, Moment};
+use super::value::Val;
+use super::{field_path, get_path, groups, sample_indices, Rng};
+use crate::error::Error;
+use crate::schema::*;
+use serde_json::{Map, Value};
+
+/// Marks drawing a point for each row
+const POINT_MARKS: &[&str] = &["area", "line", "point", "trail"];
+/// Channels that don't split the rows into series
+const NON_SERIES_CHANNELS: &[&str] = &["x", "y", "href", "text", "tooltip"];
+
+/// A strategy to reduce the number of points of a chart, a size of 0 leaving the points untouched
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Downsample {
+    /// Largest-Triangle-Three-Buckets: keep the given number of points of each series, chosen
+    /// to preserve the shape of its line
+    Lttb(usize),
+    /// Split each series into the given number of buckets of equal width along x, and keep the
+    /// points with the minimum and maximum y of each bucket, along with the first and last
+    /// points
+    MinMax(usize),
+    /// Keep a uniform random sample of the given number of points
+    Random(usize),
+    /// Keep a random sample of the given number of points, each series keeping its share of the
+    /// points
+    Stratified(usize),
+}
+
+/// The area of the triangle of three points
+fn area(a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> f64 {
+    ((a.0 - c.0) * (b.1 - a.1) - (a.0 - b.0) * (c.1 - a.1)).abs() / 2.0
+}
+
+fn lttb(points: &[(f64, f64)], threshold: usize) -> Vec<usize> {
+    let n = points.len();
+    if threshold >= n {
+        return (0..n).collect();
+    }
+    if threshold < 3 {
+        return [0, n - 1][..threshold].to_vec();
+    }
+    let every = (n - 2) as f64 / (threshold - 2) as f64;
+    let bucket = |i: usize| (i as f64 * every) as usize + 1;
+    let mut selected = vec![0];
+    let mut a = 0;
+    for i in 0..threshold - 2 {
+        let next = &points[bucket(i + 1)..bucket(i + 2).min(n)];
+        let average = if next.is_empty() {
+            points[n - 1]
+        } else {
+            let (x, y) = next
+                .iter()
+                .fold((0.0, 0.0), |(x, y), point| (x + point.0, y + point.1));
+            (x / next.len() as f64, y / next.len() as f64)
+        };
+        let (best, _) = (bucket(i)..bucket(i + 1)).fold((bucket(i), -1.0), |best, b| {
+            let area = area(points[a], points[b], average);
+            if area > best.1 {
+                (b, area)
+            } else {
+                best
+            }
+        });
+        selected.push(best);
+        a = best;
+    }
+    selected.push(n - 1);
+    selected
+}
+
+fn min_max(points: &[(f64, f64)], buckets: usize) -> Vec<usize> {
+    let n = points.len();
+    if 2 * buckets + 2 >= n {
+        return (0..n).collect();
+    }
+    let (start, stop) = (points[0].0, points[n - 1].0);
+    let width = (stop - start) / buckets as f64;
+    let mut extremes: Vec<Option<(usize, usize)>> = vec![None; buckets];
+    for (i, (x, y)) in points.iter().enumerate() {
+        let bucket = if width > 0.0 {
+            (((x - start) / width) as usize).min(buckets - 1)
+        } else {
+            0
+        };
+        match &mut extremes[bucket] {
+            Some((min, max)) => {
+                if *y < points[*min].1 {
+                    *min = i;
+                }
+                if *y > points[*max].1 {
+                    *max = i;
+                }
+            }
+            extreme => *extreme = Some((i, i)),
+        }
+    }
+    let mut selected = vec![0, n - 1];
+    selected.extend(
+        extremes
+            .into_iter()
+            .flatten()
+            .flat_map(|(min, max)| [min, max]),
+    );
+    selected.sort_unstable();
+    selected.dedup();
+    selected
+}
+
+/// A coordinate of a point: a number, or a date for temporal fields
+fn coordinate(row: &Value, path: &[String]) -> Option<f64> {
+    match Val::from(get_path(row, path)) {
+        Val::Number(n) if n.is_finite() => Some(n),
+        value @ Val::String(_) => datetime::parse(&value).map(Moment::ms),
+        _ => None,
+    }
+}
+
+impl Downsample {
+    fn select(&self, points: &[(f64, f64)], size: usize, rng: &mut Rng) -> Vec<usize> {
+        match self {
+            Downsample::Lttb(_) => lttb(points, size),
+            Downsample::MinMax(_) => min_max(points, size),
+            Downsample::Random(_) | Downsample::Stratified(_) => {
+                sample_indices(points.len(), size, rng)
+            }
+        }
+    }
+
+    fn size(&self) -> usize {
+        match self {
+            Downsample::Lttb(size)
+            | Downsample::MinMax(size)
+            | Downsample::Random(size)
+            | Downsample::Stratified(size) => *size,
+        }
+    }
+
+    /// The indices of the points to keep of a series sorted by x, in increasing order
+    pub fn indices(&self, points: &[(f64, f64)]) -> Vec<usize> {
+        if self.size() == 0 {
+            return (0..points.len()).collect();
+        }
+        self.select(points, self.size(), &mut Rng::default())
+    }
+
+    /// Downsample rows along their `x` and `y` fields, the rows being split into series by the
+    /// `series` fields; rows keep their order, and those without valid coordinates are kept
+    pub fn rows(&self, rows: &[Value], x: &str, y: &str, series: &[String]) -> Vec<Value> {
+        if self.size() == 0 {
+            return rows.to_vec();
+        }
+        let (x, y) = (field_path(x), field_path(y));
+        let series = match self {
+            Downsample::Random(_) => vec![(0..rows.len()).collect()],
+            _ => groups(rows, series),
+        };
+        let mut rng = Rng::default();
+        let mut keep = vec![false; rows.len()];
+        for group in series {
+            let mut points = vec![];
+            for i in group {
+                match (coordinate(&rows[i], &x), coordinate(&rows[i], &y)) {
+                    (Some(x), Some(y)) => points.push((i, (x, y))),
+                    _ => keep[i] = true,
+                }
+            }
+            points.sort_by(|a, b| a.1 .0.total_cmp(&b.1 .0));
+            let size = match self {
+                Downsample::Stratified(size) => {
+                    let share = *size as f64 * points.len() as f64 / rows.len() as f64;
+                    (share.round() as usize).max(1)
+                }
+                _ => self.size(),
+            };
+            let coordinates = points.iter().map(|(_, point)| *point).collect::<Vec<_>>();
+            for selected in self.select(&coordinates, size, &mut rng) {
+                keep[points[selected].0] = true;
+            }
+        }
+        rows.iter()
+            .zip(keep)
+            .filter(|(_, keep)| *keep)
+            .map(|(row, _)| row.clone())
+            .collect()
+    }
+}
+
+/// The field of a position channel, if it is a continuous field
+fn position_field(encoding: &Map<String, Value>, channel: &str) -> Option<String> {
+    let def = encoding.get(channel)?.as_object()?;
+    let continuous = matches!(
+        def.get("type").and_then(Value::as_str),
+        Some("quantitative" | "temporal")
+    );
+    let transformed = ["aggregate", "bin", "timeUnit"]
+        .iter()
+        .any(|property| def.get(*property).is_some_and(|value| !value.is_null()));
+    match def.get("field") {
+        Some(Value::String(field)) if continuous && !transformed => Some(field.clone()),
+        _ => None,
+    }
+}
+
+impl Vegalite {
+    /// Downsample the inline data of a line, area, point or trail chart along the x and y fields
+    /// of its encoding, the other fields of the encoding splitting the data into series.
+    ///
+    /// The transforms are [executed](#method.execute_transforms) first. The data is only
+    /// downsampled for single view charts when all the transforms could be executed, x and y
+    /// are quantitative or temporal fields, and no channel is aggregated or ordered. Returns
+    /// whether the data was downsampled, the chart being left untouched otherwise.
+    pub fn downsample(&mut self, strategy: Downsample) -> Result<bool, Error> {
+        if strategy.size() == 0 {
+            return Ok(false);
+        }
+        let (_, encoding, rows) = match self.single_view(|mark| POINT_MARKS.contains(&mark))? {
+            Some(view) => view,
+            None => return Ok(false),
+        };
+        let (x, y) = match (
+            position_field(&encoding, "x"),
+            position_field(&encoding, "y"),
+        ) {
+            (Some(x), Some(y)) => (x, y),
+            _ => return Ok(false),
+        };
+        if ["order", "x2", "y2"]
+            .iter()
+            .any(|channel| encoding.contains_key(*channel))
+        {
+            return Ok(false);
+        }
+        let mut series = vec![];
+        for (channel, value) in &encoding {
+            let defs = match value {
+                Value::Array(defs) => defs.iter().collect(),
+                def => vec![def],
+            };
+            for def in defs {
+                if def.get("aggregate").is_some() {
+                    return Ok(false);
+                }
+                match def.get("field") {
+                    Some(Value::String(field))
+                        if !NON_SERIES_CHANNELS.contains(&channel.as_str()) =>
+                    {
+                        series.push(field.clone())
+                    }
+                    _ => {}
+                }
+            }
+        }
+        let downsampled = strategy.rows(&rows, &x, &y, &series);
+        if downsampled.len() == rows.len() {
+            return Ok(false);
+        }
+        self.set_rows(downsampled);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn chart(spec: Value) -> Vegalite {
+        crate::with_big_stack(move || serde_json::from_value(spec).unwrap())
+    }
+
+    #[test]
+    fn test_downsample_points() {
+        let points = (0..100)
+            .map(|i| (i as f64, if i == 42 { 100.0 } else { (i % 7) as f64 }))
+            .collect::<Vec<_>>();
+        let lttb = Downsample::Lttb(10).indices(&points);
+        assert_eq!(lttb.len(), 10);
+        assert_eq!((lttb[0], lttb[9]), (0, 99));
+        assert!(lttb.contains(&42));
+        let min_max = Downsample::MinMax(5).indices(&points);
+        assert!(min_max.len() <= 12);
+        assert!(min_max.contains(&0) && min_max.contains(&42) && min_max.contains(&99));
+        let random = Downsample::Random(10).indices(&points);
+        assert_eq!(random.len(), 10);
+        assert!(random.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(Downsample::Lttb(200).indices(&points).len(), 100);
+        for strategy in [
+            Downsample::Lttb(0),
+            Downsample::MinMax(0),
+            Downsample::Random(0),
+            Downsample::Stratified(0),
+        ] {
+            assert_eq!(strategy.indices(&points).len(), 100);
+        }
+    }
+
+    #[test]
+    fn test_downsample_chart() {
+        let values = (0..1000)
+            .map(|i| json!({"t": i, "v": (i % 10), "s": if i % 4 == 0 { "a" } else { "b" }}))
+            .collect::<Vec<_>>();
+        let mut line = chart(json!({
+            "data": {"values": values},
+            "mark": "line",
+            "encoding": {
+                "x": {"field": "t", "type": "quantitative"},
+                "y": {"field": "v", "type": "quantitative"},
+                "color": {"field": "s", "type": "nominal"}
+            }
+        }));
+        let mut stratified = line.clone();
+        assert!(line.downsample(Downsample::Lttb(50)).unwrap());
+        let values = serde_json::to_value(&line).unwrap()["data"]["values"].clone();
+        let values = values.as_array().unwrap();
+        assert_eq!(values.len(), 100);
+        assert_eq!(values[0], json!({"t": 0, "v": 0, "s": "a"}));
+        assert!(values
+            .windows(2)
+            .all(|pair| pair[0]["t"].as_i64() < pair[1]["t"].as_i64()));
+
+        assert!(stratified.downsample(Downsample::Stratified(20)).unwrap());
+        let values = serde_json::to_value(&stratified).unwrap()["data"]["values"].clone();
+        let count = |series: &str| {
+            values
+                .as_array()
+                .unwrap()
+                .iter()
+                .filter(|row| row["s"] == json!(series))
+                .count()
+        };
+        assert_eq!((count("a"), count("b")), (5, 15));
+
+        let mut bar = chart(json!({
+            "data": {"values": [{"t": 1, "v": 2}, {"t": 2, "v": 3}, {"t": 3, "v": 1}]},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "t", "type": "quantitative"},
+                "y": {"field": "v", "type": "quantitative"}
+            }
+        }));
+        assert!(!bar.downsample(Downsample::Random(1)).unwrap());
+
+        let spec = json!({
+            "data": {"values": [{"t": 1, "v": 2}, {"t": 2, "v": 3}]},
+            "transform": [{"calculate": "datum.v * 2", "as": "w"}],
+            "mark": "line",
+            "encoding": {
+                "x": {"field": "t", "type": "quantitative"},
+                "y": {"field": "w", "type": "quantitative"}
+            }
+        });
+        let mut short = chart(spec.clone());
+        assert!(!short.downsample(Downsample::Lttb(10)).unwrap());
+        assert_eq!(serde_json::to_value(&short).unwrap(), spec);
+
+        let mut long = spec;
+        long["data"]["values"] = (0..20).map(|t| json!({"t": t, "v": t})).collect();
+        let mut long = chart(long);
+        let untouched = serde_json::to_value(&long).unwrap();
+        assert!(!long.downsample(Downsample::MinMax(0)).unwrap());
+        assert_eq!(serde_json::to_value(&long).unwrap(), untouched);
+        assert!(long.downsample(Downsample::Lttb(10)).unwrap());
+        let long = serde_json::to_value(&long).unwrap();
+        assert!(long.get("transform").is_none());
+        let values = long["data"]["values"].as_array().unwrap();
+        assert_eq!(values.len(), 10);
+        assert!(values
+            .iter()
+            .all(|row| row["w"] == json!(row["v"].as_i64().unwrap() * 2)));
+    }
+}
@@ -0,0 +1,509 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Pre-aggregation of the aggregating encodings of a chart.
+//!
+//! The encoding is handled as json, to go through all the field definitions of the different
+//! channels the same way.
+
+use super::aggregate::{aggregate, op_name, output_name};
+use super::bin::{self, Bins};
+use super::datetime::{self, Units};
+use super::value::{number, Val};
+use super::{field_path, get_path, partition, GroupKey, Unsupported};
+use crate::error::Error;
+use crate::schema::*;
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet};
+
+/// Marks for which a binned channel is drawn from the start to the end of the bin
+const RANGED_MARKS: &[&str] = &["bar", "rect"];
+/// Marks that aggregate their data themselves
+const COMPOSITE_MARKS: &[&str] = &["boxplot", "errorband", "errorbar"];
+const DEFAULT_COUNT_TITLE: &str = "Count of Records";
+
+/// How a field definition takes part in the aggregation
+enum Role {
+    /// A field grouped by as is
+    Field(String),
+    /// A field grouped by after truncation to a time unit
+    TimeUnit(String, TimeUnit),
+    /// A field grouped by bins
+    Bin(String, BinParams),
+    /// An aggregated field
+    Measure(AggregateOp, Option<String>),
+    /// A constant value
+    Value,
+}
+
+impl Role {
+    /// Whether two field definitions compute the same fields
+    fn same(&self, other: &Role) -> bool {
+        match (self, other) {
+            (Role::Field(a), Role::Field(b)) => a == b,
+            (Role::TimeUnit(a, unit_a), Role::TimeUnit(b, unit_b)) => {
+                a == b && op_name(unit_a) == op_name(unit_b)
+            }
+            (Role::Bin(a, params_a), Role::Bin(b, params_b)) => {
+                a == b && serde_json::to_value(params_a).ok() == serde_json::to_value(params_b).ok()
+            }
+            (Role::Measure(op_a, a), Role::Measure(op_b, b)) => {
+                a == b && op_name(op_a) == op_name(op_b)
+            }
+            _ => false,
+        }
+    }
+
+    /// The names of the pre-computed fields
+    fn names(&self) -> Vec<String> {
+        match self {
+            Role::Field(field) => vec![field.clone()],
+            Role::TimeUnit(field, time_unit) => vec![format!("{}_{}", op_name(time_unit), field)],
+            Role::Bin(field, _) => vec![format!("bin_{}", field), format!("bin_{}_end", field)],
+            Role::Measure(op, field) => vec![output_name(op, field.as_deref())],
+            Role::Value => vec![],
+        }
+    }
+
+    /// The title Vega-Lite gives to the channel, see `defaultTitle`
+    fn title(&self, config: Option<&Config>) -> String {
+        let field_title = config.and_then(|config| config.field_title.clone());
+        let count_title = config
+            .and_then(|config| config.count_title.clone())
+            .unwrap_or_else(|| DEFAULT_COUNT_TITLE.to_string());
+        let (function, field) = match self {
+            Role::Field(field) => return field.clone(),
+            Role::TimeUnit(field, time_unit) => (op_name(time_unit), field),
+            Role::Bin(field, _) => ("bin".to_string(), field),
+            Role::Measure(op, field) => (op_name(op), field.as_ref().unwrap_or(&count_title)),
+            Role::Value => return String::new(),
+        };
+        match (field_title, self) {
+            (Some(FieldTitle::Functional), Role::Measure(AggregateOp::Count, _)) => {
+                "COUNT(*)".to_string()
+            }
+            (Some(FieldTitle::Functional), _) => format!("{}({})", function.to_uppercase(), field),
+            (_, Role::Measure(AggregateOp::Count, _)) => count_title,
+            (Some(FieldTitle::Plain), _) => field.clone(),
+            (_, Role::TimeUnit(_, time_unit)) => {
+                format!("{} ({})", field, Units::of(time_unit).parts().join("-"))
+            }
+            (_, Role::Bin(..)) => format!("{} (binned)", field),
+            _ => format!(
+                "{}{} of {}",
+                function[..1].to_uppercase(),
+                &function[1..],
+                field
+            ),
+        }
+    }
+}
+
+/// The title Vega-Lite gives to a field definition that aggregates, bins or truncates a field to
+/// a time unit, `None` for other definitions
+pub(crate) fn derived_title(def: &Map<String, Value>, config: Option<&Config>) -> Option<String> {
+    let field = def.get("field").and_then(Value::as_str).map(str::to_string);
+    let role = if let Some(op) = def.get("aggregate") {
+        Role::Measure(parse(op).ok()?, field)
+    } else if let Some(time_unit) = def.get("timeUnit") {
+        Role::TimeUnit(field?, parse(time_unit).ok()?)
+    } else if def
+        .get("bin")
+        .is_some_and(|bin| !bin.is_null() && *bin != Value::Bool(false))
+    {
+        Role::Bin(field?, BinParams::default())
+    } else {
+        return None;
+    };
+    Some(role.title(config))
+}
+
+/// The field definitions of the channels of an encoding
+fn field_defs(encoding: &mut Map<String, Value>) -> Vec<(String, &mut Map<String, Value>)> {
+    let mut defs = vec![];
+    for (channel, value) in encoding.iter_mut() {
+        match value {
+            Value::Object(def) => defs.push((channel.clone(), def)),
+            Value::Array(items) => defs.extend(items.iter_mut().filter_map(|item| match item {
+                Value::Object(def) => Some((channel.clone(), def)),
+                _ => None,
+            })),
+            _ => {}
+        }
+    }
+    defs
+}
+
+fn parse<T: serde::de::DeserializeOwned>(value: &Value) -> Result<T, Unsupported> {
+    serde_json::from_value(value.clone()).map_err(|_| Unsupported(format!("`{}`", value)))
+}
+
+fn role(
+    channel: &str,
+    def: &Map<String, Value>,
+    mark: &str,
+    channels: &HashSet<String>,
+) -> Result<Role, Unsupported> {
+    let unsupported = |reason: &str| Err(Unsupported(format!("{} on `{}`", reason, channel)));
+    if def.contains_key("condition") || def.contains_key("impute") {
+        return unsupported("condition or impute");
+    }
+    if def.get("sort").is_some_and(Value::is_object) {
+        return unsupported("sort by another field");
+    }
+    let field = match def.get("field") {
+        Some(Value::String(field)) if field.contains(['.', '[', '\\']) => {
+            return unsupported("nested field")
+        }
+        Some(Value::String(field)) => Some(field.clone()),
+        Some(_) => return unsupported("repeated field"),
+        None => None,
+    };
+    let bin = def
+        .get("bin")
+        .filter(|bin| !bin.is_null() && **bin != Value::Bool(false));
+    let time_unit = def.get("timeUnit");
+    if let Some(op) = def.get("aggregate") {
+        let op = parse::<AggregateOp>(op)?;
+        if bin.is_some() || time_unit.is_some() {
+            return unsupported("aggregate of bins or time units");
+        }
+        if matches!(
+            op,
+            AggregateOp::Values | AggregateOp::Ci0 | AggregateOp::Ci1
+        ) {
+            return unsupported(&format!("`{}`", op_name(&op)));
+        }
+        return Ok(Role::Measure(op, field));
+    }
+    let field = match field {
+        Some(field) => field,
+        None => return Ok(Role::Value),
+    };
+    if let Some(time_unit) = time_unit {
+        return Ok(Role::TimeUnit(field, parse(time_unit)?));
+    }
+    match bin {
+        None => Ok(Role::Field(field)),
+        Some(bin) => {
+            let secondary = format!("{}2", channel);
+            if !["x", "y"].contains(&channel)
+                || !RANGED_MARKS.contains(&mark)
+                || def.get("type") != Some(&json!("quantitative"))
+                || channels.contains(&secondary)
+            {
+                return unsupported("bin");
+            }
+            Ok(Role::Bin(field, bin::params(&parse(bin)?)?))
+        }
+    }
+}
+
+/// Group the rows by the dimensions of the encoding and compute its measures, rewriting the
+/// encoding to reference the pre-computed fields
+fn aggregate_encoding(
+    encoding: &mut Map<String, Value>,
+    mark: &str,
+    rows: &[Value],
+    config: Option<&Config>,
+) -> Result<Vec<Value>, Unsupported> {
+    let channels = encoding.keys().cloned().collect::<HashSet<_>>();
+    let roles = field_defs(encoding)
+        .into_iter()
+        .map(|(channel, def)| role(&channel, def, mark, &channels))
+        .collect::<Result<Vec<_>, _>>()?;
+    if !roles.iter().any(|role| matches!(role, Role::Measure(..))) {
+        return Err(Unsupported("no aggregated channel".to_string()));
+    }
+
+    // the pre-computed fields, each computed once
+    let mut computed: HashMap<String, &Role> = HashMap::new();
+    let mut dimensions = vec![];
+    let mut measures = vec![];
+    for role in &roles {
+        let names = role.names();
+        if names
+            .iter()
+            .any(|name| computed.get(name).is_some_and(|other| !other.same(role)))
+        {
+            return Err(Unsupported(format!(
+                "fields `{}` computed twice",
+                names.join("`, `")
+            )));
+        }
+        if names.is_empty() || computed.contains_key(&names[0]) {
+            continue;
+        }
+        computed.extend(names.into_iter().map(|name| (name, role)));
+        if let Role::Measure(..) = role {
+            measures.push(role);
+        } else {
+            dimensions.push(role);
+        }
+    }
+
+    // the values of the dimensions of each row
+    let mut steps = HashMap::new();
+    let mut columns = vec![];
+    for dimension in &dimensions {
+        match dimension {
+            Role::Field(field) => columns.push(
+                rows.iter()
+                    .map(|row| {
+                        get_path(row, &field_path(field))
+                            .cloned()
+                            .unwrap_or(Value::Null)
+                    })
+                    .collect::<Vec<_>>(),
+            ),
+            Role::TimeUnit(field, time_unit) => columns.push(
+                rows.iter()
+                    .map(|row| {
+                        let value = Val::from(get_path(row, &field_path(field)));
+                        Ok(datetime::truncate_value(time_unit, &value)?.to_json())
+                    })
+                    .collect::<Result<Vec<_>, Unsupported>>()?,
+            ),
+            Role::Bin(field, params) => {
+                let (bins, starts): (Bins, _) = bin::bin_rows(params, rows, &field_path(field))?;
+                steps.insert(dimension.names()[0].clone(), bins.step);
+                let value = |bin: Option<f64>| bin.map_or(Value::Null, number);
+                columns.push(starts.iter().map(|start| value(*start)).collect());
+                columns.push(
+                    starts
+                        .iter()
+                        .map(|start| value(start.map(|start| start + bins.step)))
+                        .collect(),
+                );
+            }
+            _ => unreachable!("not a dimension"),
+        }
+    }
+    let names = dimensions
+        .iter()
+        .flat_map(|dimension| dimension.names())
+        .collect::<Vec<_>>();
+
+    let groups = partition(rows.len(), |i| {
+        columns
+            .iter()
+            .map(|column| GroupKey::from(Some(&column[i])))
+            .collect()
+    });
+    let mut aggregated = Vec::with_capacity(groups.len());
+    for indices in groups {
+        // the values of the dimensions are those of the first row of the group
+        let key = columns.iter().map(|column| column[indices[0]].clone());
+        let mut row = names.iter().cloned().zip(key).collect::<Map<_, _>>();
+        let group = indices.iter().map(|i| &rows[*i]).collect::<Vec<_>>();
+        for measure in &measures {
+            if let Role::Measure(op, field) = measure {
+                let value = aggregate(op, &group, field.as_deref().map(field_path).as_deref())?;
+                row.insert(measure.names()[0].clone(), value);
+            }
+        }
+        aggregated.push(Value::Object(row));
+    }
+
+    let mut secondary = vec![];
+    for ((channel, def), role) in field_defs(encoding).into_iter().zip(&roles) {
+        let names = role.names();
+        match role {
+            Role::Field(_) | Role::Value => continue,
+            Role::Measure(..) => {
+                def.remove("aggregate");
+            }
+            Role::TimeUnit(..) => {}
+            Role::Bin(..) => {
+                def.insert(
+                    "bin".to_string(),
+                    json!({"binned": true, "step": steps[&names[0]]}),
+                );
+                secondary.push((format!("{}2", channel), json!({ "field": names[1] })));
+            }
+        }
+        def.insert("field".to_string(), Value::String(names[0].clone()));
+        if !def.contains_key("title") {
+            def.insert("title".to_string(), Value::String(role.title(config)));
+        }
+    }
+    encoding.extend(secondary);
+    Ok(aggregated)
+}
+
+impl Vegalite {
+    /// Compute the aggregations of the encoding of the chart on its inline data, and rewrite the
+    /// encoding to reference the pre-computed fields, so that the browser renders the same chart
+    /// from a few rows.
+    ///
+    /// The transforms are [executed](#method.execute_transforms) first. Aggregations are only
+    /// pre-computed for single view charts without selection, when all the transforms could be
+    /// executed and all the channels can be computed. Returns whether the chart was
+    /// pre-aggregated, the chart being left untouched otherwise.
+    pub fn pre_aggregate(&mut self) -> Result<bool, Error> {
+        if self.selection.is_some() {
+            return Ok(false);
+        }
+        let (mark, mut encoding, rows) =
+            match self.single_view(|mark| !COMPOSITE_MARKS.contains(&mark))? {
+                Some(view) => view,
+                None => return Ok(false),
+            };
+        let aggregated = match aggregate_encoding(&mut encoding, &mark, &rows, self.config.as_ref())
+        {
+            Ok(aggregated) => aggregated,
+            Err(Unsupported(_)) => return Ok(false),
+        };
+        self.encoding = Some(serde_json::from_value(Value::Object(encoding))?);
+        self.set_rows(aggregated);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart(spec: Value) -> Vegalite {
+        crate::with_big_stack(move || serde_json::from_value(spec).unwrap())
+    }
+
+    fn json_of(chart: &Vegalite) -> Value {
+        serde_json::to_value(chart).unwrap()
+    }
+
+    #[test]
+    fn test_pre_aggregate_histogram() {
+        let mut histogram = chart(json!({
+            "data": {"values": [{"a": 1.3}, {"a": 47.2}, {"a": 10}, {"a": 12}]},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "a", "bin": true, "type": "quantitative"},
+                "y": {"aggregate": "count", "type": "quantitative"}
+            }
+        }));
+        assert!(histogram.pre_aggregate().unwrap());
+        assert_eq!(
+            json_of(&histogram),
+            json!({
+                "data": {"values": [
+                    {"bin_a": 0, "bin_a_end": 5, "count": 1},
+                    {"bin_a": 45, "bin_a_end": 50, "count": 1},
+                    {"bin_a": 10, "bin_a_end": 15, "count": 2}
+                ]},
+                "mark": "bar",
+                "encoding": {
+                    "x": {
+                        "field": "bin_a",
+                        "bin": {"binned": true, "step": 5.0},
+                        "type": "quantitative",
+                        "title": "a (binned)"
+                    },
+                    "x2": {"field": "bin_a_end"},
+                    "y": {"field": "count", "type": "quantitative", "title": "Count of Records"}
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_pre_aggregate_time_unit_and_transforms() {
+        let mut line = chart(json!({
+            "data": {"values": [
+                {"date": "2012-01-03T00:00", "price": 10, "symbol": "A"},
+                {"date": "2012-01-20T00:00", "price": 20, "symbol": "A"},
+                {"date": "2012-02-03T00:00", "price": 30, "symbol": "A"},
+                {"date": "2012-01-03T00:00", "price": 40, "symbol": "B"}
+            ]},
+            "transform": [{"filter": "datum.price > 10"}],
+            "mark": "line",
+            "encoding": {
+                "x": {"field": "date", "timeUnit": "yearmonth", "type": "temporal"},
+                "y": {"field": "price", "aggregate": "mean", "type": "quantitative"},
+                "color": {"field": "symbol", "type": "nominal"}
+            }
+        }));
+        assert!(line.pre_aggregate().unwrap());
+        assert_eq!(
+            json_of(&line)["data"]["values"],
+            json!([
+                {"yearmonth_date": "2012-01-01T00:00:00", "symbol": "A", "mean_price": 20},
+                {"yearmonth_date": "2012-02-01T00:00:00", "symbol": "A", "mean_price": 30},
+                {"yearmonth_date": "2012-01-01T00:00:00", "symbol": "B", "mean_price": 40}
+            ])
+        );
+        let encoding = &json_of(&line)["encoding"];
+        assert_eq!(
+            encoding["x"],
+            json!({
+                "field": "yearmonth_date",
+                "timeUnit": "yearmonth",
+                "type": "temporal",
+                "title": "date (year-month)"
+            })
+        );
+        assert_eq!(encoding["y"]["title"], json!("Mean of price"));
+        assert_eq!(
+            encoding["color"],
+            json!({"field": "symbol", "type": "nominal"})
+        );
+    }
+
+    #[test]
+    fn test_pre_aggregate_number_keys() {
+        let mut bar = chart(json!({
+            "data": {"values": [
+                {"k": 1, "v": 1},
+                {"k": 1.0, "v": 2},
+                {"k": -0.0, "v": 3},
+                {"k": 0, "v": 4}
+            ]},
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "k", "type": "ordinal"},
+                "y": {"field": "v", "aggregate": "sum", "type": "quantitative"}
+            }
+        }));
+        assert!(bar.pre_aggregate().unwrap());
+        assert_eq!(
+            json_of(&bar)["data"]["values"],
+            json!([{"k": 1, "sum_v": 3}, {"k": -0.0, "sum_v": 7}])
+        );
+    }
+
+    #[test]
+    fn test_pre_aggregate_unsupported() {
+        let spec = json!({
+            "data": {"values": [{"a": 1, "b": 2}]},
+            "mark": "point",
+            "encoding": {
+                "x": {"field": "a", "bin": true, "type": "quantitative"},
+                "y": {"aggregate": "count", "type": "quantitative"}
+            }
+        });
+        let mut point = chart(spec.clone());
+        assert!(!point.pre_aggregate().unwrap());
+        assert_eq!(json_of(&point), spec);
+
+        let spec = json!({
+            "data": {"values": [{"a": 1, "b": 2}]},
+            "transform": [{"calculate": "datum.a * 2", "as": "c"}],
+            "mark": "point",
+            "encoding": {
+                "x": {"field": "c", "type": "quantitative"},
+                "y": {"field": "b", "type": "quantitative"}
+            }
+        });
+        let mut scatter = chart(spec.clone());
+        assert!(!scatter.pre_aggregate().unwrap());
+        assert_eq!(json_of(&scatter), spec);
+    }
+}
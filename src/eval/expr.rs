@@ -0,0 +1,553 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Evaluation of expressions on a datum.
+//!
+//! Only the pure functions of the expression library that don't depend on the time zone of the
+//! browser are supported; anything else is `Unsupported`.
+
+use super::datetime::{self, Moment};
+use super::value::{js_compare, loose_eq, strict_eq, Val};
+use super::Unsupported;
+use crate::expr::{BinaryOp, Expr, UnaryOp};
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// Evaluate an expression, `datum` being bound to a row
+pub(crate) fn evaluate(expr: &Expr, datum: &Value) -> Result<Val, Unsupported> {
+    match expr {
+        Expr::Null => Ok(Val::Null),
+        Expr::Bool(b) => Ok(Val::Bool(*b)),
+        Expr::Number(n) => Ok(Val::Number(*n)),
+        Expr::String(s) => Ok(Val::String(s.clone())),
+        Expr::Regex(..) => Err(Unsupported("regular expressions".to_string())),
+        Expr::Array(items) => Ok(Val::Array(
+            items
+                .iter()
+                .map(|item| evaluate(item, datum))
+                .collect::<Result<_, _>>()?,
+        )),
+        Expr::Object(entries) => Ok(Val::Object(
+            entries
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), evaluate(value, datum)?)))
+                .collect::<Result<_, _>>()?,
+        )),
+        Expr::Variable(name) => variable(name, datum),
+        Expr::Member(object, property) => match &**object {
+            Expr::Variable(name) if name == "datum" => Ok(Val::from(datum.get(property))),
+            object => member(evaluate(object, datum)?, &Val::String(property.clone())),
+        },
+        Expr::Index(object, index) => {
+            let index = evaluate(index, datum)?;
+            match (&**object, &index) {
+                (Expr::Variable(name), Val::String(property)) if name == "datum" => {
+                    Ok(Val::from(datum.get(property)))
+                }
+                (object, _) => member(evaluate(object, datum)?, &index),
+            }
+        }
+        Expr::Call(name, args) if name == "if" => {
+            if evaluate(&args[0], datum)?.truthy() {
+                evaluate(&args[1], datum)
+            } else {
+                evaluate(&args[2], datum)
+            }
+        }
+        Expr::Call(name, args) => {
+            let args = args
+                .iter()
+                .map(|arg| evaluate(arg, datum))
+                .collect::<Result<Vec<_>, _>>()?;
+            function(name, &args)
+        }
+        Expr::Unary(op, operand) => {
+            let operand = evaluate(operand, datum)?;
+            Ok(match op {
+                UnaryOp::Not => Val::Bool(!operand.truthy()),
+                UnaryOp::Neg => Val::Number(-operand.to_number()),
+                UnaryOp::Plus => Val::Number(operand.to_number()),
+                UnaryOp::BitNot => Val::Number(f64::from(!to_int32(operand.to_number()))),
+            })
+        }
+        Expr::Binary(BinaryOp::And, left, right) => {
+            let left = evaluate(left, datum)?;
+            if left.truthy() {
+                evaluate(right, datum)
+            } else {
+                Ok(left)
+            }
+        }
+        Expr::Binary(BinaryOp::Or, left, right) => {
+            let left = evaluate(left, datum)?;
+            if left.truthy() {
+                Ok(left)
+            } else {
+                evaluate(right, datum)
+            }
+        }
+        Expr::Binary(op, left, right) => Ok(binary(
+            *op,
+            &evaluate(left, datum)?,
+            &evaluate(right, datum)?,
+        )),
+        Expr::Ternary(test, then, otherwise) => {
+            if evaluate(test, datum)?.truthy() {
+                evaluate(then, datum)
+            } else {
+                evaluate(otherwise, datum)
+            }
+        }
+    }
+}
+
+fn variable(name: &str, datum: &Value) -> Result<Val, Unsupported> {
+    let constant = match name {
+        "datum" => return Ok(Val::from(datum)),
+        "NaN" => f64::NAN,
+        "E" => std::f64::consts::E,
+        "LN2" => std::f64::consts::LN_2,
+        "LN10" => std::f64::consts::LN_10,
+        "LOG2E" => std::f64::consts::LOG2_E,
+        "LOG10E" => std::f64::consts::LOG10_E,
+        "PI" => std::f64::consts::PI,
+        "SQRT1_2" => std::f64::consts::FRAC_1_SQRT_2,
+        "SQRT2" => std::f64::consts::SQRT_2,
+        "MIN_VALUE" => 5e-324,
+        "MAX_VALUE" => f64::MAX,
+        _ => return Err(Unsupported(format!("variable `{}`", name))),
+    };
+    Ok(Val::Number(constant))
+}
+
+fn member(object: Val, property: &Val) -> Result<Val, Unsupported> {
+    let index = || {
+        let index = property.to_number();
+        Some(index as usize).filter(|_| index >= 0.0 && index.fract() == 0.0)
+    };
+    Ok(match (object, property) {
+        (Val::Undefined, _) | (Val::Null, _) => {
+            return Err(Unsupported(format!(
+                "access to `{}` of null",
+                property.to_js_string()
+            )))
+        }
+        (Val::Array(items), Val::String(key)) if key == "length" => Val::Number(items.len() as f64),
+        (Val::String(s), Val::String(key)) if key == "length" => {
+            Val::Number(s.encode_utf16().count() as f64)
+        }
+        (Val::Array(mut items), _) => match index() {
+            Some(index) if index < items.len() => items.swap_remove(index),
+            _ => Val::Undefined,
+        },
+        (Val::String(s), _) => match index() {
+            Some(index) => s
+                .chars()
+                .nth(index)
+                .map_or(Val::Undefined, |c| Val::String(c.to_string())),
+            None => Val::Undefined,
+        },
+        (Val::Object(entries), property) => {
+            let key = property.to_js_string();
+            entries
+                .into_iter()
+                .find(|(candidate, _)| *candidate == key)
+                .map_or(Val::Undefined, |(_, value)| value)
+        }
+        _ => Val::Undefined,
+    })
+}
+
+fn to_int32(n: f64) -> i32 {
+    if n.is_finite() {
+        n.trunc().rem_euclid(4_294_967_296.0) as u32 as i32
+    } else {
+        0
+    }
+}
+
+fn binary(op: BinaryOp, left: &Val, right: &Val) -> Val {
+    let number = |f: fn(f64, f64) -> f64| Val::Number(f(left.to_number(), right.to_number()));
+    let int = |f: fn(i32, i32) -> i32| {
+        Val::Number(f64::from(f(
+            to_int32(left.to_number()),
+            to_int32(right.to_number()),
+        )))
+    };
+    let compare = |f: fn(Ordering) -> bool| Val::Bool(js_compare(left, right).is_some_and(f));
+    match op {
+        BinaryOp::Or | BinaryOp::And => unreachable!("short-circuiting operators"),
+        BinaryOp::BitOr => int(|a, b| a | b),
+        BinaryOp::BitXor => int(|a, b| a ^ b),
+        BinaryOp::BitAnd => int(|a, b| a & b),
+        BinaryOp::Eq => Val::Bool(loose_eq(left, right)),
+        BinaryOp::Neq => Val::Bool(!loose_eq(left, right)),
+        BinaryOp::StrictEq => Val::Bool(strict_eq(left, right)),
+        BinaryOp::StrictNeq => Val::Bool(!strict_eq(left, right)),
+        BinaryOp::Lt => compare(Ordering::is_lt),
+        BinaryOp::Lte => compare(Ordering::is_le),
+        BinaryOp::Gt => compare(Ordering::is_gt),
+        BinaryOp::Gte => compare(Ordering::is_ge),
+        BinaryOp::Shl => int(|a, b| a.wrapping_shl(b as u32 & 31)),
+        BinaryOp::Shr => int(|a, b| a >> (b as u32 & 31)),
+        BinaryOp::UShr => Val::Number(f64::from(
+            (to_int32(left.to_number()) as u32) >> (to_int32(right.to_number()) as u32 & 31),
+        )),
+        BinaryOp::Add => {
+            let is_string =
+                |value: &Val| matches!(value, Val::String(_) | Val::Array(_) | Val::Object(_));
+            if is_string(left) || is_string(right) {
+                Val::String(left.to_js_string() + &right.to_js_string())
+            } else {
+                number(|a, b| a + b)
+            }
+        }
+        BinaryOp::Sub => number(|a, b| a - b),
+        BinaryOp::Mul => number(|a, b| a * b),
+        BinaryOp::Div => number(|a, b| a / b),
+        BinaryOp::Rem => number(|a, b| a % b),
+    }
+}
+
+/// `Math.round`, rounding half up
+fn round(n: f64) -> f64 {
+    let floor = n.floor();
+    if n - floor >= 0.5 {
+        floor + 1.0
+    } else {
+        floor
+    }
+}
+
+/// A relative index of `slice`, `substring`..., negative indices counting from the end if
+/// `from_end`
+fn relative_index(index: Option<&Val>, len: usize, default: usize, from_end: bool) -> usize {
+    let index = match index {
+        None | Some(Val::Undefined) => return default,
+        Some(index) => index.to_number(),
+    };
+    let index = if index.is_nan() { 0.0 } else { index.trunc() };
+    let index = if from_end && index < 0.0 {
+        len as f64 + index
+    } else {
+        index
+    };
+    index.clamp(0.0, len as f64) as usize
+}
+
+fn date_part(name: &str, arg: &Val) -> Result<Val, Unsupported> {
+    let (utc, part) = match name.strip_prefix("utc") {
+        Some(part) => (true, part),
+        None => (false, name),
+    };
+    let moment = datetime::parse(arg)
+        .filter(|moment| moment.utc() == utc)
+        .ok_or_else(|| {
+            Unsupported(format!(
+                "`{}` of `{}`, which depends on the time zone",
+                name,
+                arg.to_js_string()
+            ))
+        })?;
+    let f = datetime::fields(moment.ms());
+    let value = match part {
+        "year" => f.year,
+        "quarter" => f.month / 3 + 1,
+        "month" => f.month,
+        "date" => f.date,
+        "day" => f.day,
+        "hours" => f.hours,
+        "minutes" => f.minutes,
+        "seconds" => f.seconds,
+        "milliseconds" => f.milliseconds,
+        _ => unreachable!("not a date part"),
+    };
+    Ok(Val::Number(value as f64))
+}
+
+fn numbers(args: &[Val]) -> Vec<f64> {
+    args.iter().map(Val::to_number).collect()
+}
+
+fn function(name: &str, args: &[Val]) -> Result<Val, Unsupported> {
+    let arg = |i: usize| args.get(i).cloned().unwrap_or(Val::Undefined);
+    let n = |i: usize| arg(i).to_number();
+    let s = |i: usize| arg(i).to_js_string();
+    let math = |f: fn(f64) -> f64| Ok(Val::Number(f(n(0))));
+    let array = |i: usize| match arg(i) {
+        Val::Array(items) => Ok(items),
+        value => Err(Unsupported(format!(
+            "`{}` of `{}`",
+            name,
+            value.to_js_string()
+        ))),
+    };
+    Ok(match name {
+        "isArray" => Val::Bool(matches!(arg(0), Val::Array(_))),
+        "isBoolean" => Val::Bool(matches!(arg(0), Val::Bool(_))),
+        "isDate" | "isRegExp" => Val::Bool(false),
+        "isDefined" => Val::Bool(arg(0) != Val::Undefined),
+        "isNumber" => Val::Bool(matches!(arg(0), Val::Number(_))),
+        "isObject" => Val::Bool(matches!(arg(0), Val::Array(_) | Val::Object(_))),
+        "isString" => Val::Bool(matches!(arg(0), Val::String(_))),
+        "isValid" => Val::Bool(arg(0).is_valid()),
+        "isNaN" => Val::Bool(matches!(arg(0), Val::Number(n) if n.is_nan())),
+        "isFinite" => Val::Bool(matches!(arg(0), Val::Number(n) if n.is_finite())),
+        "toBoolean" | "toNumber" | "toString"
+            if arg(0).is_nullish() || arg(0) == Val::String(String::new()) =>
+        {
+            Val::Null
+        }
+        "toBoolean" => Val::Bool(match arg(0) {
+            Val::String(s) if s == "false" || s == "0" => false,
+            value => value.truthy(),
+        }),
+        "toNumber" => Val::Number(n(0)),
+        "toString" => Val::String(s(0)),
+        "abs" => return math(f64::abs),
+        "acos" => return math(f64::acos),
+        "asin" => return math(f64::asin),
+        "atan" => return math(f64::atan),
+        "atan2" => Val::Number(n(0).atan2(n(1))),
+        "ceil" => return math(f64::ceil),
+        "clamp" => Val::Number(n(1).max(n(2).min(n(0)))),
+        "cos" => return math(f64::cos),
+        "exp" => return math(f64::exp),
+        "floor" => return math(f64::floor),
+        "log" => return math(f64::ln),
+        "max" | "min" => {
+            let numbers = numbers(args);
+            let result = if numbers.iter().any(|n| n.is_nan()) {
+                f64::NAN
+            } else if name == "max" {
+                numbers.into_iter().fold(f64::NEG_INFINITY, f64::max)
+            } else {
+                numbers.into_iter().fold(f64::INFINITY, f64::min)
+            };
+            Val::Number(result)
+        }
+        "pow" => Val::Number(n(0).powf(n(1))),
+        "round" => return math(round),
+        "sin" => return math(f64::sin),
+        "sqrt" => return math(f64::sqrt),
+        "tan" => return math(f64::tan),
+        "year" | "quarter" | "month" | "date" | "day" | "hours" | "minutes" | "seconds"
+        | "milliseconds" | "utcyear" | "utcquarter" | "utcmonth" | "utcdate" | "utcday"
+        | "utchours" | "utcminutes" | "utcseconds" | "utcmilliseconds" => {
+            return date_part(name, &arg(0))
+        }
+        "time" => match datetime::parse(&arg(0)) {
+            Some(Moment::Utc(ms)) => Val::Number(ms),
+            _ => {
+                return Err(Unsupported(format!(
+                    "`time` of `{}`, which depends on the time zone",
+                    s(0)
+                )))
+            }
+        },
+        "extent" => {
+            let valid = array(0)?
+                .into_iter()
+                .filter(Val::is_valid)
+                .map(|value| value.to_number())
+                .collect::<Vec<_>>();
+            if valid.is_empty() {
+                Val::Array(vec![Val::Undefined, Val::Undefined])
+            } else {
+                Val::Array(vec![
+                    Val::Number(valid.iter().copied().fold(f64::INFINITY, f64::min)),
+                    Val::Number(valid.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+                ])
+            }
+        }
+        "inrange" => {
+            let range = numbers(&array(1)?);
+            let (min, max) = match range.as_slice() {
+                [a, b] => (a.min(*b), a.max(*b)),
+                _ => return Err(Unsupported("`inrange` without a range".to_string())),
+            };
+            let value = n(0);
+            Val::Bool(min <= value && value <= max)
+        }
+        "indexof" | "lastindexof" => {
+            let position = match arg(0) {
+                Val::Array(items) => {
+                    let found = |item: &Val| strict_eq(item, &arg(1));
+                    if name == "indexof" {
+                        items.iter().position(found)
+                    } else {
+                        items.iter().rposition(found)
+                    }
+                }
+                Val::String(haystack) => {
+                    let needle = s(1);
+                    let byte = if name == "indexof" {
+                        haystack.find(&needle)
+                    } else {
+                        haystack.rfind(&needle)
+                    };
+                    byte.map(|byte| haystack[..byte].chars().count())
+                }
+                _ => return Err(Unsupported(format!("`{}` of `{}`", name, s(0)))),
+            };
+            Val::Number(position.map_or(-1.0, |position| position as f64))
+        }
+        "join" => {
+            let separator = match arg(1) {
+                Val::Undefined => ",".to_string(),
+                separator => separator.to_js_string(),
+            };
+            Val::String(
+                array(0)?
+                    .iter()
+                    .map(|item| {
+                        if item.is_nullish() {
+                            String::new()
+                        } else {
+                            item.to_js_string()
+                        }
+                    })
+                    .collect::<Vec<_>>()
+                    .join(&separator),
+            )
+        }
+        "length" => member(arg(0), &Val::String("length".to_string()))?,
+        "peek" => array(0)?.pop().unwrap_or(Val::Undefined),
+        "reverse" => {
+            let mut items = array(0)?;
+            items.reverse();
+            Val::Array(items)
+        }
+        "span" => {
+            let items = numbers(&array(0)?);
+            match (items.first(), items.last()) {
+                (Some(first), Some(last)) => Val::Number(last - first),
+                _ => Val::Number(0.0),
+            }
+        }
+        "slice" => match arg(0) {
+            Val::Array(items) => {
+                let start = relative_index(args.get(1), items.len(), 0, true);
+                let end = relative_index(args.get(2), items.len(), items.len(), true);
+                Val::Array(
+                    items
+                        .get(start..end.max(start))
+                        .unwrap_or_default()
+                        .to_vec(),
+                )
+            }
+            value => {
+                let chars = value.to_js_string().chars().collect::<Vec<_>>();
+                let start = relative_index(args.get(1), chars.len(), 0, true);
+                let end = relative_index(args.get(2), chars.len(), chars.len(), true);
+                Val::String(chars[start..end.max(start)].iter().collect())
+            }
+        },
+        "substring" => {
+            let chars = s(0).chars().collect::<Vec<_>>();
+            let start = relative_index(args.get(1), chars.len(), 0, false);
+            let end = relative_index(args.get(2), chars.len(), chars.len(), false);
+            Val::String(chars[start.min(end)..start.max(end)].iter().collect())
+        }
+        "lower" => Val::String(s(0).to_lowercase()),
+        "upper" => Val::String(s(0).to_uppercase()),
+        "trim" => Val::String(s(0).trim().to_string()),
+        "parseFloat" => {
+            let text = s(0);
+            let text = text.trim_start();
+            let prefix = (0..=text.len())
+                .rev()
+                .filter(|end| text.is_char_boundary(*end))
+                .find(|end| {
+                    let candidate = &text[..*end];
+                    !candidate.ends_with(|c: char| c.is_ascii_alphabetic())
+                        && candidate.parse::<f64>().is_ok()
+                });
+            Val::Number(prefix.map_or(f64::NAN, |end| text[..end].parse().unwrap()))
+        }
+        "parseInt" => {
+            let radix = match arg(1) {
+                Val::Undefined => 10,
+                radix => radix.to_number() as u32,
+            };
+            if !(2..=36).contains(&radix) {
+                return Err(Unsupported(format!("`parseInt` in base {}", radix)));
+            }
+            let text = s(0);
+            let text = text.trim_start();
+            let (sign, digits) = match text.strip_prefix('-') {
+                Some(digits) => (-1.0, digits),
+                None => (1.0, text.strip_prefix('+').unwrap_or(text)),
+            };
+            let digits = digits
+                .chars()
+                .take_while(|c| c.is_digit(radix))
+                .collect::<String>();
+            Val::Number(
+                u64::from_str_radix(&digits, radix).map_or(f64::NAN, |value| sign * value as f64),
+            )
+        }
+        "replace" => Val::String(s(0).replacen(&s(1), &s(2), 1)),
+        "split" => {
+            let text = s(0);
+            let separator = s(1);
+            let parts: Vec<String> = if separator.is_empty() {
+                text.chars().map(String::from).collect()
+            } else {
+                text.split(separator.as_str()).map(String::from).collect()
+            };
+            let limit = match arg(2) {
+                Val::Undefined => parts.len(),
+                limit => limit.to_number() as usize,
+            };
+            Val::Array(parts.into_iter().take(limit).map(Val::String).collect())
+        }
+        _ => return Err(Unsupported(format!("function `{}`", name))),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::parse;
+    use serde_json::json;
+
+    fn eval(expr: &str, datum: &Value) -> Result<Value, Unsupported> {
+        evaluate(&parse(expr).unwrap(), datum).map(Val::into_json)
+    }
+
+    #[test]
+    fn test_evaluate() {
+        let datum = json!({"a": 3, "b": "x", "c": null, "d": [1, 2, 3], "e e": 2});
+        let ok = |expr: &str| eval(expr, &datum).unwrap();
+        assert_eq!(ok("datum.a * 2 + 1"), json!(7));
+        assert_eq!(ok("datum.a + datum.b"), json!("3x"));
+        assert_eq!(ok("datum['e e'] / 4"), json!(0.5));
+        assert_eq!(ok("datum.c == null && datum.missing == null"), json!(true));
+        assert_eq!(ok("datum.missing === null"), json!(false));
+        assert_eq!(ok("datum.a > 2 ? 'big' : 'small'"), json!("big"));
+        assert_eq!(ok("datum.d[1] + length(datum.d)"), json!(5));
+        assert_eq!(ok("round(-2.5) + abs(-1) + max(1, datum.a)"), json!(2));
+        assert_eq!(ok("upper(substring(datum.b + 'yz', 1))"), json!("YZ"));
+        assert_eq!(ok("isValid(datum.c) || 'default'"), json!("default"));
+        assert_eq!(
+            ok("year('2012-03-04T10:00') + month('Mar 4 2012')"),
+            json!(2014)
+        );
+        assert_eq!(ok("utchours('2012-03-04T10:00Z')"), json!(10));
+        assert_eq!(ok("indexof(split('a-b-c', '-'), 'c')"), json!(2));
+        assert!(eval("random()", &datum).is_err());
+        assert!(eval("year('2012-03-04')", &datum).is_err());
+        assert!(eval("utcyear('2012-03-04T10:00')", &datum).is_err());
+        assert!(eval("datum.c.d", &datum).is_err());
+        assert!(eval("myParam * 2", &datum).is_err());
+    }
+}
@@ -0,0 +1,789 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Evaluation of transforms on inline data, to ship their result instead of the raw rows.
+
+mod aggregate;
+mod bin;
+mod datetime;
+mod downsample;
+mod encoding;
+mod expr;
+mod predicate;
+mod value;
+mod window;
+
+use crate::error::Error;
+use crate::schema::*;
+use crate::transform::TransformKind;
+use crate::RemovableValue;
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+pub(crate) use aggregate::quantile;
+pub use downsample::Downsample;
+pub(crate) use encoding::derived_title;
+
+/// Vega-Lite's default size of a sample
+const DEFAULT_SAMPLE: f64 = 1000.0;
+
+/// The reason why a transform can't be evaluated in Rust, and must be left to the browser
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Unsupported(pub(crate) String);
+
+/// Split a field into its path of nested properties: `a.b`, `a[0]` and `a['b']` access nested
+/// properties, `a\.b` is a property containing a dot
+pub(crate) fn field_path(field: &str) -> Vec<String> {
+    let mut path = vec![];
+    let mut current = String::new();
+    let mut closed_bracket = false;
+    let mut chars = field.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => current.extend(chars.next()),
+            '.' if closed_bracket => {}
+            '.' => path.push(std::mem::take(&mut current)),
+            '[' => {
+                if !current.is_empty() {
+                    path.push(std::mem::take(&mut current));
+                }
+                let property = chars.by_ref().take_while(|c| *c != ']').collect::<String>();
+                path.push(property.trim_matches(|c| c == '\'' || c == '"').to_string());
+                closed_bracket = true;
+                continue;
+            }
+            c => current.push(c),
+        }
+        closed_bracket = false;
+    }
+    if !current.is_empty() || path.is_empty() {
+        path.push(current);
+    }
+    path
+}
+
+/// The value of a nested property
+pub(crate) fn get_path<'a>(row: &'a Value, path: &[String]) -> Option<&'a Value> {
+    path.iter().try_fold(row, |value, property| match value {
+        Value::Object(entries) => entries.get(property),
+        Value::Array(items) => items.get(property.parse::<usize>().ok()?),
+        _ => None,
+    })
+}
+
+/// Set a property of a row
+pub(crate) fn set(row: &mut Value, property: &str, value: Value) {
+    if let Value::Object(entries) = row {
+        entries.insert(property.to_string(), value);
+    }
+}
+
+/// The value of a groupby field, numbers being equal whatever their json representation
+#[derive(Debug, PartialEq, Eq, Hash)]
+pub(crate) enum GroupKey<'a> {
+    Missing,
+    Null,
+    Bool(bool),
+    /// The bits of the number as a float, `-0` being `0` and all NaNs being the same
+    Number(u64),
+    String(&'a str),
+    Array(Vec<GroupKey<'a>>),
+    Object(Vec<(&'a str, GroupKey<'a>)>),
+}
+
+impl<'a> From<Option<&'a Value>> for GroupKey<'a> {
+    fn from(value: Option<&'a Value>) -> Self {
+        match value {
+            None => GroupKey::Missing,
+            Some(Value::Null) => GroupKey::Null,
+            Some(Value::Bool(b)) => GroupKey::Bool(*b),
+            Some(Value::Number(n)) => {
+                let n = n.as_f64().unwrap_or(f64::NAN);
+                let n = if n == 0.0 {
+                    0.0
+                } else if n.is_nan() {
+                    f64::NAN
+                } else {
+                    n
+                };
+                GroupKey::Number(n.to_bits())
+            }
+            Some(Value::String(s)) => GroupKey::String(s),
+            Some(Value::Array(values)) => {
+                GroupKey::Array(values.iter().map(|value| Some(value).into()).collect())
+            }
+            Some(Value::Object(entries)) => GroupKey::Object(
+                entries
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), Some(value).into()))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+/// Partition rows by the values of fields, in order of first appearance
+pub(crate) fn groups(rows: &[Value], groupby: &[String]) -> Vec<Vec<usize>> {
+    let paths = groupby
+        .iter()
+        .map(|field| field_path(field))
+        .collect::<Vec<_>>();
+    partition(rows.len(), |i| {
+        paths
+            .iter()
+            .map(|path| GroupKey::from(get_path(&rows[i], path)))
+            .collect()
+    })
+}
+
+/// Partition the indices of `len` items by their keys, in order of first appearance
+pub(crate) fn partition<'a>(
+    len: usize,
+    key: impl Fn(usize) -> Vec<GroupKey<'a>>,
+) -> Vec<Vec<usize>> {
+    let mut indices = HashMap::new();
+    let mut groups: Vec<Vec<usize>> = vec![];
+    for i in 0..len {
+        let key = key(i);
+        let group = *indices.entry(key).or_insert_with(|| {
+            groups.push(vec![]);
+            groups.len() - 1
+        });
+        groups[group].push(i);
+    }
+    groups
+}
+
+/// A deterministic pseudo-random generator (xorshift64*), so that the same spec always gives the
+/// same chart
+#[derive(Debug, Clone)]
+pub(crate) struct Rng(u64);
+
+impl Default for Rng {
+    fn default() -> Self {
+        Rng(0x9E37_79B9_7F4A_7C15)
+    }
+}
+
+impl Rng {
+    /// A random number below `n`
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        (self.0.wrapping_mul(0x2545_F491_4F6C_DD1D) % n as u64) as usize
+    }
+}
+
+/// Indices of a uniform random sample of `size` elements of `len`, in increasing order
+pub(crate) fn sample_indices(len: usize, size: usize, rng: &mut Rng) -> Vec<usize> {
+    let mut reservoir = (0..len.min(size)).collect::<Vec<_>>();
+    for i in size..len {
+        let j = rng.below(i + 1);
+        if j < size {
+            reservoir[j] = i;
+        }
+    }
+    reservoir.sort_unstable();
+    reservoir
+}
+
+/// The output names of a transform, defaulting to `default`
+fn output_names(transform_as: &Option<Style>, default: [&str; 2]) -> [String; 2] {
+    match transform_as {
+        Some(Style::String(name)) => [name.clone(), format!("{}_end", name)],
+        Some(Style::StringArray(names)) => [
+            names.first().map_or(default[0], String::as_str).to_string(),
+            names.get(1).map_or(default[1], String::as_str).to_string(),
+        ],
+        None => [default[0].to_string(), default[1].to_string()],
+    }
+}
+
+fn aggregate_rows(
+    rows: &[&Value],
+    defs: impl Iterator<Item = (Option<AggregateOp>, Option<String>, Option<String>)>,
+) -> Result<Vec<(String, Value)>, Unsupported> {
+    defs.map(|(op, field, name)| {
+        let op = op.ok_or_else(|| Unsupported("aggregate without op".to_string()))?;
+        let value = aggregate::aggregate(&op, rows, field.as_deref().map(field_path).as_deref())?;
+        let name = name.unwrap_or_else(|| aggregate::output_name(&op, field.as_deref()));
+        Ok((name, value))
+    })
+    .collect()
+}
+
+/// Execute a transform on the rows, which are left untouched when it is unsupported
+fn execute(
+    kind: TransformKind,
+    transform: &Transform,
+    rows: &mut Vec<Value>,
+) -> Result<(), Unsupported> {
+    let groupby = transform.groupby.as_deref().unwrap_or_default();
+    match kind {
+        TransformKind::Aggregate | TransformKind::JoinAggregate => {
+            let defs = || -> Box<dyn Iterator<Item = _>> {
+                match (&transform.aggregate, &transform.joinaggregate) {
+                    (Some(defs), _) => Box::new(defs.iter().map(|def| {
+                        (
+                            def.op.clone(),
+                            def.field.clone(),
+                            def.aggregated_field_def_as.clone(),
+                        )
+                    })),
+                    (_, Some(defs)) => Box::new(defs.iter().map(|def| {
+                        (
+                            def.op.clone(),
+                            def.field.clone(),
+                            def.join_aggregate_field_def_as.clone(),
+                        )
+                    })),
+                    _ => Box::new(std::iter::empty()),
+                }
+            };
+            let mut output = vec![];
+            let mut joined = vec![];
+            for group in groups(rows, groupby) {
+                let group_rows = group.iter().map(|i| &rows[*i]).collect::<Vec<_>>();
+                let values = aggregate_rows(&group_rows, defs())?;
+                if kind == TransformKind::JoinAggregate {
+                    joined.push((group, values));
+                    continue;
+                }
+                let mut row = Map::new();
+                for field in groupby {
+                    if let Some(value) = get_path(group_rows[0], &field_path(field)) {
+                        row.insert(field.clone(), value.clone());
+                    }
+                }
+                row.extend(values);
+                output.push(Value::Object(row));
+            }
+            if kind == TransformKind::Aggregate {
+                *rows = output;
+                return Ok(());
+            }
+            for (group, values) in joined {
+                for i in group {
+                    for (name, value) in &values {
+                        set(&mut rows[i], name, value.clone());
+                    }
+                }
+            }
+        }
+        TransformKind::Bin => {
+            let params = bin::params(transform.bin.as_ref().unwrap())?;
+            let path = field_path(transform.field.as_deref().unwrap_or_default());
+            let (bins, starts) = bin::bin_rows(&params, rows, &path)?;
+            let [start, end] = output_names(&transform.transform_as, ["bin", "bin_end"]);
+            for (row, bin_start) in rows.iter_mut().zip(starts) {
+                set(row, &start, bin_start.map_or(Value::Null, value::number));
+                set(
+                    row,
+                    &end,
+                    bin_start.map_or(Value::Null, |bin| value::number(bin + bins.step)),
+                );
+            }
+        }
+        TransformKind::Calculate => {
+            let source = transform.calculate.as_deref().unwrap_or_default();
+            let expr = crate::expr::parse(source)
+                .map_err(|err| Unsupported(format!("expression `{}`: {}", source, err)))?;
+            let [name, _] = output_names(&transform.transform_as, ["", ""]);
+            let values = rows
+                .iter()
+                .map(|row| Ok(expr::evaluate(&expr, row)?.into_json()))
+                .collect::<Result<Vec<_>, Unsupported>>()?;
+            for (row, value) in rows.iter_mut().zip(values) {
+                set(row, &name, value);
+            }
+        }
+        TransformKind::Filter => {
+            let predicate = predicate::compile(transform.filter.as_ref().unwrap())?;
+            let kept = rows
+                .iter()
+                .map(|row| predicate.test(row))
+                .collect::<Result<Vec<_>, _>>()?;
+            let mut kept = kept.into_iter();
+            rows.retain(|_| kept.next().unwrap_or(false));
+        }
+        TransformKind::Fold => {
+            let fields = transform.fold.as_deref().unwrap_or_default();
+            let [key, value] = output_names(&transform.transform_as, ["key", "value"]);
+            let mut folded = Vec::with_capacity(rows.len() * fields.len());
+            for row in rows.iter() {
+                for field in fields {
+                    let mut row = row.clone();
+                    let field_value = get_path(&row, &field_path(field))
+                        .cloned()
+                        .unwrap_or(Value::Null);
+                    set(&mut row, &key, Value::String(field.clone()));
+                    set(&mut row, &value, field_value);
+                    folded.push(row);
+                }
+            }
+            *rows = folded;
+        }
+        TransformKind::Sample => {
+            let size = transform.sample.unwrap_or(DEFAULT_SAMPLE).max(0.0) as usize;
+            let indices = sample_indices(rows.len(), size, &mut Rng::default());
+            let mut taken = std::mem::take(rows)
+                .into_iter()
+                .map(Some)
+                .collect::<Vec<_>>();
+            *rows = indices
+                .into_iter()
+                .filter_map(|i| taken[i].take())
+                .collect();
+        }
+        TransformKind::TimeUnit => {
+            let time_unit = transform.time_unit.as_ref().unwrap();
+            let path = field_path(transform.field.as_deref().unwrap_or_default());
+            let [name, _] = output_names(&transform.transform_as, ["", ""]);
+            let moments = rows
+                .iter()
+                .map(|row| {
+                    datetime::truncate_value(time_unit, &value::Val::from(get_path(row, &path)))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            for (row, moment) in rows.iter_mut().zip(moments) {
+                set(row, &name, moment.to_json());
+            }
+        }
+        TransformKind::Window => window::window(transform, rows)?,
+        TransformKind::Flatten
+        | TransformKind::Impute
+        | TransformKind::Lookup
+        | TransformKind::Stack => return Err(Unsupported("transform".to_string())),
+    }
+    Ok(())
+}
+
+/// The rows of inline data that Vega-Lite reads as is, primitive values being wrapped in a
+/// `data` property
+pub(crate) fn inline_rows(data: &UrlData) -> Option<Vec<Value>> {
+    let plain_format = data.format.as_ref().map_or(true, |format| {
+        let no_parse = match &format.parse {
+            RemovableValue::Specified(parse) => parse.is_empty(),
+            _ => true,
+        };
+        no_parse
+            && format.property.is_none()
+            && matches!(format.data_format_type, None | Some(DataFormatType::Json))
+    });
+    match &data.values {
+        Some(UrlDataInlineDataset::UnionArray(values)) if plain_format && data.url.is_none() => {
+            Some(
+                values
+                    .iter()
+                    .map(|value| match value {
+                        Value::Object(_) => value.clone(),
+                        value => serde_json::json!({ "data": value }),
+                    })
+                    .collect(),
+            )
+        }
+        _ => None,
+    }
+}
+
+/// The rows of inline data after a sequence of transforms, failing when a transform can't be
+/// evaluated
+pub(crate) fn transformed_rows(
+    data: &UrlData,
+    transforms: &[Transform],
+) -> Result<Vec<Value>, Error> {
+    let mut rows = inline_rows(data).ok_or_else(|| {
+        Error::Validation("the data is not a plain array of inline rows".to_string())
+    })?;
+    for (i, transform) in transforms.iter().enumerate() {
+        let kind = transform
+            .validate()
+            .map_err(|err| Error::Validation(format!("transform {}: {}", i, err)))?;
+        execute(kind, transform, &mut rows).map_err(|Unsupported(reason)| {
+            Error::Validation(format!("transform {}: {} can't be evaluated", i, reason))
+        })?;
+    }
+    Ok(rows)
+}
+
+/// The mark type, the encoding as json and the rows of a single view chart
+type SingleView = (String, Map<String, Value>, Vec<Value>);
+
+fn mark_type(mark: &AnyMark) -> String {
+    let mark = serde_json::to_value(mark).unwrap_or(Value::Null);
+    mark.get("type")
+        .unwrap_or(&mark)
+        .as_str()
+        .unwrap_or_default()
+        .to_string()
+}
+
+impl Vegalite {
+    /// Evaluate the transforms of the chart on its inline data, replacing the data with the
+    /// result and removing the evaluated transforms.
+    ///
+    /// Transforms are evaluated in order, up to the first one that can't be evaluated the way
+    /// Vega would: lookups, imputes, stacks, flattens, selection predicates, random or time zone
+    /// dependent expressions are left to the browser, along with all the following transforms.
+    /// Returns the number of evaluated transforms, nothing being modified on error.
+    pub fn execute_transforms(&mut self) -> Result<usize, Error> {
+        let (executed, rows) = match self.executed_rows()? {
+            Some((executed, rows)) if executed > 0 => (executed, rows),
+            _ => return Ok(0),
+        };
+        if let RemovableValue::Specified(data) = &mut self.data {
+            data.values = Some(UrlDataInlineDataset::UnionArray(rows));
+        }
+        let remaining = self
+            .transform
+            .take()
+            .unwrap_or_default()
+            .split_off(executed);
+        self.transform = Some(remaining).filter(|remaining| !remaining.is_empty());
+        Ok(executed)
+    }
+
+    /// The number of transforms that can be evaluated and the inline rows they result in,
+    /// without modifying the chart
+    fn executed_rows(&self) -> Result<Option<(usize, Vec<Value>)>, Error> {
+        let mut rows = match &self.data {
+            RemovableValue::Specified(data) => match inline_rows(data) {
+                Some(rows) => rows,
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+        let mut executed = 0;
+        for (i, transform) in self.transform.iter().flatten().enumerate() {
+            let kind = transform
+                .validate()
+                .map_err(|err| Error::Validation(format!("transform {}: {}", i, err)))?;
+            match execute(kind, transform, &mut rows) {
+                Ok(()) => executed += 1,
+                Err(Unsupported(_)) => break,
+            }
+        }
+        Ok(Some((executed, rows)))
+    }
+
+    /// The mark type, encoding and rows of a single view chart with an accepted mark, after
+    /// evaluating all its transforms, unless one can't be evaluated or its data is not inline.
+    ///
+    /// The chart is left untouched: callers replace its data with their result and drop its
+    /// transforms with [`set_rows`](#method.set_rows).
+    fn single_view(&self, accept: impl Fn(&str) -> bool) -> Result<Option<SingleView>, Error> {
+        let single_view = self.layer.is_none()
+            && self.hconcat.is_none()
+            && self.vconcat.is_none()
+            && self.concat.is_none()
+            && self.facet.is_none()
+            && self.repeat.is_none();
+        let mark = match &self.mark {
+            Some(mark) if single_view && self.encoding.is_some() => mark_type(mark),
+            _ => return Ok(None),
+        };
+        if !accept(&mark) {
+            return Ok(None);
+        }
+        let rows = match self.executed_rows()? {
+            Some((executed, rows)) if executed == self.transform.as_ref().map_or(0, Vec::len) => {
+                rows
+            }
+            _ => return Ok(None),
+        };
+        match serde_json::to_value(&self.encoding)? {
+            Value::Object(encoding) => Ok(Some((mark, encoding, rows))),
+            _ => Ok(None),
+        }
+    }
+
+    /// Replace the inline data of the chart with rows computed from its transformed data, and
+    /// drop the transforms
+    fn set_rows(&mut self, rows: Vec<Value>) {
+        if let RemovableValue::Specified(data) = &mut self.data {
+            data.values = Some(UrlDataInlineDataset::UnionArray(rows));
+        }
+        self.transform = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::datum;
+    use crate::predicate::{field, selection};
+    use serde_json::json;
+
+    fn chart(values: Value, transforms: Vec<Transform>) -> Vegalite {
+        VegaliteBuilder::default()
+            .data(
+                UrlDataBuilder::default()
+                    .values(UrlDataInlineDataset::UnionArray(
+                        values.as_array().unwrap().clone(),
+                    ))
+                    .build()
+                    .unwrap(),
+            )
+            .transform(transforms)
+            .build()
+            .unwrap()
+    }
+
+    fn values(chart: &Vegalite) -> Value {
+        match &chart.data {
+            RemovableValue::Specified(UrlData {
+                values: Some(UrlDataInlineDataset::UnionArray(values)),
+                ..
+            }) => Value::Array(values.clone()),
+            _ => panic!("no inline data"),
+        }
+    }
+
+    #[test]
+    fn test_field_path() {
+        assert_eq!(field_path("a"), vec!["a"]);
+        assert_eq!(field_path("a.b"), vec!["a", "b"]);
+        assert_eq!(field_path("a\\.b"), vec!["a.b"]);
+        assert_eq!(field_path("a[0].b"), vec!["a", "0", "b"]);
+        assert_eq!(field_path("a['b c']"), vec!["a", "b c"]);
+    }
+
+    #[test]
+    fn test_groups() {
+        let rows = json!([
+            {"k": 1, "v": 1},
+            {"k": 1.0, "v": 2},
+            {"k": -0.0, "v": 3},
+            {"k": 0, "v": 4},
+            {"k": "1", "v": 5},
+            {"v": 6},
+            {"k": null, "v": 7}
+        ]);
+        let rows = rows.as_array().unwrap();
+        assert_eq!(
+            groups(rows, &["k".to_string()]),
+            vec![vec![0, 1], vec![2, 3], vec![4], vec![5], vec![6]]
+        );
+        assert_eq!(groups(rows, &[]), vec![(0..7).collect::<Vec<_>>()]);
+    }
+
+    #[test]
+    fn test_execute_transforms() {
+        let mut chart = chart(
+            json!([
+                {"k": "a", "v": 1, "t": "2012-01-15T10:00"},
+                {"k": "a", "v": 3, "t": "2012-02-15T10:00"},
+                {"k": "b", "v": 5, "t": "2012-02-20T10:00"},
+                {"k": "c", "v": null, "t": "2012-03-01T10:00"}
+            ]),
+            vec![
+                Transform::filter(field("v").valid()),
+                Transform::calculate(datum("v") * 10, "v10"),
+                Transform::time_unit(TimeUnit::Yearmonth, "t", "month"),
+                Transform::aggregate(vec![
+                    AggregatedFieldDefBuilder::default()
+                        .op(AggregateOp::Sum)
+                        .field("v10")
+                        .aggregated_field_def_as("total")
+                        .build()
+                        .unwrap(),
+                    AggregatedFieldDefBuilder::default()
+                        .op(AggregateOp::Count)
+                        .build()
+                        .unwrap(),
+                ])
+                .groupby(vec!["month"])
+                .into(),
+                Transform::window(vec![WindowFieldDefBuilder::default()
+                    .op(Op::Sum)
+                    .field("total")
+                    .window_field_def_as("running")
+                    .build()
+                    .unwrap()])
+                .sort(vec![SortFieldBuilder::default()
+                    .field("month")
+                    .build()
+                    .unwrap()])
+                .into(),
+            ],
+        );
+        assert_eq!(chart.execute_transforms().unwrap(), 5);
+        assert!(chart.transform.is_none());
+        assert_eq!(
+            values(&chart),
+            json!([
+                {"month": "2012-01-01T00:00:00", "total": 10, "count": 1, "running": 10},
+                {"month": "2012-02-01T00:00:00", "total": 80, "count": 2, "running": 90}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_execute_bin_fold_and_join_aggregate() {
+        let mut chart = chart(
+            json!([{"a": 1.3, "b": 2}, {"a": 47.2, "b": 4}]),
+            vec![
+                Transform::bin("a", "a_bin").into(),
+                Transform::fold(vec!["a", "b"]).into(),
+                Transform::join_aggregate(vec![JoinAggregateFieldDefBuilder::default()
+                    .op(AggregateOp::Max)
+                    .field("value")
+                    .build()
+                    .unwrap()])
+                .groupby(vec!["key"])
+                .into(),
+            ],
+        );
+        assert_eq!(chart.execute_transforms().unwrap(), 3);
+        let values = values(&chart);
+        assert_eq!(values.as_array().unwrap().len(), 4);
+        assert_eq!(
+            values[1],
+            json!({"a": 1.3, "b": 2, "a_bin": 0, "a_bin_end": 5, "key": "b", "value": 2, "max_value": 4})
+        );
+    }
+
+    #[test]
+    fn test_execute_stops_at_unsupported() {
+        let mut chart = chart(
+            json!([{"a": 1}, {"a": 2}, {"a": 3}]),
+            vec![
+                Transform::filter(field("a").gt(1.0)),
+                Transform::filter(selection("brush")),
+                Transform::sample(1.0),
+            ],
+        );
+        assert_eq!(chart.execute_transforms().unwrap(), 1);
+        assert_eq!(values(&chart), json!([{"a": 2}, {"a": 3}]));
+        assert_eq!(chart.transform.as_ref().unwrap().len(), 2);
+
+        let mut chart = self::chart(
+            json!([{"t": "2012-01-15"}]),
+            vec![Transform::time_unit(TimeUnit::Month, "t", "m")],
+        );
+        assert_eq!(chart.execute_transforms().unwrap(), 0);
+        assert_eq!(values(&chart), json!([{"t": "2012-01-15"}]));
+    }
+
+    #[test]
+    fn test_execute_window_frames() {
+        let window = |start: Option<f64>, end: Option<f64>| -> Transform {
+            Transform::window(vec![WindowFieldDefBuilder::default()
+                .op(Op::Sum)
+                .field("a")
+                .window_field_def_as("s")
+                .build()
+                .unwrap()])
+            .frame(start, end)
+            .into()
+        };
+        let rows = json!([{"a": 1}, {"a": 2}, {"a": 3}, {"a": 4}, {"a": 5}]);
+        let sums = |start, end| {
+            let mut chart = chart(rows.clone(), vec![window(start, end)]);
+            assert_eq!(chart.execute_transforms().unwrap(), 1);
+            values(&chart)
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|row| row["s"].clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            sums(Some(1.0), Some(2.0)),
+            vec![json!(5), json!(7), json!(9), json!(5), json!(0)]
+        );
+        assert_eq!(
+            sums(Some(-2.0), Some(-1.0)),
+            vec![json!(0), json!(1), json!(3), json!(5), json!(7)]
+        );
+        assert_eq!(
+            sums(Some(-1.0), Some(1.0)),
+            vec![json!(3), json!(6), json!(9), json!(12), json!(9)]
+        );
+        assert_eq!(
+            sums(None, Some(0.0)),
+            vec![json!(1), json!(3), json!(6), json!(10), json!(15)]
+        );
+    }
+
+    #[test]
+    fn test_execute_window_empty_frames() {
+        let window = |op: Op, start: f64, end: f64| -> Transform {
+            Transform::window(vec![WindowFieldDefBuilder::default()
+                .op(op)
+                .field("a")
+                .window_field_def_as("v")
+                .build()
+                .unwrap()])
+            .frame(Some(start), Some(end))
+            .into()
+        };
+        let outputs = |transform| {
+            let mut chart = chart(json!([{"a": 1}, {"a": 2}, {"a": 3}]), vec![transform]);
+            assert_eq!(chart.execute_transforms().unwrap(), 1);
+            values(&chart)
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|row| row["v"].clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            outputs(window(Op::FirstValue, 1.0, 2.0)),
+            vec![json!(2), json!(3), Value::Null]
+        );
+        assert_eq!(
+            outputs(window(Op::LastValue, 1.0, 2.0)),
+            vec![json!(3), json!(3), Value::Null]
+        );
+        assert_eq!(
+            outputs(window(Op::FirstValue, -2.0, -1.0)),
+            vec![Value::Null, json!(1), json!(1)]
+        );
+        assert_eq!(
+            outputs(window(Op::LastValue, -2.0, -1.0)),
+            vec![Value::Null, json!(1), json!(2)]
+        );
+
+        let mut chart = chart(
+            json!([{"k": "a"}, {"k": "b"}, {"k": "b"}]),
+            vec![Transform::window(vec![WindowFieldDefBuilder::default()
+                .op(Op::PercentRank)
+                .window_field_def_as("p")
+                .build()
+                .unwrap()])
+            .groupby(vec!["k"])
+            .into()],
+        );
+        assert_eq!(chart.execute_transforms().unwrap(), 1);
+        let ranks = values(&chart)
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|row| row["p"].clone())
+            .collect::<Vec<_>>();
+        assert_eq!(ranks, vec![json!(0), json!(0), json!(1)]);
+    }
+
+    #[test]
+    fn test_execute_sample() {
+        let rows = (0..100).map(|i| json!({ "i": i })).collect::<Vec<_>>();
+        let mut chart = chart(Value::Array(rows), vec![Transform::sample(10.0)]);
+        assert_eq!(chart.execute_transforms().unwrap(), 1);
+        let sample = values(&chart);
+        let sample = sample.as_array().unwrap();
+        assert_eq!(sample.len(), 10);
+        assert!(sample
+            .windows(2)
+            .all(|w| w[0]["i"].as_i64() < w[1]["i"].as_i64()));
+    }
+}
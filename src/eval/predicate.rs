@@ -0,0 +1,241 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Evaluation of filter predicates, following the expressions generated by Vega-Lite.
+
+use super::datetime::{self, Units};
+use super::expr::evaluate;
+use super::value::{js_compare, strict_eq, Val};
+use super::{field_path, get_path, Unsupported};
+use crate::expr::{parse, Expr};
+use crate::schema::*;
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// A predicate ready to be tested on rows
+#[derive(Debug)]
+pub(crate) enum Compiled {
+    Expr(Expr),
+    Field {
+        path: Vec<String>,
+        time_unit: Option<TimeUnit>,
+        test: Test,
+    },
+    Not(Box<Compiled>),
+    And(Vec<Compiled>),
+    Or(Vec<Compiled>),
+}
+
+/// The test of a field predicate, with values already converted to timestamps for a field with
+/// a time unit
+#[derive(Debug)]
+pub(crate) enum Test {
+    Equal(Val),
+    Compare(Ordering, bool, Val),
+    Range(Option<Val>, Option<Val>),
+    OneOf(Vec<Val>),
+    Valid(bool),
+}
+
+/// A literal of a field predicate, `time_unit` being the time unit of the field
+fn literal(value: Value, time_unit: Option<&TimeUnit>) -> Result<Val, Unsupported> {
+    let date_time = serde_json::from_value::<DateTime>(value.clone())
+        .ok()
+        .filter(|_| value.is_object());
+    let time_unit = match time_unit {
+        Some(time_unit) => time_unit,
+        None if date_time.is_some() => {
+            return Err(Unsupported(
+                "comparison to a date time of a field without time unit".to_string(),
+            ))
+        }
+        None => return Ok(Val::from(&value)),
+    };
+    let moment = match date_time {
+        Some(date_time) => datetime::from_date_time(&date_time),
+        None => datetime::from_unit_value(time_unit, &value),
+    };
+    match moment {
+        Some(moment) if moment.utc() == Units::of(time_unit).utc => Ok(Val::Number(moment.ms())),
+        _ => Err(Unsupported(format!(
+            "comparison of a field with time unit to `{}`, which depends on the time zone",
+            value
+        ))),
+    }
+}
+
+fn json(value: &impl serde::Serialize) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+fn compile_field(predicate: &Predicate, field: &str) -> Result<Compiled, Unsupported> {
+    let time_unit = predicate.time_unit.as_ref();
+    let literal = |value: Value| literal(value, time_unit);
+    let compare = |ordering, or_equal, value: &Lt| {
+        Ok(Test::Compare(ordering, or_equal, literal(json(value))?))
+    };
+    let test = if let Some(equal) = &predicate.equal {
+        Test::Equal(literal(json(equal))?)
+    } else if let Some(lt) = &predicate.lt {
+        compare(Ordering::Less, false, lt)?
+    } else if let Some(gt) = &predicate.gt {
+        compare(Ordering::Greater, false, gt)?
+    } else if let Some(lte) = &predicate.lte {
+        compare(Ordering::Less, true, lte)?
+    } else if let Some(gte) = &predicate.gte {
+        compare(Ordering::Greater, true, gte)?
+    } else if let Some(range) = &predicate.range {
+        let bound = |i: usize| -> Result<Option<Val>, Unsupported> {
+            match range.get(i) {
+                Some(Some(bound)) => Ok(Some(literal(json(bound))?)),
+                _ => Ok(None),
+            }
+        };
+        Test::Range(bound(0)?, bound(1)?)
+    } else if let Some(one_of) = &predicate.one_of {
+        Test::OneOf(
+            one_of
+                .iter()
+                .map(|value| literal(json(value)))
+                .collect::<Result<_, _>>()?,
+        )
+    } else if let Some(valid) = predicate.valid {
+        Test::Valid(valid)
+    } else {
+        return Err(Unsupported(format!(
+            "predicate on `{}` without test",
+            field
+        )));
+    };
+    Ok(Compiled::Field {
+        path: field_path(field),
+        time_unit: predicate.time_unit.clone(),
+        test,
+    })
+}
+
+fn compile_expr(expr: &str) -> Result<Compiled, Unsupported> {
+    parse(expr)
+        .map(Compiled::Expr)
+        .map_err(|err| Unsupported(format!("expression `{}`: {}", expr, err)))
+}
+
+fn compile_elements(
+    elements: &[LogicalOperandPredicateElement],
+) -> Result<Vec<Compiled>, Unsupported> {
+    elements
+        .iter()
+        .map(|element| match element {
+            LogicalOperandPredicateElement::Predicate(predicate) => compile_predicate(predicate),
+            LogicalOperandPredicateElement::String(expr) => compile_expr(expr),
+        })
+        .collect()
+}
+
+fn compile_predicate(predicate: &Predicate) -> Result<Compiled, Unsupported> {
+    if let Some(not) = &predicate.not {
+        Ok(Compiled::Not(Box::new(compile(not)?)))
+    } else if let Some(and) = &predicate.and {
+        Ok(Compiled::And(compile_elements(and)?))
+    } else if let Some(or) = &predicate.or {
+        Ok(Compiled::Or(compile_elements(or)?))
+    } else if predicate.selection.is_some() {
+        Err(Unsupported("selection predicate".to_string()))
+    } else if let Some(field) = &predicate.field {
+        compile_field(predicate, field)
+    } else {
+        Err(Unsupported("empty predicate".to_string()))
+    }
+}
+
+/// Compile a filter predicate
+pub(crate) fn compile(predicate: &PurpleLogicalOperandPredicate) -> Result<Compiled, Unsupported> {
+    match predicate {
+        PurpleLogicalOperandPredicate::Predicate(predicate) => compile_predicate(predicate),
+        PurpleLogicalOperandPredicate::String(expr) => compile_expr(expr),
+    }
+}
+
+fn field_value(
+    row: &Value,
+    path: &[String],
+    time_unit: Option<&TimeUnit>,
+) -> Result<Val, Unsupported> {
+    let value = Val::from(get_path(row, path));
+    let time_unit = match time_unit {
+        Some(time_unit) => time_unit,
+        None => return Ok(value),
+    };
+    Ok(Val::Number(
+        datetime::truncate_value(time_unit, &value)?.ms(),
+    ))
+}
+
+impl Compiled {
+    /// Test a row
+    pub(crate) fn test(&self, row: &Value) -> Result<bool, Unsupported> {
+        match self {
+            Compiled::Expr(expr) => Ok(evaluate(expr, row)?.truthy()),
+            Compiled::Not(predicate) => Ok(!predicate.test(row)?),
+            Compiled::And(predicates) => {
+                for predicate in predicates {
+                    if !predicate.test(row)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Compiled::Or(predicates) => {
+                for predicate in predicates {
+                    if predicate.test(row)? {
+                        return Ok(true);
+                    }
+                }
+                Ok(false)
+            }
+            Compiled::Field {
+                path,
+                test: Test::Valid(valid),
+                ..
+            } => {
+                let value = Val::from(get_path(row, path));
+                let is_valid = value != Val::Null && !matches!(value, Val::Number(n) if n.is_nan());
+                Ok(is_valid == *valid)
+            }
+            Compiled::Field {
+                path,
+                time_unit,
+                test,
+            } => {
+                let value = field_value(row, path, time_unit.as_ref())?;
+                let compare = |other: &Val, ordering: Ordering, or_equal: bool| {
+                    js_compare(&value, other)
+                        .is_some_and(|result| result == ordering || (or_equal && result.is_eq()))
+                };
+                Ok(match test {
+                    Test::Equal(other) => strict_eq(&value, other),
+                    Test::Compare(ordering, or_equal, other) => {
+                        compare(other, *ordering, *or_equal)
+                    }
+                    Test::Range(min, max) => {
+                        min.as_ref()
+                            .map_or(true, |min| compare(min, Ordering::Greater, true))
+                            && max
+                                .as_ref()
+                                .map_or(true, |max| compare(max, Ordering::Less, true))
+                    }
+                    Test::OneOf(values) => values.iter().any(|other| strict_eq(&value, other)),
+                    Test::Valid(_) => unreachable!("tested above"),
+                })
+            }
+        }
+    }
+}
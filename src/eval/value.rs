@@ -0,0 +1,262 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Values of the expression language, following the semantics of javascript.
+
+use serde_json::{Map, Number, Value};
+use std::cmp::Ordering;
+
+/// A javascript value, as seen by an expression
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum Val {
+    Undefined,
+    Null,
+    Bool(bool),
+    Number(f64),
+    String(String),
+    Array(Vec<Val>),
+    Object(Vec<(String, Val)>),
+}
+
+impl From<&Value> for Val {
+    fn from(value: &Value) -> Self {
+        match value {
+            Value::Null => Val::Null,
+            Value::Bool(b) => Val::Bool(*b),
+            Value::Number(n) => Val::Number(n.as_f64().unwrap_or(f64::NAN)),
+            Value::String(s) => Val::String(s.clone()),
+            Value::Array(values) => Val::Array(values.iter().map(Val::from).collect()),
+            Value::Object(entries) => Val::Object(
+                entries
+                    .iter()
+                    .map(|(key, value)| (key.clone(), Val::from(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl From<Option<&Value>> for Val {
+    fn from(value: Option<&Value>) -> Self {
+        value.map_or(Val::Undefined, Val::from)
+    }
+}
+
+/// Convert a number to json, integral numbers being kept as integers and non finite numbers,
+/// that json can't represent, becoming `null`
+pub(crate) fn number(n: f64) -> Value {
+    if n.is_finite() && n.fract() == 0.0 && n.abs() < 9_007_199_254_740_992.0 {
+        Value::Number(Number::from(n as i64))
+    } else {
+        Number::from_f64(n).map_or(Value::Null, Value::Number)
+    }
+}
+
+impl Val {
+    /// Convert to json, `undefined` being converted to `null`
+    pub(crate) fn into_json(self) -> Value {
+        match self {
+            Val::Undefined | Val::Null => Value::Null,
+            Val::Bool(b) => Value::Bool(b),
+            Val::Number(n) => number(n),
+            Val::String(s) => Value::String(s),
+            Val::Array(values) => Value::Array(values.into_iter().map(Val::into_json).collect()),
+            Val::Object(entries) => Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, value.into_json()))
+                    .collect::<Map<_, _>>(),
+            ),
+        }
+    }
+
+    pub(crate) fn is_nullish(&self) -> bool {
+        matches!(self, Val::Undefined | Val::Null)
+    }
+
+    /// `value != null && value === value`
+    pub(crate) fn is_valid(&self) -> bool {
+        match self {
+            Val::Undefined | Val::Null => false,
+            Val::Number(n) => !n.is_nan(),
+            _ => true,
+        }
+    }
+
+    pub(crate) fn truthy(&self) -> bool {
+        match self {
+            Val::Undefined | Val::Null => false,
+            Val::Bool(b) => *b,
+            Val::Number(n) => *n != 0.0 && !n.is_nan(),
+            Val::String(s) => !s.is_empty(),
+            Val::Array(_) | Val::Object(_) => true,
+        }
+    }
+
+    /// The javascript `ToNumber` conversion
+    pub(crate) fn to_number(&self) -> f64 {
+        match self {
+            Val::Undefined => f64::NAN,
+            Val::Null => 0.0,
+            Val::Bool(b) => f64::from(u8::from(*b)),
+            Val::Number(n) => *n,
+            Val::String(s) => string_to_number(s),
+            Val::Array(_) | Val::Object(_) => string_to_number(&self.to_js_string()),
+        }
+    }
+
+    /// The javascript `ToString` conversion
+    pub(crate) fn to_js_string(&self) -> String {
+        match self {
+            Val::Undefined => "undefined".to_string(),
+            Val::Null => "null".to_string(),
+            Val::Bool(b) => b.to_string(),
+            Val::Number(n) => number_to_string(*n),
+            Val::String(s) => s.clone(),
+            Val::Array(values) => values
+                .iter()
+                .map(|value| {
+                    if value.is_nullish() {
+                        String::new()
+                    } else {
+                        value.to_js_string()
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(","),
+            Val::Object(_) => "[object Object]".to_string(),
+        }
+    }
+
+    fn is_primitive(&self) -> bool {
+        !matches!(self, Val::Array(_) | Val::Object(_))
+    }
+}
+
+fn string_to_number(s: &str) -> f64 {
+    let s = s.trim();
+    match s {
+        "" => 0.0,
+        "Infinity" | "+Infinity" => f64::INFINITY,
+        "-Infinity" => f64::NEG_INFINITY,
+        _ if s.starts_with("0x") || s.starts_with("0X") => {
+            u64::from_str_radix(&s[2..], 16).map_or(f64::NAN, |n| n as f64)
+        }
+        _ if s
+            .bytes()
+            .all(|b| b.is_ascii_digit() || b"+-.eE".contains(&b)) =>
+        {
+            s.parse().unwrap_or(f64::NAN)
+        }
+        _ => f64::NAN,
+    }
+}
+
+/// Format a number the way javascript does
+pub(crate) fn number_to_string(n: f64) -> String {
+    if n.is_nan() {
+        "NaN".to_string()
+    } else if n.is_infinite() {
+        if n > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else if n == 0.0 {
+        "0".to_string()
+    } else if n.abs() >= 1e21 {
+        format!("{:e}", n).replace('e', "e+")
+    } else if n.abs() < 1e-6 {
+        format!("{:e}", n)
+    } else {
+        format!("{}", n)
+    }
+}
+
+/// The `==` operator
+pub(crate) fn loose_eq(a: &Val, b: &Val) -> bool {
+    match (a, b) {
+        (a, b) if a.is_nullish() || b.is_nullish() => a.is_nullish() && b.is_nullish(),
+        (Val::Number(_), Val::String(_)) | (Val::String(_), Val::Number(_)) => {
+            a.to_number() == b.to_number()
+        }
+        (Val::Bool(_), _) => loose_eq(&Val::Number(a.to_number()), b),
+        (_, Val::Bool(_)) => loose_eq(a, &Val::Number(b.to_number())),
+        (a, b) if a.is_primitive() != b.is_primitive() => {
+            let (primitive, object) = if a.is_primitive() { (a, b) } else { (b, a) };
+            loose_eq(primitive, &Val::String(object.to_js_string()))
+        }
+        (a, b) => strict_eq(a, b),
+    }
+}
+
+/// The `===` operator; arrays and objects are never identical
+pub(crate) fn strict_eq(a: &Val, b: &Val) -> bool {
+    match (a, b) {
+        (Val::Undefined, Val::Undefined) | (Val::Null, Val::Null) => true,
+        (Val::Bool(a), Val::Bool(b)) => a == b,
+        (Val::Number(a), Val::Number(b)) => a == b,
+        (Val::String(a), Val::String(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// The `<` operator, `None` when the values are not comparable (`NaN` is involved)
+pub(crate) fn js_compare(a: &Val, b: &Val) -> Option<Ordering> {
+    let to_primitive = |value: &Val| {
+        if value.is_primitive() {
+            value.clone()
+        } else {
+            Val::String(value.to_js_string())
+        }
+    };
+    match (to_primitive(a), to_primitive(b)) {
+        (Val::String(a), Val::String(b)) => Some(a.cmp(&b)),
+        (a, b) => a.to_number().partial_cmp(&b.to_number()),
+    }
+}
+
+/// The ascending order used to sort data: nullish values first, then by `<`
+pub(crate) fn ascending(a: &Val, b: &Val) -> Ordering {
+    match (a.is_nullish(), b.is_nullish()) {
+        (true, true) => Ordering::Equal,
+        (true, false) => Ordering::Less,
+        (false, true) => Ordering::Greater,
+        (false, false) => js_compare(a, b).unwrap_or(Ordering::Equal),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_javascript_semantics() {
+        assert_eq!(number_to_string(3.0), "3");
+        assert_eq!(number_to_string(0.1), "0.1");
+        assert_eq!(number_to_string(1e21), "1e+21");
+        assert_eq!(number_to_string(1.5e-7), "1.5e-7");
+        assert!(loose_eq(&Val::Number(1.0), &Val::String("1".to_string())));
+        assert!(loose_eq(&Val::Null, &Val::Undefined));
+        assert!(!strict_eq(&Val::Null, &Val::Undefined));
+        assert!(loose_eq(&Val::Bool(true), &Val::Number(1.0)));
+        assert!(Val::String("abc".to_string()).to_number().is_nan());
+        assert_eq!(
+            js_compare(
+                &Val::String("10".to_string()),
+                &Val::String("9".to_string())
+            ),
+            Some(Ordering::Less)
+        );
+        assert_eq!(
+            js_compare(&Val::Number(10.0), &Val::String("9".to_string())),
+            Some(Ordering::Greater)
+        );
+        assert_eq!(number(2.0), Value::from(2));
+        assert_eq!(number(f64::NAN), Value::Null);
+    }
+}
@@ -0,0 +1,223 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! The window transform, as computed by Vega.
+
+use super::aggregate::{aggregate, op_name, output_name};
+use super::value::{ascending, number, Val};
+use super::{field_path, get_path, groups, set, Unsupported};
+use crate::schema::*;
+use serde_json::Value;
+use std::cmp::Ordering;
+
+/// Compare rows by sort fields, rows being peers when they are equal
+fn compare(sort: &[(Vec<String>, bool)], a: &Value, b: &Value) -> Ordering {
+    sort.iter()
+        .map(|(path, descending)| {
+            let ordering = ascending(&Val::from(get_path(a, path)), &Val::from(get_path(b, path)));
+            if *descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        })
+        .find(|ordering| ordering.is_ne())
+        .unwrap_or(Ordering::Equal)
+}
+
+/// An operation of a window, computed on the sorted rows of a partition
+struct Operation<'a> {
+    op: &'a Op,
+    path: Option<Vec<String>>,
+    param: Option<f64>,
+    aggregate: Option<AggregateOp>,
+}
+
+/// The state of the window at a row
+struct Frame<'a> {
+    data: &'a [&'a Value],
+    index: usize,
+    /// Start of the frame
+    i0: usize,
+    /// End of the frame, exclusive
+    i1: usize,
+    /// Whether two rows are distinct according to the sort
+    distinct: &'a dyn Fn(usize, usize) -> bool,
+}
+
+impl Operation<'_> {
+    fn field(&self, row: &Value) -> Value {
+        self.path
+            .as_ref()
+            .and_then(|path| get_path(row, path))
+            .cloned()
+            .unwrap_or(Value::Null)
+    }
+
+    fn positive_param(&self) -> Result<f64, Unsupported> {
+        self.param.filter(|param| *param > 0.0).ok_or_else(|| {
+            Unsupported(format!(
+                "`{}` without a positive parameter",
+                op_name(self.op)
+            ))
+        })
+    }
+
+    fn rank(&self, w: &Frame) -> usize {
+        (1..=w.index)
+            .rev()
+            .find(|i| (w.distinct)(i - 1, *i))
+            .map_or(1, |i| i + 1)
+    }
+
+    fn cume_dist(&self, w: &Frame) -> f64 {
+        let last = (w.index..w.data.len() - 1)
+            .find(|i| (w.distinct)(*i, i + 1))
+            .unwrap_or(w.data.len() - 1);
+        (last + 1) as f64 / w.data.len() as f64
+    }
+
+    fn compute(&self, w: &Frame) -> Result<Value, Unsupported> {
+        if let Some(op) = &self.aggregate {
+            return aggregate(op, &w.data[w.i0..w.i1], self.path.as_deref());
+        }
+        let offset = || self.param.filter(|offset| *offset != 0.0).unwrap_or(1.0) as usize;
+        Ok(match self.op {
+            Op::RowNumber => number((w.index + 1) as f64),
+            Op::Rank => number(self.rank(w) as f64),
+            Op::DenseRank => {
+                number((1 + (1..=w.index).filter(|i| (w.distinct)(i - 1, *i)).count()) as f64)
+            }
+            Op::PercentRank if w.data.len() > 1 => {
+                number((self.rank(w) - 1) as f64 / (w.data.len() - 1) as f64)
+            }
+            Op::PercentRank => number(0.0),
+            Op::CumeDist => number(self.cume_dist(w)),
+            Op::Ntile => number((self.positive_param()? * self.cume_dist(w)).ceil()),
+            Op::Lag => match w.index.checked_sub(offset()) {
+                Some(i) => self.field(w.data[i]),
+                None => Value::Null,
+            },
+            Op::Lead => match w.data.get(w.index + offset()) {
+                Some(row) => self.field(row),
+                None => Value::Null,
+            },
+            Op::FirstValue | Op::LastValue if w.i0 >= w.i1 => Value::Null,
+            Op::FirstValue => self.field(w.data[w.i0]),
+            Op::LastValue => self.field(w.data[w.i1 - 1]),
+            Op::NthValue => {
+                let i = w.i0 + self.positive_param()? as usize - 1;
+                if i < w.i1 {
+                    self.field(w.data[i])
+                } else {
+                    Value::Null
+                }
+            }
+            op => unreachable!("aggregate operation {}", op_name(op)),
+        })
+    }
+}
+
+/// Compute the window fields of each row, the rows keeping their order
+pub(crate) fn window(transform: &Transform, rows: &mut [Value]) -> Result<(), Unsupported> {
+    let defs = transform.window.as_deref().unwrap_or_default();
+    let operations = defs
+        .iter()
+        .map(|def| {
+            let op = def
+                .op
+                .as_ref()
+                .ok_or_else(|| Unsupported("window operation without op".to_string()))?;
+            Ok(Operation {
+                op,
+                path: def.field.as_deref().map(field_path),
+                param: def.param,
+                aggregate: serde_json::to_value(op)
+                    .ok()
+                    .and_then(|op| serde_json::from_value(op).ok()),
+            })
+        })
+        .collect::<Result<Vec<_>, Unsupported>>()?;
+    let names = defs
+        .iter()
+        .map(|def| {
+            def.window_field_def_as
+                .clone()
+                .unwrap_or_else(|| output_name(def.op.as_ref().unwrap(), def.field.as_deref()))
+        })
+        .collect::<Vec<_>>();
+    let sort = transform
+        .sort
+        .as_ref()
+        .map(|sort| {
+            sort.iter()
+                .filter_map(|sort| {
+                    let descending = matches!(
+                        sort.order,
+                        crate::RemovableValue::Specified(SortOrder::Descending)
+                    );
+                    Some((field_path(sort.field.as_deref()?), descending))
+                })
+                .collect::<Vec<_>>()
+        })
+        .filter(|sort| !sort.is_empty());
+    let peers = sort.is_some() && transform.ignore_peers != Some(true);
+    let frame = transform
+        .frame
+        .clone()
+        .unwrap_or_else(|| vec![None, Some(0.0)]);
+    let frame_bound = |i: usize| frame.get(i).copied().flatten().map(|offset| offset as i64);
+
+    let mut outputs = vec![];
+    for mut partition in groups(rows, transform.groupby.as_deref().unwrap_or_default()) {
+        if let Some(sort) = &sort {
+            partition.sort_by(|a, b| compare(sort, &rows[*a], &rows[*b]));
+        }
+        let data = partition.iter().map(|i| &rows[*i]).collect::<Vec<_>>();
+        let n = data.len();
+        let distinct = |a: usize, b: usize| {
+            sort.as_ref()
+                .map_or(true, |sort| compare(sort, data[a], data[b]).is_ne())
+        };
+        for (index, row) in partition.iter().enumerate() {
+            // Offsets are signed: a frame can lie entirely before or after the row
+            let clamp = |i: i64| i.clamp(0, n as i64) as usize;
+            let mut i1 = frame_bound(1).map_or(n, |offset| clamp(index as i64 + offset + 1));
+            let mut i0 = frame_bound(0).map_or(0, |offset| clamp(index as i64 + offset).min(i1));
+            if peers && i0 < i1 {
+                while i0 > 0 && !distinct(i0 - 1, i0) {
+                    i0 -= 1;
+                }
+                while i1 < n && !distinct(i1 - 1, i1) {
+                    i1 += 1;
+                }
+            }
+            let w = Frame {
+                data: &data,
+                index,
+                i0,
+                i1,
+                distinct: &distinct,
+            };
+            let values = operations
+                .iter()
+                .map(|operation| operation.compute(&w))
+                .collect::<Result<Vec<_>, _>>()?;
+            outputs.push((*row, values));
+        }
+    }
+    for (i, values) in outputs {
+        for (name, value) in names.iter().zip(values) {
+            set(&mut rows[i], name, value);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,387 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Typed [Vega event streams](https://vega.github.io/vega/docs/event-streams/), as used by the
+//! `on`, `clear`, `translate` and `zoom` of selections.
+//!
+//! ```
+//! use vega_lite_3::event::*;
+//!
+//! let drag = EventStream::event("mousemove")
+//!     .source(EventSource::Window)
+//!     .between(EventStream::event("mousedown"), EventStream::event("mouseup"));
+//! assert_eq!(
+//!     drag.selector().unwrap(),
+//!     "[mousedown, mouseup] > window:mousemove"
+//! );
+//!
+//! let parsed: EventStream = "[mousedown, mouseup] > window:mousemove".parse().unwrap();
+//! assert_eq!(parsed, drag);
+//! ```
+
+mod parser;
+
+pub use parser::{parse, ParseError};
+
+use crate::error::Error;
+use crate::schema::Translate;
+use serde::{Deserialize, Serialize};
+use std::convert::TryFrom;
+use std::str::FromStr;
+
+/// Where events come from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EventSource {
+    /// The view, the default
+    View,
+    /// The group mark containing the selection
+    Scope,
+    /// The browser window
+    Window,
+    /// The elements of the page matching a CSS selector
+    #[serde(untagged)]
+    Selector(String),
+}
+
+/// A single type of event
+#[derive(Debug, Clone, PartialEq)]
+pub struct Event {
+    /// Where the events come from
+    pub source: Option<EventSource>,
+    /// The type of events, like `click`, or `*` for all types
+    pub event_type: String,
+    /// Only events on marks with this name
+    pub markname: Option<String>,
+    /// Only events on marks of this type
+    pub marktype: Option<String>,
+}
+
+/// What an event stream listens to
+#[derive(Debug, Clone, PartialEq)]
+pub enum StreamKind {
+    /// Events of a single type
+    Event(Event),
+    /// Events of any of the streams
+    Merge(Vec<EventStream>),
+    /// The events of another stream
+    Stream(Box<EventStream>),
+}
+
+/// A stream of events, with its filters
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "Repr", into = "Repr")]
+pub struct EventStream {
+    /// The events listened to
+    pub kind: StreamKind,
+    /// Only events between an event of the first stream and an event of the second one
+    pub between: Option<Box<(EventStream, EventStream)>>,
+    /// Only events satisfying all these expressions
+    pub filter: Vec<String>,
+    /// Minimum number of milliseconds between two events
+    pub throttle: Option<f64>,
+    /// Number of milliseconds to wait for the events to stop before emitting the last one
+    pub debounce: Option<f64>,
+    /// Whether to stop the propagation of events
+    pub consume: Option<bool>,
+}
+
+impl EventStream {
+    fn new(kind: StreamKind) -> Self {
+        EventStream {
+            kind,
+            between: None,
+            filter: vec![],
+            throttle: None,
+            debounce: None,
+            consume: None,
+        }
+    }
+
+    /// Events of a type, like `click`, or `*` for all types
+    pub fn event(event_type: &str) -> Self {
+        EventStream::new(StreamKind::Event(Event {
+            source: None,
+            event_type: event_type.to_string(),
+            markname: None,
+            marktype: None,
+        }))
+    }
+
+    /// Events of any of the streams
+    pub fn merge(streams: Vec<EventStream>) -> Self {
+        EventStream::new(StreamKind::Merge(streams))
+    }
+
+    /// The events of another stream, to filter them again
+    pub fn stream(stream: EventStream) -> Self {
+        EventStream::new(StreamKind::Stream(Box::new(stream)))
+    }
+
+    fn events(&mut self, set: &impl Fn(&mut Event)) {
+        match &mut self.kind {
+            StreamKind::Event(event) => set(event),
+            StreamKind::Merge(streams) => streams.iter_mut().for_each(|stream| stream.events(set)),
+            StreamKind::Stream(stream) => stream.events(set),
+        }
+    }
+
+    /// Listen to events from `source`, for all the events of the stream
+    pub fn source(mut self, source: EventSource) -> Self {
+        self.events(&|event| event.source = Some(source.clone()));
+        self
+    }
+
+    /// Only events on marks named `markname`, for all the events of the stream
+    pub fn markname(mut self, markname: &str) -> Self {
+        self.events(&|event| event.markname = Some(markname.to_string()));
+        self
+    }
+
+    /// Only events on marks of type `marktype`, for all the events of the stream
+    pub fn marktype(mut self, marktype: &str) -> Self {
+        self.events(&|event| event.marktype = Some(marktype.to_string()));
+        self
+    }
+
+    /// Only events between an event of `start` and an event of `end`
+    pub fn between(mut self, start: EventStream, end: EventStream) -> Self {
+        self.between = Some(Box::new((start, end)));
+        self
+    }
+
+    /// Only events satisfying an [expression](../expr/index.html), in addition to the previous
+    /// filters
+    pub fn filter(mut self, filter: impl Into<String>) -> Self {
+        self.filter.push(filter.into());
+        self
+    }
+
+    /// At most one event every `throttle` milliseconds
+    pub fn throttle(mut self, throttle: f64) -> Self {
+        self.throttle = Some(throttle);
+        self
+    }
+
+    /// Only the last event, once no event happened for `debounce` milliseconds
+    pub fn debounce(mut self, debounce: f64) -> Self {
+        self.debounce = Some(debounce);
+        self
+    }
+
+    /// Stop the propagation of the events
+    pub fn consume(mut self, consume: bool) -> Self {
+        self.consume = Some(consume);
+        self
+    }
+
+    /// The event selector string of the stream, unless the stream can only be written as a
+    /// json object
+    pub fn selector(&self) -> Option<String> {
+        parser::print(self)
+    }
+}
+
+impl FromStr for EventStream {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
+
+/// One or several filter expressions
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum Filter {
+    One(String),
+    Many(Vec<String>),
+}
+
+/// The json object of an event stream
+#[derive(Serialize, Deserialize)]
+struct Repr {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source: Option<EventSource>,
+    #[serde(rename = "type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    event_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    markname: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    marktype: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    merge: Option<Vec<EventStream>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream: Option<Box<EventStream>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    between: Option<Vec<EventStream>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    filter: Option<Filter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    throttle: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    debounce: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    consume: Option<bool>,
+}
+
+impl TryFrom<Repr> for EventStream {
+    type Error = String;
+
+    fn try_from(repr: Repr) -> Result<Self, String> {
+        let kind = match (repr.event_type, repr.merge, repr.stream) {
+            (Some(event_type), None, None) => StreamKind::Event(Event {
+                source: repr.source,
+                event_type,
+                markname: repr.markname,
+                marktype: repr.marktype,
+            }),
+            (None, Some(merge), None) => StreamKind::Merge(merge),
+            (None, None, Some(stream)) => StreamKind::Stream(stream),
+            _ => {
+                return Err(
+                    "an event stream needs exactly one of `type`, `merge` and `stream`".to_string(),
+                )
+            }
+        };
+        let between = match repr.between {
+            None => None,
+            Some(between) => match <[EventStream; 2]>::try_from(between) {
+                Ok([start, end]) => Some(Box::new((start, end))),
+                Err(_) => return Err("`between` needs two event streams".to_string()),
+            },
+        };
+        Ok(EventStream {
+            kind,
+            between,
+            filter: match repr.filter {
+                None => vec![],
+                Some(Filter::One(filter)) => vec![filter],
+                Some(Filter::Many(filters)) => filters,
+            },
+            throttle: repr.throttle,
+            debounce: repr.debounce,
+            consume: repr.consume,
+        })
+    }
+}
+
+impl From<EventStream> for Repr {
+    fn from(stream: EventStream) -> Self {
+        let mut repr = Repr {
+            source: None,
+            event_type: None,
+            markname: None,
+            marktype: None,
+            merge: None,
+            stream: None,
+            between: stream.between.map(|between| vec![between.0, between.1]),
+            filter: match stream.filter.len() {
+                0 => None,
+                1 => stream.filter.into_iter().next().map(Filter::One),
+                _ => Some(Filter::Many(stream.filter)),
+            },
+            throttle: stream.throttle,
+            debounce: stream.debounce,
+            consume: stream.consume,
+        };
+        match stream.kind {
+            StreamKind::Event(event) => {
+                repr.source = event.source;
+                repr.event_type = Some(event.event_type);
+                repr.markname = event.markname;
+                repr.marktype = event.marktype;
+            }
+            StreamKind::Merge(merge) => repr.merge = Some(merge),
+            StreamKind::Stream(stream) => repr.stream = Some(stream),
+        }
+        repr
+    }
+}
+
+impl From<EventStream> for serde_json::Value {
+    fn from(stream: EventStream) -> Self {
+        serde_json::to_value(stream).expect("an event stream is always valid json")
+    }
+}
+
+impl TryFrom<&serde_json::Value> for EventStream {
+    type Error = Error;
+
+    /// Read an event stream from its json object or selector string
+    fn try_from(value: &serde_json::Value) -> Result<Self, Self::Error> {
+        match value {
+            serde_json::Value::String(selector) => {
+                parse(selector).map_err(|err| Error::Validation(format!("event selector: {}", err)))
+            }
+            value => Ok(serde_json::from_value(value.clone())?),
+        }
+    }
+}
+
+impl TryFrom<EventStream> for Translate {
+    type Error = Error;
+
+    /// The selector string of an event stream, for the properties that don't accept json
+    /// objects
+    fn try_from(stream: EventStream) -> Result<Self, Self::Error> {
+        stream.selector().map(Translate::String).ok_or_else(|| {
+            Error::Validation(format!(
+                "event stream without selector string: {}",
+                serde_json::Value::from(stream)
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_event_stream_json() {
+        let drag = EventStream::event("mousemove")
+            .between(
+                EventStream::event("mousedown").markname("brush"),
+                EventStream::event("mouseup").source(EventSource::Window),
+            )
+            .filter("event.shiftKey")
+            .throttle(16.0);
+        let value = json!({
+            "type": "mousemove",
+            "between": [
+                {"type": "mousedown", "markname": "brush"},
+                {"source": "window", "type": "mouseup"}
+            ],
+            "filter": "event.shiftKey",
+            "throttle": 16.0
+        });
+        assert_eq!(serde_json::Value::from(drag.clone()), value);
+        assert_eq!(EventStream::try_from(&value).unwrap(), drag);
+
+        let merged = EventStream::merge(vec![
+            EventStream::event("click"),
+            EventStream::event("dblclick"),
+        ])
+        .source(EventSource::Scope);
+        assert_eq!(
+            serde_json::Value::from(merged),
+            json!({"merge": [
+                {"source": "scope", "type": "click"},
+                {"source": "scope", "type": "dblclick"}
+            ]})
+        );
+        assert!(EventStream::try_from(&json!({"type": "click", "merge": []})).is_err());
+        assert!(EventStream::try_from(&json!({"type": "click", "between": []})).is_err());
+    }
+}
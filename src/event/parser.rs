@@ -0,0 +1,456 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Parser and printer of event selector strings, following `vega-event-selector`.
+
+use super::{Event, EventSource, EventStream, StreamKind};
+use std::fmt;
+
+/// Mark types that can prefix an event type, like `rect:mousedown`
+pub(crate) const MARK_TYPES: &[&str] = &[
+    "arc", "area", "group", "image", "line", "path", "rect", "rule", "shape", "symbol", "text",
+    "trail",
+];
+
+/// Characters that can't be part of an event type
+const ILLEGAL: &[char] = &['[', ']', '{', '}'];
+
+/// Error returned when a string is not a valid event selector
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// What went wrong
+    pub message: String,
+    /// Byte offset in the selector string where the error was detected
+    pub position: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+fn error<T>(message: impl Into<String>, position: usize) -> Result<T, ParseError> {
+    Err(ParseError {
+        message: message.into(),
+        position,
+    })
+}
+
+/// A part of the selector, with its position in the whole selector
+#[derive(Clone, Copy)]
+struct Part<'a> {
+    s: &'a str,
+    position: usize,
+}
+
+impl<'a> Part<'a> {
+    fn trim(self) -> Self {
+        let trimmed = self.s.trim_start();
+        Part {
+            s: trimmed.trim_end(),
+            position: self.position + self.s.len() - trimmed.len(),
+        }
+    }
+
+    fn slice(self, start: usize, end: usize) -> Self {
+        Part {
+            s: &self.s[start..end],
+            position: self.position + start,
+        }
+    }
+
+    /// The index of `end` from `i`, outside of nested `push` and `pop` characters, or the
+    /// length of the part
+    fn find(self, mut i: usize, end: u8, push: &[u8], pop: &[u8]) -> usize {
+        let bytes = self.s.as_bytes();
+        let mut count = 0i32;
+        while i < bytes.len() {
+            let c = bytes[i];
+            if count == 0 && c == end {
+                return i;
+            } else if pop.contains(&c) {
+                count -= 1;
+            } else if push.contains(&c) {
+                count += 1;
+            }
+            i += 1;
+        }
+        i
+    }
+}
+
+/// Split a selector on the commas merging streams
+fn parse_merge(part: Part) -> Result<Vec<Part>, ParseError> {
+    let mut parts = vec![];
+    let (mut start, mut i) = (0, 0);
+    while i < part.s.len() {
+        i = part.find(i, b',', b"[{", b"]}");
+        parts.push(part.slice(start, i).trim());
+        i += 1;
+        start = i;
+    }
+    if parts.is_empty() {
+        return error("empty event selector", part.position);
+    }
+    Ok(parts)
+}
+
+fn parse_selector(part: Part) -> Result<EventStream, ParseError> {
+    if part.s.starts_with('[') {
+        parse_between(part)
+    } else {
+        parse_stream(part)
+    }
+}
+
+fn parse_between(part: Part) -> Result<EventStream, ParseError> {
+    let n = part.s.len();
+    let i = part.find(1, b']', b"[", b"]");
+    if i == n {
+        return error("unterminated `[`", part.position);
+    }
+    let streams = parse_merge(part.slice(1, i))?;
+    if streams.len() != 2 {
+        return error("`between` needs two event streams", part.position);
+    }
+    let rest = part.slice(i + 1, n).trim();
+    if !rest.s.starts_with('>') {
+        return error("expected `>` after `between`", rest.position);
+    }
+    let start = parse_selector(streams[0])?;
+    let end = parse_selector(streams[1])?;
+    let stream = parse_selector(rest.slice(1, rest.s.len()).trim())?;
+    Ok(if stream.between.is_some() {
+        EventStream::stream(stream).between(start, end)
+    } else {
+        stream.between(start, end)
+    })
+}
+
+fn parse_throttle(part: Part) -> Result<Vec<f64>, ParseError> {
+    let values = part.s.split(',').collect::<Vec<_>>();
+    if part.s.is_empty() || values.len() > 2 {
+        return error(
+            "expected `{throttle}` or `{throttle, debounce}`",
+            part.position,
+        );
+    }
+    values
+        .iter()
+        .map(|value| match value.trim() {
+            "" => Ok(0.0),
+            value => value
+                .parse()
+                .or_else(|_| error(format!("invalid number `{}`", value), part.position)),
+        })
+        .collect()
+}
+
+fn parse_stream(mut part: Part) -> Result<EventStream, ParseError> {
+    let mut throttle = vec![];
+    if part.s.ends_with('}') {
+        match part.s.rfind('{') {
+            Some(i) => {
+                throttle = parse_throttle(part.slice(i + 1, part.s.len() - 1))?;
+                part = part.slice(0, i).trim();
+            }
+            None => return error("unmatched `}`", part.position + part.s.len() - 1),
+        }
+    }
+    let n = part.s.len();
+    if n == 0 {
+        return error("expected an event type", part.position);
+    }
+    let markname = part.s.starts_with('@');
+    let mut i = usize::from(markname);
+    let mut start = 0;
+    let mut sources = vec![];
+    let j = part.find(i, b':', b"", b"");
+    if j < n {
+        sources.push(part.slice(start, j).trim());
+        i = j + 1;
+        start = i;
+    }
+    let mut filters = vec![];
+    i = part.find(i, b'[', b"", b"");
+    sources.push(part.slice(start, i).trim());
+    start = i + 1;
+    if i < n && start == n {
+        return error("unterminated `[`", part.position + i);
+    }
+    i = start;
+    while i < n {
+        i = part.find(i, b']', b"", b"");
+        if i == n {
+            return error("unterminated `[`", part.position + start - 1);
+        }
+        filters.push(part.slice(start, i).trim().s.to_string());
+        i += 1;
+        if i < n && part.s.as_bytes()[i] != b'[' {
+            return error("expected `[` after a filter", part.position + i);
+        }
+        i += 1;
+        start = i;
+    }
+
+    let event_type = sources[sources.len() - 1];
+    if event_type.s.is_empty() || event_type.s.contains(ILLEGAL) {
+        return error(
+            format!("invalid event type `{}`", event_type.s),
+            event_type.position,
+        );
+    }
+    let mut event = Event {
+        source: None,
+        event_type: event_type.s.to_string(),
+        markname: None,
+        marktype: None,
+    };
+    if sources.len() > 1 {
+        let prefix = sources[0].s;
+        if markname {
+            event.markname = Some(prefix[1..].to_string());
+        } else if MARK_TYPES.contains(&prefix) {
+            event.marktype = Some(prefix.to_string());
+        } else {
+            event.source = Some(match prefix {
+                "view" => EventSource::View,
+                "scope" => EventSource::Scope,
+                "window" => EventSource::Window,
+                selector => EventSource::Selector(selector.to_string()),
+            });
+        }
+    }
+    let mut stream = EventStream::new(StreamKind::Event(event));
+    if let StreamKind::Event(event) = &mut stream.kind {
+        if let Some(event_type) = event.event_type.strip_suffix('!') {
+            event.event_type = event_type.to_string();
+            stream.consume = Some(true);
+        }
+    }
+    stream.filter = filters;
+    stream.throttle = throttle.first().copied().filter(|t| *t != 0.0);
+    stream.debounce = throttle.get(1).copied().filter(|d| *d != 0.0);
+    Ok(stream)
+}
+
+/// Parse an event selector string, like `[mousedown, mouseup] > mousemove`; streams
+/// separated by commas are merged
+pub fn parse(input: &str) -> Result<EventStream, ParseError> {
+    let part = Part {
+        s: input,
+        position: 0,
+    }
+    .trim();
+    let mut streams = parse_merge(part)?
+        .into_iter()
+        .map(parse_selector)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(if streams.len() == 1 {
+        streams.remove(0)
+    } else {
+        EventStream::merge(streams)
+    })
+}
+
+/// Whether a name can be written in a selector without being split or reinterpreted
+fn plain(name: &str) -> bool {
+    !name.is_empty() && name.trim() == name && !name.contains(|c| "[]{},:!>@".contains(c))
+}
+
+fn print_event(event: &Event) -> Option<String> {
+    if !plain(&event.event_type) && event.event_type != "*" {
+        return None;
+    }
+    let prefix = match (&event.source, &event.markname, &event.marktype) {
+        (None, None, None) => None,
+        (None, Some(markname), None) if plain(markname) => Some(format!("@{}", markname)),
+        (None, None, Some(marktype)) if MARK_TYPES.contains(&marktype.as_str()) => {
+            Some(marktype.clone())
+        }
+        (Some(EventSource::View), None, None) => Some("view".to_string()),
+        (Some(EventSource::Scope), None, None) => Some("scope".to_string()),
+        (Some(EventSource::Window), None, None) => Some("window".to_string()),
+        (Some(EventSource::Selector(selector)), None, None)
+            if plain(selector) && !MARK_TYPES.contains(&selector.as_str()) =>
+        {
+            Some(selector.clone())
+        }
+        _ => return None,
+    };
+    Some(match prefix {
+        Some(prefix) => format!("{}:{}", prefix, event.event_type),
+        None => event.event_type.clone(),
+    })
+}
+
+/// A stream that is parsed back as a single stream, not a merge
+fn print_single(stream: &EventStream) -> Option<String> {
+    match stream.kind {
+        StreamKind::Merge(_) => None,
+        _ => print(stream),
+    }
+}
+
+/// Print an event stream as a selector string, if it can be parsed back as the same stream
+pub(crate) fn print(stream: &EventStream) -> Option<String> {
+    let between = match &stream.between {
+        Some(between) => format!(
+            "[{}, {}] > ",
+            print_single(&between.0)?,
+            print_single(&between.1)?
+        ),
+        None => String::new(),
+    };
+    let modified = !stream.filter.is_empty()
+        || stream.throttle.is_some()
+        || stream.debounce.is_some()
+        || stream.consume.is_some();
+    match &stream.kind {
+        StreamKind::Merge(streams) if streams.len() > 1 && between.is_empty() && !modified => {
+            streams
+                .iter()
+                .map(print_single)
+                .collect::<Option<Vec<_>>>()
+                .map(|streams| streams.join(", "))
+        }
+        StreamKind::Stream(inner)
+            if inner.between.is_some() && !between.is_empty() && !modified =>
+        {
+            Some(format!("{}{}", between, print_single(inner)?))
+        }
+        StreamKind::Event(event) => {
+            let mut selector = between + &print_event(event)?;
+            match stream.consume {
+                Some(true) => selector.push('!'),
+                Some(false) => return None,
+                None => {}
+            }
+            for filter in &stream.filter {
+                if filter.contains(['[', ']']) || filter.trim() != filter || filter.is_empty() {
+                    return None;
+                }
+                selector.push_str(&format!("[{}]", filter));
+            }
+            match (stream.throttle, stream.debounce) {
+                (None, None) => {}
+                (Some(throttle), None) if throttle != 0.0 => {
+                    selector.push_str(&format!("{{{}}}", throttle))
+                }
+                (throttle, Some(debounce)) if throttle != Some(0.0) && debounce != 0.0 => {
+                    selector.push_str(&format!("{{{},{}}}", throttle.unwrap_or(0.0), debounce))
+                }
+                _ => return None,
+            }
+            Some(selector)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn to_json(selector: &str) -> serde_json::Value {
+        serde_json::Value::from(parse(selector).unwrap())
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(to_json("click"), json!({"type": "click"}));
+        assert_eq!(
+            to_json("rect:mousedown!"),
+            json!({"type": "mousedown", "marktype": "rect", "consume": true})
+        );
+        assert_eq!(
+            to_json("@cell:mouseover, window:mouseup"),
+            json!({"merge": [
+                {"type": "mouseover", "markname": "cell"},
+                {"source": "window", "type": "mouseup"}
+            ]})
+        );
+        assert_eq!(
+            to_json("[mousedown, window:mouseup] > window:mousemove{16, 100}"),
+            json!({
+                "source": "window",
+                "type": "mousemove",
+                "between": [{"type": "mousedown"}, {"source": "window", "type": "mouseup"}],
+                "throttle": 16.0,
+                "debounce": 100.0
+            })
+        );
+        assert_eq!(
+            to_json("wheel![event.shiftKey][!event.altKey]"),
+            json!({"type": "wheel", "consume": true, "filter": ["event.shiftKey", "!event.altKey"]})
+        );
+        assert_eq!(
+            to_json("[a, b] > [c, d] > e"),
+            json!({
+                "stream": {"type": "e", "between": [{"type": "c"}, {"type": "d"}]},
+                "between": [{"type": "a"}, {"type": "b"}]
+            })
+        );
+        assert_eq!(
+            to_json("#button:click"),
+            json!({"source": "#button", "type": "click"})
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("[mousedown] > mousemove").unwrap_err().message,
+            "`between` needs two event streams"
+        );
+        assert_eq!(
+            parse("[mousedown, mouseup] mousemove").unwrap_err(),
+            ParseError {
+                message: "expected `>` after `between`".to_string(),
+                position: 21
+            }
+        );
+        assert_eq!(parse("click[a").unwrap_err().position, 5);
+        assert!(parse("click{a}").is_err());
+        assert!(parse("click{1,2,3}").is_err());
+        assert!(parse("").is_err());
+        assert!(parse("window:").is_err());
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for selector in &[
+            "click",
+            "window:mouseup!",
+            "@brush:mousedown, scope:touchstart",
+            "[mousedown, window:mouseup] > window:mousemove!{16}",
+            "[a, b] > [c[event.x], d{0,5}] > e",
+            "wheel[event.shiftKey][!event.altKey]{10,20}",
+        ] {
+            assert_eq!(print(&parse(selector).unwrap()).as_deref(), Some(*selector));
+        }
+        let stream = EventStream::merge(vec![EventStream::event("click")]);
+        assert_eq!(print(&stream), None);
+        assert_eq!(print(&EventStream::event("click").consume(false)), None);
+        assert_eq!(
+            print(
+                &EventStream::event("click")
+                    .markname("a")
+                    .source(EventSource::Window)
+            ),
+            None
+        );
+    }
+}
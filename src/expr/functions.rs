@@ -0,0 +1,392 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Functions of the [Vega expression language](https://vega.github.io/vega/docs/expressions/).
+
+use super::{call, Expr};
+
+/// Functions of the expression language, with their minimum and maximum number of arguments
+/// (`None` when there is no maximum).
+pub(crate) const LIBRARY: &[(&str, usize, Option<usize>)] = &[
+    ("isArray", 1, Some(1)),
+    ("isBoolean", 1, Some(1)),
+    ("isDate", 1, Some(1)),
+    ("isDefined", 1, Some(1)),
+    ("isNumber", 1, Some(1)),
+    ("isObject", 1, Some(1)),
+    ("isRegExp", 1, Some(1)),
+    ("isString", 1, Some(1)),
+    ("isValid", 1, Some(1)),
+    ("toBoolean", 1, Some(1)),
+    ("toDate", 1, Some(1)),
+    ("toNumber", 1, Some(1)),
+    ("toString", 1, Some(1)),
+    ("if", 3, Some(3)),
+    ("isNaN", 1, Some(1)),
+    ("isFinite", 1, Some(1)),
+    ("abs", 1, Some(1)),
+    ("acos", 1, Some(1)),
+    ("asin", 1, Some(1)),
+    ("atan", 1, Some(1)),
+    ("atan2", 2, Some(2)),
+    ("ceil", 1, Some(1)),
+    ("clamp", 3, Some(3)),
+    ("cos", 1, Some(1)),
+    ("exp", 1, Some(1)),
+    ("floor", 1, Some(1)),
+    ("log", 1, Some(1)),
+    ("max", 1, None),
+    ("min", 1, None),
+    ("pow", 2, Some(2)),
+    ("random", 0, Some(0)),
+    ("round", 1, Some(1)),
+    ("sin", 1, Some(1)),
+    ("sqrt", 1, Some(1)),
+    ("tan", 1, Some(1)),
+    ("sampleNormal", 0, Some(2)),
+    ("cumulativeNormal", 1, Some(3)),
+    ("densityNormal", 1, Some(3)),
+    ("quantileNormal", 1, Some(3)),
+    ("sampleLogNormal", 0, Some(2)),
+    ("cumulativeLogNormal", 1, Some(3)),
+    ("densityLogNormal", 1, Some(3)),
+    ("quantileLogNormal", 1, Some(3)),
+    ("sampleUniform", 0, Some(2)),
+    ("cumulativeUniform", 1, Some(3)),
+    ("densityUniform", 1, Some(3)),
+    ("quantileUniform", 1, Some(3)),
+    ("now", 0, Some(0)),
+    ("datetime", 1, Some(7)),
+    ("date", 1, Some(1)),
+    ("day", 1, Some(1)),
+    ("year", 1, Some(1)),
+    ("quarter", 1, Some(1)),
+    ("month", 1, Some(1)),
+    ("hours", 1, Some(1)),
+    ("minutes", 1, Some(1)),
+    ("seconds", 1, Some(1)),
+    ("milliseconds", 1, Some(1)),
+    ("time", 1, Some(1)),
+    ("timezoneoffset", 1, Some(1)),
+    ("utc", 1, Some(7)),
+    ("utcdate", 1, Some(1)),
+    ("utcday", 1, Some(1)),
+    ("utcyear", 1, Some(1)),
+    ("utcquarter", 1, Some(1)),
+    ("utcmonth", 1, Some(1)),
+    ("utchours", 1, Some(1)),
+    ("utcminutes", 1, Some(1)),
+    ("utcseconds", 1, Some(1)),
+    ("utcmilliseconds", 1, Some(1)),
+    ("extent", 1, Some(1)),
+    ("clampRange", 3, Some(3)),
+    ("indexof", 2, Some(2)),
+    ("inrange", 2, Some(3)),
+    ("join", 1, Some(2)),
+    ("lastindexof", 2, Some(2)),
+    ("length", 1, Some(1)),
+    ("lerp", 2, Some(2)),
+    ("peek", 1, Some(1)),
+    ("reverse", 1, Some(1)),
+    ("sequence", 1, Some(3)),
+    ("slice", 2, Some(3)),
+    ("span", 1, Some(1)),
+    ("lower", 1, Some(1)),
+    ("pad", 2, Some(4)),
+    ("parseFloat", 1, Some(1)),
+    ("parseInt", 1, Some(2)),
+    ("replace", 3, Some(3)),
+    ("split", 2, Some(3)),
+    ("substring", 2, Some(3)),
+    ("trim", 1, Some(1)),
+    ("truncate", 2, Some(5)),
+    ("upper", 1, Some(1)),
+    ("merge", 1, None),
+    ("dayFormat", 1, Some(1)),
+    ("dayAbbrevFormat", 1, Some(1)),
+    ("format", 2, Some(2)),
+    ("monthFormat", 1, Some(1)),
+    ("monthAbbrevFormat", 1, Some(1)),
+    ("timeFormat", 2, Some(2)),
+    ("timeParse", 2, Some(2)),
+    ("utcFormat", 2, Some(2)),
+    ("utcParse", 2, Some(2)),
+    ("regexp", 1, Some(2)),
+    ("test", 2, Some(3)),
+    ("rgb", 1, Some(4)),
+    ("hsl", 1, Some(4)),
+    ("lab", 1, Some(4)),
+    ("hcl", 1, Some(4)),
+    ("luminance", 1, Some(1)),
+    ("contrast", 2, Some(2)),
+    ("item", 0, Some(0)),
+    ("group", 0, Some(1)),
+    ("xy", 0, Some(1)),
+    ("x", 0, Some(1)),
+    ("y", 0, Some(1)),
+    ("pinchDistance", 1, Some(1)),
+    ("pinchAngle", 1, Some(1)),
+    ("inScope", 1, Some(1)),
+    ("data", 1, Some(1)),
+    ("indata", 3, Some(3)),
+    ("scale", 2, Some(3)),
+    ("invert", 2, Some(3)),
+    ("copy", 1, Some(2)),
+    ("domain", 1, Some(2)),
+    ("range", 1, Some(2)),
+    ("bandwidth", 1, Some(2)),
+    ("bandspace", 1, Some(3)),
+    ("gradient", 3, Some(5)),
+    ("panLinear", 2, Some(2)),
+    ("panLog", 2, Some(2)),
+    ("panPow", 3, Some(3)),
+    ("panSymlog", 3, Some(3)),
+    ("zoomLinear", 3, Some(3)),
+    ("zoomLog", 3, Some(3)),
+    ("zoomPow", 4, Some(4)),
+    ("zoomSymlog", 4, Some(4)),
+    ("geoArea", 2, Some(3)),
+    ("geoBounds", 2, Some(3)),
+    ("geoCentroid", 2, Some(3)),
+    ("treePath", 3, Some(3)),
+    ("treeAncestors", 2, Some(2)),
+    ("containerSize", 0, Some(0)),
+    ("screen", 0, Some(0)),
+    ("windowSize", 0, Some(0)),
+    ("warn", 1, None),
+    ("info", 1, None),
+    ("debug", 1, None),
+    ("vlSelectionTest", 2, Some(3)),
+];
+
+/// Lookup a function of the expression language by name, giving its arity bounds
+pub(crate) fn lookup(name: &str) -> Option<(usize, Option<usize>)> {
+    LIBRARY
+        .iter()
+        .find(|(function, _, _)| *function == name)
+        .map(|(_, min, max)| (*min, *max))
+}
+
+macro_rules! functions {
+    ( $( $(#[$doc:meta])* $rust:ident => $name:literal ( $( $arg:ident ),* ) ),* $(,)? ) => {
+        $(
+            $(#[$doc])*
+            pub fn $rust( $( $arg: impl Into<Expr> ),* ) -> Expr {
+                call($name, vec![ $( $arg.into() ),* ])
+            }
+        )*
+    };
+}
+
+macro_rules! variadic_functions {
+    ( $( $(#[$doc:meta])* $rust:ident => $name:literal ),* $(,)? ) => {
+        $(
+            $(#[$doc])*
+            pub fn $rust<T: Into<Expr>>(args: Vec<T>) -> Expr {
+                call($name, args.into_iter().map(Into::into).collect())
+            }
+        )*
+    };
+}
+
+functions! {
+    /// Whether `value` is an array
+    is_array => "isArray"(value),
+    /// Whether `value` is a boolean
+    is_boolean => "isBoolean"(value),
+    /// Whether `value` is a `Date` object
+    is_date => "isDate"(value),
+    /// Whether `value` is defined
+    is_defined => "isDefined"(value),
+    /// Whether `value` is a number
+    is_number => "isNumber"(value),
+    /// Whether `value` is an object
+    is_object => "isObject"(value),
+    /// Whether `value` is a string
+    is_string => "isString"(value),
+    /// Whether `value` is neither `null`, `undefined` nor `NaN`
+    is_valid => "isValid"(value),
+    /// Coerce `value` to a boolean
+    to_boolean => "toBoolean"(value),
+    /// Coerce `value` to a `Date`
+    to_date => "toDate"(value),
+    /// Coerce `value` to a number
+    to_number => "toNumber"(value),
+    /// Coerce `value` to a string
+    to_string => "toString"(value),
+    /// Whether `value` is not a number
+    is_nan => "isNaN"(value),
+    /// Whether `value` is a finite number
+    is_finite => "isFinite"(value),
+    /// Absolute value
+    abs => "abs"(value),
+    /// Arc cosine
+    acos => "acos"(value),
+    /// Arc sine
+    asin => "asin"(value),
+    /// Arc tangent
+    atan => "atan"(value),
+    /// Arc tangent of the quotient of its arguments
+    atan2 => "atan2"(dy, dx),
+    /// Round up to the nearest integer
+    ceil => "ceil"(value),
+    /// Restrict `value` between `min` and `max`
+    clamp => "clamp"(value, min, max),
+    /// Cosine
+    cos => "cos"(value),
+    /// Exponential
+    exp => "exp"(value),
+    /// Round down to the nearest integer
+    floor => "floor"(value),
+    /// Natural logarithm
+    log => "log"(value),
+    /// `value` raised to the power of `exponent`
+    pow => "pow"(value, exponent),
+    /// Pseudo-random number between 0 and 1
+    random => "random"(),
+    /// Round to the nearest integer
+    round => "round"(value),
+    /// Sine
+    sin => "sin"(value),
+    /// Square root
+    sqrt => "sqrt"(value),
+    /// Tangent
+    tan => "tan"(value),
+    /// Current timestamp
+    now => "now"(),
+    /// Day of the month, in local time
+    date => "date"(datetime),
+    /// Day of the week, in local time
+    day => "day"(datetime),
+    /// Year, in local time
+    year => "year"(datetime),
+    /// Quarter of the year (0-3), in local time
+    quarter => "quarter"(datetime),
+    /// Month (0-11), in local time
+    month => "month"(datetime),
+    /// Hours, in local time
+    hours => "hours"(datetime),
+    /// Minutes, in local time
+    minutes => "minutes"(datetime),
+    /// Seconds, in local time
+    seconds => "seconds"(datetime),
+    /// Milliseconds, in local time
+    milliseconds => "milliseconds"(datetime),
+    /// Epoch-based timestamp
+    time => "time"(datetime),
+    /// Timezone offset in minutes
+    timezoneoffset => "timezoneoffset"(datetime),
+    /// Day of the month, in UTC time
+    utcdate => "utcdate"(datetime),
+    /// Day of the week, in UTC time
+    utcday => "utcday"(datetime),
+    /// Year, in UTC time
+    utcyear => "utcyear"(datetime),
+    /// Quarter of the year (0-3), in UTC time
+    utcquarter => "utcquarter"(datetime),
+    /// Month (0-11), in UTC time
+    utcmonth => "utcmonth"(datetime),
+    /// Hours, in UTC time
+    utchours => "utchours"(datetime),
+    /// Minutes, in UTC time
+    utcminutes => "utcminutes"(datetime),
+    /// Seconds, in UTC time
+    utcseconds => "utcseconds"(datetime),
+    /// Milliseconds, in UTC time
+    utcmilliseconds => "utcmilliseconds"(datetime),
+    /// `[min, max]` of an array
+    extent => "extent"(array),
+    /// First index of `value` in an array or string
+    indexof => "indexof"(array, value),
+    /// Whether `value` is within the `[min, max]` range
+    inrange => "inrange"(value, range),
+    /// Join the elements of an array with commas
+    join => "join"(array),
+    /// Last index of `value` in an array or string
+    lastindexof => "lastindexof"(array, value),
+    /// Length of an array or string
+    length => "length"(array),
+    /// Linear interpolation between the first and last element of an array
+    lerp => "lerp"(array, fraction),
+    /// Last element of an array
+    peek => "peek"(array),
+    /// Reversed copy of an array
+    reverse => "reverse"(array),
+    /// `[0, 1, ..., stop - 1]`
+    sequence => "sequence"(stop),
+    /// Section of an array or string starting at `start`
+    slice => "slice"(array, start),
+    /// `max - min` of an array
+    span => "span"(array),
+    /// Lowercase string
+    lower => "lower"(string),
+    /// Pad a string with spaces up to `length`
+    pad => "pad"(string, length),
+    /// Parse a floating point number
+    parse_float => "parseFloat"(string),
+    /// Parse an integer
+    parse_int => "parseInt"(string),
+    /// Replace `pattern` by `replacement` in a string
+    replace => "replace"(string, pattern, replacement),
+    /// Split a string
+    split => "split"(string, separator),
+    /// Substring from `start`
+    substring => "substring"(string, start),
+    /// String with leading and trailing whitespace removed
+    trim => "trim"(string),
+    /// Truncate a string to `length` characters
+    truncate => "truncate"(string, length),
+    /// Uppercase string
+    upper => "upper"(string),
+    /// Full name of a day of the week
+    day_format => "dayFormat"(day),
+    /// Abbreviated name of a day of the week
+    day_abbrev_format => "dayAbbrevFormat"(day),
+    /// Format a number using a d3-format specifier
+    format => "format"(value, specifier),
+    /// Full name of a month
+    month_format => "monthFormat"(month),
+    /// Abbreviated name of a month
+    month_abbrev_format => "monthAbbrevFormat"(month),
+    /// Format a datetime using a d3-time-format specifier, in local time
+    time_format => "timeFormat"(value, specifier),
+    /// Parse a datetime using a d3-time-format specifier, in local time
+    time_parse => "timeParse"(string, specifier),
+    /// Format a datetime using a d3-time-format specifier, in UTC time
+    utc_format => "utcFormat"(value, specifier),
+    /// Parse a datetime using a d3-time-format specifier, in UTC time
+    utc_parse => "utcParse"(string, specifier),
+    /// Regular expression
+    regexp => "regexp"(pattern),
+    /// Whether `string` matches `regexp`
+    test => "test"(regexp, string),
+    /// RGB color from a CSS color specifier
+    rgb => "rgb"(specifier),
+    /// HSL color from a CSS color specifier
+    hsl => "hsl"(specifier),
+    /// Lab color from a CSS color specifier
+    lab => "lab"(specifier),
+    /// HCL color from a CSS color specifier
+    hcl => "hcl"(specifier),
+}
+
+variadic_functions! {
+    /// Maximum of the arguments
+    max => "max",
+    /// Minimum of the arguments
+    min => "min",
+    /// Datetime from year, month, day, hours, minutes, seconds and milliseconds, in local time
+    datetime => "datetime",
+    /// Timestamp from year, month, day, hours, minutes, seconds and milliseconds, in UTC time
+    utc => "utc",
+    /// Object merging the properties of the arguments
+    merge => "merge",
+}
@@ -0,0 +1,605 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Typed [Vega expressions](https://vega.github.io/vega/docs/expressions/), as used by
+//! `Transform.calculate`, string filters and conditional `test`s.
+//!
+//! ```
+//! use vega_lite_3::expr::*;
+//!
+//! let gender = ternary(datum("sex").eq(2), "Female", "Male");
+//! assert_eq!(gender.to_string(), "datum.sex == 2 ? 'Female' : 'Male'");
+//!
+//! let parsed: Expr = "datum.sex == 2 ? 'Female' : 'Male'".parse().unwrap();
+//! assert_eq!(parsed, gender);
+//! ```
+
+mod functions;
+mod parser;
+
+pub use functions::*;
+pub use parser::{parse, ParseError};
+
+use crate::schema::*;
+use std::fmt;
+use std::ops;
+use std::str::FromStr;
+
+/// Unary operators of the expression language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UnaryOp {
+    /// `!`
+    Not,
+    /// `-`
+    Neg,
+    /// `+`
+    Plus,
+    /// `~`
+    BitNot,
+}
+
+/// Binary operators of the expression language
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BinaryOp {
+    /// `||`
+    Or,
+    /// `&&`
+    And,
+    /// `|`
+    BitOr,
+    /// `^`
+    BitXor,
+    /// `&`
+    BitAnd,
+    /// `==`
+    Eq,
+    /// `!=`
+    Neq,
+    /// `===`
+    StrictEq,
+    /// `!==`
+    StrictNeq,
+    /// `<`
+    Lt,
+    /// `<=`
+    Lte,
+    /// `>`
+    Gt,
+    /// `>=`
+    Gte,
+    /// `<<`
+    Shl,
+    /// `>>`
+    Shr,
+    /// `>>>`
+    UShr,
+    /// `+`
+    Add,
+    /// `-`
+    Sub,
+    /// `*`
+    Mul,
+    /// `/`
+    Div,
+    /// `%`
+    Rem,
+}
+
+impl BinaryOp {
+    pub(crate) const ALL: [BinaryOp; 21] = [
+        BinaryOp::Or,
+        BinaryOp::And,
+        BinaryOp::BitOr,
+        BinaryOp::BitXor,
+        BinaryOp::BitAnd,
+        BinaryOp::Eq,
+        BinaryOp::Neq,
+        BinaryOp::StrictEq,
+        BinaryOp::StrictNeq,
+        BinaryOp::Lt,
+        BinaryOp::Lte,
+        BinaryOp::Gt,
+        BinaryOp::Gte,
+        BinaryOp::Shl,
+        BinaryOp::Shr,
+        BinaryOp::UShr,
+        BinaryOp::Add,
+        BinaryOp::Sub,
+        BinaryOp::Mul,
+        BinaryOp::Div,
+        BinaryOp::Rem,
+    ];
+
+    /// The operator as written in an expression
+    pub fn symbol(self) -> &'static str {
+        match self {
+            BinaryOp::Or => "||",
+            BinaryOp::And => "&&",
+            BinaryOp::BitOr => "|",
+            BinaryOp::BitXor => "^",
+            BinaryOp::BitAnd => "&",
+            BinaryOp::Eq => "==",
+            BinaryOp::Neq => "!=",
+            BinaryOp::StrictEq => "===",
+            BinaryOp::StrictNeq => "!==",
+            BinaryOp::Lt => "<",
+            BinaryOp::Lte => "<=",
+            BinaryOp::Gt => ">",
+            BinaryOp::Gte => ">=",
+            BinaryOp::Shl => "<<",
+            BinaryOp::Shr => ">>",
+            BinaryOp::UShr => ">>>",
+            BinaryOp::Add => "+",
+            BinaryOp::Sub => "-",
+            BinaryOp::Mul => "*",
+            BinaryOp::Div => "/",
+            BinaryOp::Rem => "%",
+        }
+    }
+
+    pub(crate) fn precedence(self) -> u8 {
+        match self {
+            BinaryOp::Or => 2,
+            BinaryOp::And => 3,
+            BinaryOp::BitOr => 4,
+            BinaryOp::BitXor => 5,
+            BinaryOp::BitAnd => 6,
+            BinaryOp::Eq | BinaryOp::Neq | BinaryOp::StrictEq | BinaryOp::StrictNeq => 7,
+            BinaryOp::Lt | BinaryOp::Lte | BinaryOp::Gt | BinaryOp::Gte => 8,
+            BinaryOp::Shl | BinaryOp::Shr | BinaryOp::UShr => 9,
+            BinaryOp::Add | BinaryOp::Sub => 10,
+            BinaryOp::Mul | BinaryOp::Div | BinaryOp::Rem => 11,
+        }
+    }
+}
+
+const TERNARY_PRECEDENCE: u8 = 1;
+const UNARY_PRECEDENCE: u8 = 12;
+const MEMBER_PRECEDENCE: u8 = 13;
+
+/// A Vega expression
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    /// `null`
+    Null,
+    /// `true` or `false`
+    Bool(bool),
+    /// A number literal
+    Number(f64),
+    /// A string literal
+    String(String),
+    /// A regular expression literal, `/pattern/flags`
+    Regex(String, String),
+    /// An array literal
+    Array(Vec<Expr>),
+    /// An object literal
+    Object(Vec<(String, Expr)>),
+    /// A variable or constant, such as `datum`, `event` or `PI`
+    Variable(String),
+    /// A property access, `object.property`
+    Member(Box<Expr>, String),
+    /// A computed property access, `object[index]`
+    Index(Box<Expr>, Box<Expr>),
+    /// A call to a function of the expression language
+    Call(String, Vec<Expr>),
+    /// A unary operation
+    Unary(UnaryOp, Box<Expr>),
+    /// A binary operation
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    /// `test ? then : otherwise`
+    Ternary(Box<Expr>, Box<Expr>, Box<Expr>),
+}
+
+/// A field of the current data object, rendered as `datum.field` or `datum['field']`
+pub fn datum(field: &str) -> Expr {
+    Expr::Variable("datum".to_string()).get(field)
+}
+
+/// A variable or constant, such as `event` or `PI`
+pub fn var(name: &str) -> Expr {
+    Expr::Variable(name.to_string())
+}
+
+/// A literal value
+pub fn lit(value: impl Into<Expr>) -> Expr {
+    value.into()
+}
+
+/// `test ? then : otherwise`
+pub fn ternary(test: impl Into<Expr>, then: impl Into<Expr>, otherwise: impl Into<Expr>) -> Expr {
+    Expr::Ternary(
+        Box::new(test.into()),
+        Box::new(then.into()),
+        Box::new(otherwise.into()),
+    )
+}
+
+/// A call to a function of the expression library, by name
+pub fn call(name: &str, args: Vec<Expr>) -> Expr {
+    Expr::Call(name.to_string(), args)
+}
+
+pub(crate) fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' || c == '$' => {}
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$')
+        && !parser::KEYWORDS.contains(&name)
+}
+
+impl Expr {
+    /// Access a property of this expression, rendered as `.property` when possible
+    pub fn get(self, property: &str) -> Expr {
+        if is_identifier(property) {
+            Expr::Member(Box::new(self), property.to_string())
+        } else {
+            Expr::Index(Box::new(self), Box::new(Expr::String(property.to_string())))
+        }
+    }
+
+    /// Access an element of this expression, `self[index]`
+    pub fn index(self, index: impl Into<Expr>) -> Expr {
+        Expr::Index(Box::new(self), Box::new(index.into()))
+    }
+
+    fn binary(self, op: BinaryOp, other: impl Into<Expr>) -> Expr {
+        Expr::Binary(op, Box::new(self), Box::new(other.into()))
+    }
+
+    /// `self == other`
+    pub fn eq(self, other: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::Eq, other)
+    }
+
+    /// `self != other`
+    pub fn neq(self, other: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::Neq, other)
+    }
+
+    /// `self === other`
+    pub fn strict_eq(self, other: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::StrictEq, other)
+    }
+
+    /// `self !== other`
+    pub fn strict_neq(self, other: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::StrictNeq, other)
+    }
+
+    /// `self < other`
+    pub fn lt(self, other: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::Lt, other)
+    }
+
+    /// `self <= other`
+    pub fn lte(self, other: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::Lte, other)
+    }
+
+    /// `self > other`
+    pub fn gt(self, other: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::Gt, other)
+    }
+
+    /// `self >= other`
+    pub fn gte(self, other: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::Gte, other)
+    }
+
+    /// `self && other`
+    pub fn and(self, other: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::And, other)
+    }
+
+    /// `self || other`
+    pub fn or(self, other: impl Into<Expr>) -> Expr {
+        self.binary(BinaryOp::Or, other)
+    }
+
+    /// `self ? then : otherwise`
+    pub fn then(self, then: impl Into<Expr>, otherwise: impl Into<Expr>) -> Expr {
+        ternary(self, then, otherwise)
+    }
+
+    fn precedence(&self) -> u8 {
+        match self {
+            Expr::Ternary(..) => TERNARY_PRECEDENCE,
+            Expr::Binary(op, ..) => op.precedence(),
+            Expr::Unary(..) => UNARY_PRECEDENCE,
+            // infinities are rendered as a division
+            Expr::Number(n) if n.is_infinite() => BinaryOp::Div.precedence(),
+            Expr::Number(n) if n.is_sign_negative() => UNARY_PRECEDENCE,
+            _ => MEMBER_PRECEDENCE,
+        }
+    }
+
+    fn fmt_operand(&self, f: &mut fmt::Formatter, parenthesize: bool) -> fmt::Result {
+        if parenthesize {
+            write!(f, "({})", self)
+        } else {
+            write!(f, "{}", self)
+        }
+    }
+}
+
+fn fmt_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+    f.write_str("'")?;
+    for c in s.chars() {
+        match c {
+            '\'' => f.write_str("\\'")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => write!(f, "{}", c)?,
+        }
+    }
+    f.write_str("'")
+}
+
+fn fmt_list(f: &mut fmt::Formatter, items: &[Expr]) -> fmt::Result {
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            f.write_str(", ")?;
+        }
+        write!(f, "{}", item)?;
+    }
+    Ok(())
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Null => f.write_str("null"),
+            Expr::Bool(b) => write!(f, "{}", b),
+            Expr::Number(n) if n.is_nan() => f.write_str("NaN"),
+            Expr::Number(n) if n.is_infinite() && *n > 0.0 => f.write_str("1 / 0"),
+            Expr::Number(n) if n.is_infinite() => f.write_str("-1 / 0"),
+            Expr::Number(n) => write!(f, "{}", n),
+            Expr::String(s) => fmt_string(f, s),
+            Expr::Regex(pattern, flags) => write!(f, "/{}/{}", pattern, flags),
+            Expr::Array(items) => {
+                f.write_str("[")?;
+                fmt_list(f, items)?;
+                f.write_str("]")
+            }
+            Expr::Object(entries) => {
+                f.write_str("{")?;
+                for (i, (key, value)) in entries.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    if is_identifier(key) {
+                        f.write_str(key)?;
+                    } else {
+                        fmt_string(f, key)?;
+                    }
+                    write!(f, ": {}", value)?;
+                }
+                f.write_str("}")
+            }
+            Expr::Variable(name) => f.write_str(name),
+            Expr::Member(object, property) => {
+                object.fmt_operand(f, object.precedence() < MEMBER_PRECEDENCE)?;
+                write!(f, ".{}", property)
+            }
+            Expr::Index(object, index) => {
+                object.fmt_operand(f, object.precedence() < MEMBER_PRECEDENCE)?;
+                write!(f, "[{}]", index)
+            }
+            Expr::Call(name, args) => {
+                write!(f, "{}(", name)?;
+                fmt_list(f, args)?;
+                f.write_str(")")
+            }
+            Expr::Unary(op, operand) => {
+                let symbol = match op {
+                    UnaryOp::Not => "!",
+                    UnaryOp::Neg => "-",
+                    UnaryOp::Plus => "+",
+                    UnaryOp::BitNot => "~",
+                };
+                f.write_str(symbol)?;
+                // avoid printing `--x` or `++x`, which are not negations
+                let ambiguous = match (op, &**operand) {
+                    (UnaryOp::Neg, Expr::Unary(UnaryOp::Neg, _))
+                    | (UnaryOp::Plus, Expr::Unary(UnaryOp::Plus, _)) => true,
+                    (UnaryOp::Neg, Expr::Number(n)) => n.is_sign_negative(),
+                    _ => false,
+                };
+                operand.fmt_operand(f, ambiguous || operand.precedence() < UNARY_PRECEDENCE)
+            }
+            Expr::Binary(op, left, right) => {
+                left.fmt_operand(f, left.precedence() < op.precedence())?;
+                write!(f, " {} ", op.symbol())?;
+                right.fmt_operand(f, right.precedence() <= op.precedence())
+            }
+            Expr::Ternary(test, then, otherwise) => {
+                test.fmt_operand(f, test.precedence() <= TERNARY_PRECEDENCE)?;
+                write!(f, " ? {} : {}", then, otherwise)
+            }
+        }
+    }
+}
+
+impl FromStr for Expr {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse(s)
+    }
+}
+
+impl From<bool> for Expr {
+    fn from(v: bool) -> Self {
+        Expr::Bool(v)
+    }
+}
+
+impl From<f64> for Expr {
+    fn from(v: f64) -> Self {
+        Expr::Number(v)
+    }
+}
+
+impl From<i32> for Expr {
+    fn from(v: i32) -> Self {
+        Expr::Number(v.into())
+    }
+}
+
+impl From<&str> for Expr {
+    fn from(v: &str) -> Self {
+        Expr::String(v.to_string())
+    }
+}
+
+impl From<String> for Expr {
+    fn from(v: String) -> Self {
+        Expr::String(v)
+    }
+}
+
+impl<T: Into<Expr>> From<Vec<T>> for Expr {
+    fn from(v: Vec<T>) -> Self {
+        Expr::Array(v.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Expr>> From<Option<T>> for Expr {
+    fn from(v: Option<T>) -> Self {
+        v.map(Into::into).unwrap_or(Expr::Null)
+    }
+}
+
+macro_rules! binary_operator {
+    ( $( $trait:ident, $method:ident => $op:expr ),* $(,)? ) => {
+        $(
+            impl<T: Into<Expr>> ops::$trait<T> for Expr {
+                type Output = Expr;
+
+                fn $method(self, other: T) -> Expr {
+                    self.binary($op, other)
+                }
+            }
+        )*
+    };
+}
+
+binary_operator! {
+    Add, add => BinaryOp::Add,
+    Sub, sub => BinaryOp::Sub,
+    Mul, mul => BinaryOp::Mul,
+    Div, div => BinaryOp::Div,
+    Rem, rem => BinaryOp::Rem,
+}
+
+impl ops::Neg for Expr {
+    type Output = Expr;
+
+    fn neg(self) -> Expr {
+        Expr::Unary(UnaryOp::Neg, Box::new(self))
+    }
+}
+
+impl ops::Not for Expr {
+    type Output = Expr;
+
+    fn not(self) -> Expr {
+        Expr::Unary(UnaryOp::Not, Box::new(self))
+    }
+}
+
+impl From<Expr> for String {
+    fn from(v: Expr) -> Self {
+        v.to_string()
+    }
+}
+
+impl From<Expr> for PurpleLogicalOperandPredicate {
+    fn from(v: Expr) -> Self {
+        PurpleLogicalOperandPredicate::String(v.to_string())
+    }
+}
+
+impl From<Expr> for Box<PurpleLogicalOperandPredicate> {
+    fn from(v: Expr) -> Self {
+        Box::new(v.into())
+    }
+}
+
+impl From<Expr> for LogicalOperandPredicateElement {
+    fn from(v: Expr) -> Self {
+        LogicalOperandPredicateElement::String(v.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        assert_eq!(datum("price").to_string(), "datum.price");
+        assert_eq!(datum("my field").to_string(), "datum['my field']");
+        assert_eq!(datum("it's").to_string(), "datum['it\\'s']");
+        assert_eq!(
+            ((datum("a") + 1) * datum("b")).to_string(),
+            "(datum.a + 1) * datum.b"
+        );
+        assert_eq!(
+            (datum("a") - (datum("b") - 1)).to_string(),
+            "datum.a - (datum.b - 1)"
+        );
+        assert_eq!((-lit(-2.5)).to_string(), "-(-2.5)");
+        assert_eq!((!datum("a").gt(2)).to_string(), "!(datum.a > 2)");
+        assert_eq!(
+            datum("a").gt(2).and(datum("b").lte(3.5)).to_string(),
+            "datum.a > 2 && datum.b <= 3.5"
+        );
+        assert_eq!(
+            ternary(ternary(datum("a"), 1, 2), 3, 4).to_string(),
+            "(datum.a ? 1 : 2) ? 3 : 4"
+        );
+        assert_eq!(
+            year(datum("date")).strict_eq(2000).to_string(),
+            "year(datum.date) === 2000"
+        );
+    }
+
+    #[test]
+    fn test_into_schema() {
+        let transform = TransformBuilder::default()
+            .calculate(datum("sex").eq(2).then("Female", "Male"))
+            .transform_as("gender")
+            .build()
+            .unwrap();
+        assert_eq!(
+            transform.calculate.unwrap(),
+            "datum.sex == 2 ? 'Female' : 'Male'"
+        );
+
+        let transform = TransformBuilder::default()
+            .filter(datum("symbol").strict_eq("GOOG"))
+            .build()
+            .unwrap();
+        match transform.filter {
+            Some(PurpleLogicalOperandPredicate::String(filter)) => {
+                assert_eq!(filter, "datum.symbol === 'GOOG'")
+            }
+            _ => panic!("expected a string filter"),
+        }
+    }
+}
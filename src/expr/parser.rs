@@ -0,0 +1,581 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use super::functions::lookup;
+use super::{BinaryOp, Expr, UnaryOp};
+use std::fmt;
+
+pub(crate) const KEYWORDS: &[&str] = &["true", "false", "null"];
+
+const PUNCTUATORS: &[&str] = &[
+    ">>>", "===", "!==", "==", "!=", "<=", ">=", "&&", "||", "<<", ">>", "+", "-", "*", "/", "%",
+    "<", ">", "!", "~", "&", "|", "^", "?", ":", ".", ",", "(", ")", "[", "]", "{", "}",
+];
+
+/// Error returned when an expression string is not a valid Vega expression
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// What went wrong
+    pub message: String,
+    /// Byte offset in the expression string where the error was detected
+    pub position: usize,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    String(String),
+    Regex(String, String),
+    Identifier(String),
+    Punctuator(&'static str),
+    End,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "number {}", n),
+            Token::String(s) => write!(f, "string {:?}", s),
+            Token::Regex(pattern, flags) => write!(f, "regular expression /{}/{}", pattern, flags),
+            Token::Identifier(name) => write!(f, "identifier `{}`", name),
+            Token::Punctuator(p) => write!(f, "`{}`", p),
+            Token::End => f.write_str("end of expression"),
+        }
+    }
+}
+
+fn error<T>(message: impl Into<String>, position: usize) -> Result<T, ParseError> {
+    Err(ParseError {
+        message: message.into(),
+        position,
+    })
+}
+
+fn tokenize(input: &str) -> Result<Vec<(Token, usize)>, ParseError> {
+    let chars = input.char_indices().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        let (start, c) = chars[i];
+        if c.is_whitespace() {
+            i += 1;
+        } else if c.is_ascii_digit()
+            || (c == '.' && chars.get(i + 1).is_some_and(|(_, c)| c.is_ascii_digit()))
+        {
+            let mut end = i;
+            if c == '0' && matches!(chars.get(i + 1), Some((_, 'x')) | Some((_, 'X'))) {
+                end += 2;
+                while end < chars.len() && chars[end].1.is_ascii_hexdigit() {
+                    end += 1;
+                }
+                let digits = chars[i + 2..end].iter().map(|(_, c)| c).collect::<String>();
+                match u64::from_str_radix(&digits, 16) {
+                    Ok(n) => tokens.push((Token::Number(n as f64), start)),
+                    Err(_) => return error("invalid hexadecimal number", start),
+                }
+            } else {
+                while end < chars.len() && chars[end].1.is_ascii_digit() {
+                    end += 1;
+                }
+                if end < chars.len() && chars[end].1 == '.' {
+                    end += 1;
+                    while end < chars.len() && chars[end].1.is_ascii_digit() {
+                        end += 1;
+                    }
+                }
+                if end < chars.len() && (chars[end].1 == 'e' || chars[end].1 == 'E') {
+                    end += 1;
+                    if end < chars.len() && (chars[end].1 == '+' || chars[end].1 == '-') {
+                        end += 1;
+                    }
+                    while end < chars.len() && chars[end].1.is_ascii_digit() {
+                        end += 1;
+                    }
+                }
+                let literal = chars[i..end].iter().map(|(_, c)| c).collect::<String>();
+                match literal.parse() {
+                    Ok(n) => tokens.push((Token::Number(n), start)),
+                    Err(_) => return error(format!("invalid number `{}`", literal), start),
+                }
+            }
+            i = end;
+        } else if c == '\'' || c == '"' {
+            let mut value = String::new();
+            i += 1;
+            loop {
+                match chars.get(i) {
+                    None => return error("unterminated string", start),
+                    Some((_, q)) if *q == c => break,
+                    Some((position, '\\')) => {
+                        let escaped = match chars.get(i + 1) {
+                            None => return error("unterminated string", start),
+                            Some((_, e)) => *e,
+                        };
+                        i += 2;
+                        match escaped {
+                            'n' => value.push('\n'),
+                            'r' => value.push('\r'),
+                            't' => value.push('\t'),
+                            'b' => value.push('\u{8}'),
+                            'f' => value.push('\u{c}'),
+                            'v' => value.push('\u{b}'),
+                            '0' => value.push('\0'),
+                            'u' | 'x' => {
+                                let len = if escaped == 'u' { 4 } else { 2 };
+                                let digits = chars
+                                    .iter()
+                                    .skip(i)
+                                    .take(len)
+                                    .map(|(_, c)| c)
+                                    .collect::<String>();
+                                match u32::from_str_radix(&digits, 16)
+                                    .ok()
+                                    .filter(|_| digits.len() == len)
+                                    .and_then(std::char::from_u32)
+                                {
+                                    Some(c) => value.push(c),
+                                    None => return error("invalid escape sequence", *position),
+                                }
+                                i += len;
+                            }
+                            other => value.push(other),
+                        }
+                        continue;
+                    }
+                    Some((_, other)) => value.push(*other),
+                }
+                i += 1;
+            }
+            tokens.push((Token::String(value), start));
+            i += 1;
+        } else if c == '/' && regex_allowed(tokens.last()) {
+            let (pattern, flags, end) = regex(&chars, i)?;
+            tokens.push((Token::Regex(pattern, flags), start));
+            i = end;
+        } else if c.is_ascii_alphabetic() || c == '_' || c == '$' {
+            let mut end = i;
+            while end < chars.len()
+                && (chars[end].1.is_ascii_alphanumeric()
+                    || chars[end].1 == '_'
+                    || chars[end].1 == '$')
+            {
+                end += 1;
+            }
+            let name = chars[i..end].iter().map(|(_, c)| c).collect::<String>();
+            tokens.push((Token::Identifier(name), start));
+            i = end;
+        } else {
+            let rest = &input[start..];
+            match PUNCTUATORS.iter().find(|p| rest.starts_with(**p)) {
+                Some(p) => {
+                    tokens.push((Token::Punctuator(p), start));
+                    i += p.len();
+                }
+                None => return error(format!("unexpected character `{}`", c), start),
+            }
+        }
+    }
+    tokens.push((Token::End, input.len()));
+    Ok(tokens)
+}
+
+/// Whether a `/` starts a regular expression rather than a division, which is the case when it
+/// can't follow an operand
+fn regex_allowed(previous: Option<&(Token, usize)>) -> bool {
+    match previous {
+        None => true,
+        Some((Token::Punctuator(p), _)) => !matches!(*p, ")" | "]" | "}"),
+        Some(_) => false,
+    }
+}
+
+/// Read a regular expression literal starting at the `/` at index `i`, returning its pattern,
+/// its flags and the index following it
+fn regex(chars: &[(usize, char)], i: usize) -> Result<(String, String, usize), ParseError> {
+    let start = chars[i].0;
+    let mut pattern = String::new();
+    let mut in_class = false;
+    let mut end = i + 1;
+    loop {
+        match chars.get(end).map(|(_, c)| *c) {
+            None | Some('\n') | Some('\r') => {
+                return error("unterminated regular expression", start)
+            }
+            Some('/') if !in_class => break,
+            Some('\\') => {
+                pattern.push('\\');
+                end += 1;
+                match chars.get(end) {
+                    Some((_, c)) if *c != '\n' && *c != '\r' => pattern.push(*c),
+                    _ => return error("unterminated regular expression", start),
+                }
+            }
+            Some(c) => {
+                if c == '[' {
+                    in_class = true;
+                } else if c == ']' {
+                    in_class = false;
+                }
+                pattern.push(c);
+            }
+        }
+        end += 1;
+    }
+    end += 1;
+    let mut flags = String::new();
+    while let Some((position, c)) = chars.get(end).filter(|(_, c)| c.is_ascii_alphanumeric()) {
+        if !"gimsuy".contains(*c) || flags.contains(*c) {
+            return error(
+                format!("invalid regular expression flag `{}`", c),
+                *position,
+            );
+        }
+        flags.push(*c);
+        end += 1;
+    }
+    Ok((pattern, flags, end))
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    current: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.current].0
+    }
+
+    fn peek_at(&self, offset: usize) -> &Token {
+        let index = (self.current + offset).min(self.tokens.len() - 1);
+        &self.tokens[index].0
+    }
+
+    fn position(&self) -> usize {
+        self.tokens[self.current].1
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.current].0.clone();
+        if token != Token::End {
+            self.current += 1;
+        }
+        token
+    }
+
+    fn eat(&mut self, punctuator: &str) -> bool {
+        if *self.peek() == Token::Punctuator(punctuator_ref(punctuator)) {
+            self.current += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, punctuator: &str) -> Result<(), ParseError> {
+        if self.eat(punctuator) {
+            Ok(())
+        } else {
+            self.unexpected(&format!("`{}`", punctuator))
+        }
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T, ParseError> {
+        error(
+            format!("expected {}, found {}", expected, self.peek()),
+            self.position(),
+        )
+    }
+
+    fn parse_ternary(&mut self) -> Result<Expr, ParseError> {
+        let test = self.parse_binary(BinaryOp::Or.precedence())?;
+        if self.eat("?") {
+            let then = self.parse_ternary()?;
+            self.expect(":")?;
+            let otherwise = self.parse_ternary()?;
+            Ok(Expr::Ternary(
+                Box::new(test),
+                Box::new(then),
+                Box::new(otherwise),
+            ))
+        } else {
+            Ok(test)
+        }
+    }
+
+    fn binary_operator(&self) -> Option<BinaryOp> {
+        match self.peek() {
+            Token::Punctuator(p) => BinaryOp::ALL.iter().find(|op| op.symbol() == *p).copied(),
+            _ => None,
+        }
+    }
+
+    fn parse_binary(&mut self, min_precedence: u8) -> Result<Expr, ParseError> {
+        let mut left = self.parse_unary()?;
+        while let Some(op) = self.binary_operator() {
+            if op.precedence() < min_precedence {
+                break;
+            }
+            self.next();
+            let right = self.parse_binary(op.precedence() + 1)?;
+            left = Expr::Binary(op, Box::new(left), Box::new(right));
+        }
+        Ok(left)
+    }
+
+    fn parse_unary(&mut self) -> Result<Expr, ParseError> {
+        let op = match self.peek() {
+            Token::Punctuator("!") => UnaryOp::Not,
+            Token::Punctuator("-") => UnaryOp::Neg,
+            Token::Punctuator("+") => UnaryOp::Plus,
+            Token::Punctuator("~") => UnaryOp::BitNot,
+            _ => return self.parse_postfix(),
+        };
+        self.next();
+        // a negative number literal is a number, not a negation
+        if let (UnaryOp::Neg, Token::Number(n)) = (op, self.peek().clone()) {
+            if !matches!(
+                self.peek_at(1),
+                Token::Punctuator(".") | Token::Punctuator("[") | Token::Punctuator("(")
+            ) {
+                self.next();
+                return Ok(Expr::Number(-n));
+            }
+        }
+        Ok(Expr::Unary(op, Box::new(self.parse_unary()?)))
+    }
+
+    fn parse_postfix(&mut self) -> Result<Expr, ParseError> {
+        let mut expr = self.parse_primary()?;
+        loop {
+            if self.eat(".") {
+                match self.peek().clone() {
+                    Token::Identifier(name) => {
+                        self.next();
+                        expr = Expr::Member(Box::new(expr), name);
+                    }
+                    _ => return self.unexpected("a property name"),
+                }
+            } else if self.eat("[") {
+                let index = self.parse_ternary()?;
+                self.expect("]")?;
+                expr = Expr::Index(Box::new(expr), Box::new(index));
+            } else if *self.peek() == Token::Punctuator("(") {
+                return error(
+                    "only functions of the expression library can be called",
+                    self.position(),
+                );
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    fn parse_list(&mut self, close: &str) -> Result<Vec<Expr>, ParseError> {
+        let mut items = vec![];
+        if self.eat(close) {
+            return Ok(items);
+        }
+        loop {
+            items.push(self.parse_ternary()?);
+            if self.eat(close) {
+                return Ok(items);
+            }
+            self.expect(",")?;
+        }
+    }
+
+    fn parse_call(&mut self, name: String, position: usize) -> Result<Expr, ParseError> {
+        let args = self.parse_list(")")?;
+        match lookup(&name) {
+            None => error(format!("unknown function `{}`", name), position),
+            Some((min, max)) if args.len() < min || max.is_some_and(|max| args.len() > max) => {
+                let expected = match max {
+                    Some(max) if max == min => format!("{}", min),
+                    Some(max) => format!("{} to {}", min, max),
+                    None => format!("at least {}", min),
+                };
+                error(
+                    format!(
+                        "function `{}` expects {} arguments, found {}",
+                        name,
+                        expected,
+                        args.len()
+                    ),
+                    position,
+                )
+            }
+            Some(_) => Ok(Expr::Call(name, args)),
+        }
+    }
+
+    fn parse_primary(&mut self) -> Result<Expr, ParseError> {
+        let position = self.position();
+        match self.next() {
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::String(s) => Ok(Expr::String(s)),
+            Token::Regex(pattern, flags) => Ok(Expr::Regex(pattern, flags)),
+            Token::Identifier(name) => match name.as_str() {
+                "true" => Ok(Expr::Bool(true)),
+                "false" => Ok(Expr::Bool(false)),
+                "null" => Ok(Expr::Null),
+                _ if self.eat("(") => self.parse_call(name, position),
+                _ => Ok(Expr::Variable(name)),
+            },
+            Token::Punctuator("(") => {
+                let expr = self.parse_ternary()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Punctuator("[") => Ok(Expr::Array(self.parse_list("]")?)),
+            Token::Punctuator("{") => {
+                let mut entries = vec![];
+                if self.eat("}") {
+                    return Ok(Expr::Object(entries));
+                }
+                loop {
+                    let key = match self.peek().clone() {
+                        Token::Identifier(name) => name,
+                        Token::String(s) => s,
+                        Token::Number(n) => n.to_string(),
+                        _ => return self.unexpected("a property name"),
+                    };
+                    self.next();
+                    self.expect(":")?;
+                    entries.push((key, self.parse_ternary()?));
+                    if self.eat("}") {
+                        return Ok(Expr::Object(entries));
+                    }
+                    self.expect(",")?;
+                }
+            }
+            token => error(format!("expected an expression, found {}", token), position),
+        }
+    }
+}
+
+fn punctuator_ref(punctuator: &str) -> &'static str {
+    PUNCTUATORS
+        .iter()
+        .find(|p| **p == punctuator)
+        .expect("unknown punctuator")
+}
+
+/// Parse a Vega expression string, checking that it only calls functions of the expression
+/// library with a valid number of arguments
+pub fn parse(input: &str) -> Result<Expr, ParseError> {
+    let mut parser = Parser {
+        tokens: tokenize(input)?,
+        current: 0,
+    };
+    let expr = parser.parse_ternary()?;
+    if *parser.peek() != Token::End {
+        return parser.unexpected("end of expression");
+    }
+    Ok(expr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            parse("datum.sex == 2 ? 'Female' : 'Male'").unwrap(),
+            ternary(datum("sex").eq(2), "Female", "Male")
+        );
+        assert_eq!(
+            parse("datum[\"my field\"] * -2").unwrap(),
+            datum("my field") * -2
+        );
+        assert_eq!(
+            parse("datum.IMDB_Rating - datum.AverageRating").unwrap(),
+            datum("IMDB_Rating") - datum("AverageRating")
+        );
+        assert_eq!(
+            parse("year(datum.Year) >= 1970 && !isValid(datum.x)").unwrap(),
+            year(datum("Year")).gte(1970).and(!is_valid(datum("x")))
+        );
+        assert_eq!(parse("(datum.a) / 2").unwrap(), datum("a") / 2);
+        assert_eq!(
+            parse("test(/^a/, datum.s)").unwrap(),
+            test(Expr::Regex("^a".to_string(), String::new()), datum("s"))
+        );
+        assert_eq!(
+            parse("{a: [1, 2], 'b c': null}").unwrap(),
+            Expr::Object(vec![
+                ("a".to_string(), lit(vec![1, 2])),
+                ("b c".to_string(), Expr::Null)
+            ])
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        for input in &[
+            "datum.a - (datum.b - 1)",
+            "(datum.a + 1) * datum.b",
+            "-(-2.5)",
+            "datum.rank < 10 ? datum.Director : 'All Others'",
+            "sin(datum.x)",
+            "datum['count_*'] > 100",
+            "format(datum.value, '.2f') + ' %'",
+            "(datum.a ? 1 : 2) ? 3 : 4",
+            "datum.a || datum.b && datum.c",
+            "(datum.a || datum.b) && datum.c",
+            "test(/^a[/\\]]+/i, datum.s)",
+            "datum.a / 2 / datum.b",
+            "[/x/g, datum.a / 2]",
+        ] {
+            assert_eq!(&parse(input).unwrap().to_string(), input);
+        }
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert_eq!(
+            parse("foo(datum.x)").unwrap_err(),
+            ParseError {
+                message: "unknown function `foo`".to_string(),
+                position: 0
+            }
+        );
+        assert_eq!(
+            parse("pow(2)").unwrap_err().message,
+            "function `pow` expects 2 arguments, found 1"
+        );
+        assert_eq!(parse("datum.a +").unwrap_err().position, 9);
+        assert_eq!(parse("'abc").unwrap_err().message, "unterminated string");
+        assert_eq!(
+            parse("datum.a datum.b").unwrap_err().message,
+            "expected end of expression, found identifier `datum`"
+        );
+        assert!(parse("datum.f(1)").is_err());
+        assert_eq!(
+            parse("test(/a, datum.s)").unwrap_err().message,
+            "unterminated regular expression"
+        );
+        assert_eq!(
+            parse("test(/a/x, datum.s)").unwrap_err().message,
+            "invalid regular expression flag `x`"
+        );
+    }
+}
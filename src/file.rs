@@ -0,0 +1,109 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::error::Error;
+use crate::schema::*;
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+impl Vegalite {
+    /// Read a graph from a reader over its json specification
+    pub fn from_reader<R: Read>(reader: R) -> Result<Self, Error> {
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// Read a graph from a file containing its json specification
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Write the json for a graph, with the [default theme](themes/fn.set_default.html) applied
+    pub fn to_writer<W: Write>(&self, writer: W) -> Result<(), Error> {
+        Ok(serde_json::to_writer(writer, &*self.themed())?)
+    }
+
+    /// Write the json for a graph, indented
+    pub fn to_writer_pretty<W: Write>(&self, writer: W) -> Result<(), Error> {
+        Ok(serde_json::to_writer_pretty(writer, &*self.themed())?)
+    }
+
+    /// Save a graph to a file. The format is chosen from the extension of the path: `.json` (or
+    /// `.vl`) for the json specification, `.html` (or `.htm`) for a standalone html page.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let path = path.as_ref();
+        let extension = path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map(|extension| extension.to_lowercase());
+        match extension.as_deref() {
+            Some("json") | Some("vl") => {
+                let mut writer = BufWriter::new(File::create(path)?);
+                self.to_writer_pretty(&mut writer)?;
+                Ok(writer.flush()?)
+            }
+            Some("html") | Some("htm") => Ok(std::fs::write(path, self.to_html()?)?),
+            _ => Err(Error::UnsupportedExtension(path.to_path_buf())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart() -> Vegalite {
+        VegaliteBuilder::default()
+            .description("a simple chart")
+            .mark(Mark::Point)
+            .build()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_writer_reader() {
+        let mut buffer = vec![];
+        chart().to_writer_pretty(&mut buffer).unwrap();
+        let description = crate::with_big_stack(move || {
+            Vegalite::from_reader(buffer.as_slice())
+                .unwrap()
+                .description
+        });
+        assert_eq!(description, Some(String::from("a simple chart")));
+    }
+
+    #[test]
+    fn test_save_by_extension() {
+        let dir = std::env::temp_dir().join(format!("vega_lite_3-save-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let json = dir.join("chart.vl.json");
+        chart().save(&json).unwrap();
+        let read = json.clone();
+        assert_eq!(
+            crate::with_big_stack(move || Vegalite::from_path(read).unwrap().description),
+            chart().description
+        );
+
+        let html = dir.join("chart.html");
+        chart().save(&html).unwrap();
+        let page = std::fs::read_to_string(&html).unwrap();
+        assert!(page.contains("vegaEmbed"));
+        assert!(page.contains("a simple chart"));
+
+        assert!(matches!(
+            chart().save(dir.join("chart.png")),
+            Err(Error::UnsupportedExtension(_))
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,736 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Typed [d3-format](https://github.com/d3/d3-format) number formats and
+//! [d3-time-format](https://github.com/d3/d3-time-format) time formats, with parsers to
+//! validate existing format strings.
+//!
+//! ```
+//! use vega_lite_3::format::{Directive, NumberFormat, TimeFormat};
+//! use vega_lite_3::*;
+//!
+//! let axis = AxisBuilder::default()
+//!     .typed_format(NumberFormat::percent(1))
+//!     .build()
+//!     .unwrap();
+//! let date = TimeFormat::new()
+//!     .then(Directive::Year)
+//!     .text("-")
+//!     .then(Directive::MonthNumber);
+//! assert_eq!(date.to_string(), "%Y-%m");
+//! assert!("%Y-%i".parse::<TimeFormat>().is_err());
+//! assert_eq!("$,.2f".parse::<NumberFormat>().unwrap(), NumberFormat::currency(2));
+//! ```
+
+use crate::error::Error;
+use crate::schema::*;
+use std::fmt;
+use std::str::FromStr;
+
+/// The alignment of a number padded to its width
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Alignment {
+    /// `<`
+    Left,
+    /// `>`, the default
+    Right,
+    /// `^`
+    Center,
+    /// `=`, the sign and symbol on the left, the number on the right
+    Sign,
+}
+
+/// How the sign of a number is shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sign {
+    /// `-`, only for negative numbers, the default
+    Negative,
+    /// `+`, for all numbers
+    Always,
+    /// `(`, parentheses around negative numbers
+    Parentheses,
+    /// ` `, a space for positive numbers
+    Space,
+}
+
+/// A symbol added to a number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Symbol {
+    /// `$`, the currency symbol of the locale
+    Currency,
+    /// `#`, the `0b`, `0o` or `0x` prefix of binary, octal or hexadecimal numbers
+    Prefix,
+}
+
+/// How a number is written
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NumberType {
+    /// `e`, exponent notation
+    Exponent,
+    /// `f`, fixed point notation
+    Fixed,
+    /// `g`, decimal or exponent notation, rounded to significant digits
+    General,
+    /// `r`, decimal notation, rounded to significant digits
+    Rounded,
+    /// `s`, decimal notation with an SI prefix, rounded to significant digits
+    Si,
+    /// `%`, multiplied by 100, in fixed point notation with a percent sign
+    Percent,
+    /// `p`, multiplied by 100, rounded to significant digits, with a percent sign
+    RoundedPercent,
+    /// `b`, binary notation, rounded to an integer
+    Binary,
+    /// `o`, octal notation, rounded to an integer
+    Octal,
+    /// `d`, decimal notation, rounded to an integer
+    Integer,
+    /// `x`, lower-case hexadecimal notation, rounded to an integer
+    Hexadecimal,
+    /// `X`, upper-case hexadecimal notation, rounded to an integer
+    UpperHexadecimal,
+    /// `c`, the character with this code
+    Character,
+    /// `n`, shorthand for `,g`
+    Grouped,
+}
+
+/// Map the variants of an enum of format options to their characters
+macro_rules! format_chars {
+    ($($name:ident { $($variant:ident => $char:expr,)* })*) => {
+        $(
+            impl $name {
+                fn char(self) -> char {
+                    match self {
+                        $($name::$variant => $char,)*
+                    }
+                }
+
+                fn from_char(c: char) -> Option<Self> {
+                    match c {
+                        $($char => Some($name::$variant),)*
+                        _ => None,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+format_chars! {
+    Alignment {
+        Left => '<',
+        Right => '>',
+        Center => '^',
+        Sign => '=',
+    }
+    Sign {
+        Negative => '-',
+        Always => '+',
+        Parentheses => '(',
+        Space => ' ',
+    }
+    Symbol {
+        Currency => '$',
+        Prefix => '#',
+    }
+    NumberType {
+        Exponent => 'e',
+        Fixed => 'f',
+        General => 'g',
+        Rounded => 'r',
+        Si => 's',
+        Percent => '%',
+        RoundedPercent => 'p',
+        Binary => 'b',
+        Octal => 'o',
+        Integer => 'd',
+        Hexadecimal => 'x',
+        UpperHexadecimal => 'X',
+        Character => 'c',
+        Grouped => 'n',
+    }
+}
+
+/// A d3-format number format, `[[fill]align][sign][symbol][0][width][,][.precision][~][type]`
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct NumberFormat {
+    fill: Option<char>,
+    align: Option<Alignment>,
+    sign: Option<Sign>,
+    symbol: Option<Symbol>,
+    zero: bool,
+    width: Option<usize>,
+    grouping: bool,
+    precision: Option<usize>,
+    trim: bool,
+    number_type: Option<NumberType>,
+}
+
+impl NumberFormat {
+    /// The default format, `""`, like `~g` with a precision of 12
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Fixed point notation with `precision` digits after the decimal point, `.{precision}f`
+    pub fn fixed(precision: usize) -> Self {
+        Self::new()
+            .precision(precision)
+            .number_type(NumberType::Fixed)
+    }
+
+    /// Percentage with `precision` digits after the decimal point, `.{precision}%`
+    pub fn percent(precision: usize) -> Self {
+        Self::new()
+            .precision(precision)
+            .number_type(NumberType::Percent)
+    }
+
+    /// SI prefix notation with `precision` significant digits, trailing zeros being removed,
+    /// `.{precision}~s`
+    pub fn si(precision: usize) -> Self {
+        Self::new()
+            .precision(precision)
+            .trim()
+            .number_type(NumberType::Si)
+    }
+
+    /// Amount of money with `precision` digits after the decimal point and thousands
+    /// separators, `$,.{precision}f`
+    pub fn currency(precision: usize) -> Self {
+        Self::fixed(precision).symbol(Symbol::Currency).grouping()
+    }
+
+    /// Rounded integer with thousands separators, `,d`
+    pub fn integer() -> Self {
+        Self::new().grouping().number_type(NumberType::Integer)
+    }
+
+    /// The character padding the number to its width, a space by default
+    pub fn fill(mut self, fill: char) -> Self {
+        self.fill = Some(fill);
+        self
+    }
+
+    /// The alignment of the number padded to its width
+    pub fn align(mut self, align: Alignment) -> Self {
+        self.align = Some(align);
+        self
+    }
+
+    /// How the sign is shown
+    pub fn sign(mut self, sign: Sign) -> Self {
+        self.sign = Some(sign);
+        self
+    }
+
+    /// Add a currency symbol or a base prefix
+    pub fn symbol(mut self, symbol: Symbol) -> Self {
+        self.symbol = Some(symbol);
+        self
+    }
+
+    /// Pad the number to its width with zeros
+    pub fn zero_padded(mut self) -> Self {
+        self.zero = true;
+        self
+    }
+
+    /// The minimum width of the formatted number
+    pub fn width(mut self, width: usize) -> Self {
+        self.width = Some(width);
+        self
+    }
+
+    /// Add thousands separators
+    pub fn grouping(mut self) -> Self {
+        self.grouping = true;
+        self
+    }
+
+    /// The number of digits after the decimal point for fixed point types, or the number of
+    /// significant digits otherwise
+    pub fn precision(mut self, precision: usize) -> Self {
+        self.precision = Some(precision);
+        self
+    }
+
+    /// Remove the trailing zeros after the decimal point
+    pub fn trim(mut self) -> Self {
+        self.trim = true;
+        self
+    }
+
+    /// How the number is written
+    pub fn number_type(mut self, number_type: NumberType) -> Self {
+        self.number_type = Some(number_type);
+        self
+    }
+}
+
+impl fmt::Display for NumberFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(align) = self.align {
+            if let Some(fill) = self.fill {
+                write!(f, "{}", fill)?;
+            }
+            write!(f, "{}", align.char())?;
+        }
+        if let Some(sign) = self.sign {
+            write!(f, "{}", sign.char())?;
+        }
+        if let Some(symbol) = self.symbol {
+            write!(f, "{}", symbol.char())?;
+        }
+        if self.zero {
+            write!(f, "0")?;
+        }
+        if let Some(width) = self.width {
+            write!(f, "{}", width)?;
+        }
+        if self.grouping {
+            write!(f, ",")?;
+        }
+        if let Some(precision) = self.precision {
+            write!(f, ".{}", precision)?;
+        }
+        if self.trim {
+            write!(f, "~")?;
+        }
+        if let Some(number_type) = self.number_type {
+            write!(f, "{}", number_type.char())?;
+        }
+        Ok(())
+    }
+}
+
+/// Take the digits at the start of a format
+fn digits(chars: &[char], i: &mut usize) -> Option<usize> {
+    let start = *i;
+    while *i < chars.len() && chars[*i].is_ascii_digit() {
+        *i += 1;
+    }
+    chars[start..*i].iter().collect::<String>().parse().ok()
+}
+
+impl FromStr for NumberFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || Error::Validation(format!("invalid number format `{}`", s));
+        let chars = s.chars().collect::<Vec<_>>();
+        let mut format = NumberFormat::new();
+        let mut i = 0;
+        match (
+            chars.get(1).cloned().and_then(Alignment::from_char),
+            chars.first().cloned().and_then(Alignment::from_char),
+        ) {
+            (Some(align), _) => {
+                format.fill = Some(chars[0]);
+                format.align = Some(align);
+                i = 2;
+            }
+            (None, Some(align)) => {
+                format.align = Some(align);
+                i = 1;
+            }
+            (None, None) => {}
+        }
+        if let Some(sign) = chars.get(i).cloned().and_then(Sign::from_char) {
+            format.sign = Some(sign);
+            i += 1;
+        }
+        if let Some(symbol) = chars.get(i).cloned().and_then(Symbol::from_char) {
+            format.symbol = Some(symbol);
+            i += 1;
+        }
+        if chars.get(i) == Some(&'0') {
+            format.zero = true;
+            i += 1;
+        }
+        format.width = digits(&chars, &mut i);
+        if chars.get(i) == Some(&',') {
+            format.grouping = true;
+            i += 1;
+        }
+        if chars.get(i) == Some(&'.') {
+            i += 1;
+            format.precision = Some(digits(&chars, &mut i).ok_or_else(invalid)?);
+        }
+        if chars.get(i) == Some(&'~') {
+            format.trim = true;
+            i += 1;
+        }
+        if let Some(number_type) = chars.get(i).cloned().and_then(NumberType::from_char) {
+            format.number_type = Some(number_type);
+            i += 1;
+        }
+        if i < chars.len() {
+            return Err(invalid());
+        }
+        Ok(format)
+    }
+}
+
+/// A part of a date or time, written `%` and a letter in a time format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Directive {
+    /// `%a`, abbreviated weekday name
+    ShortWeekday,
+    /// `%A`, full weekday name
+    Weekday,
+    /// `%b`, abbreviated month name
+    ShortMonth,
+    /// `%B`, full month name
+    Month,
+    /// `%c`, the date and time format of the locale
+    LocaleDateTime,
+    /// `%d`, zero-padded day of the month, `[01, 31]`
+    Day,
+    /// `%e`, space-padded day of the month, `[ 1, 31]`
+    SpacePaddedDay,
+    /// `%f`, microseconds, `[000000, 999999]`
+    Microseconds,
+    /// `%g`, ISO 8601 week-based year without century, `[00, 99]`
+    IsoShortYear,
+    /// `%G`, ISO 8601 week-based year with century
+    IsoYear,
+    /// `%H`, hour of a 24-hour clock, `[00, 23]`
+    Hour24,
+    /// `%I`, hour of a 12-hour clock, `[01, 12]`
+    Hour12,
+    /// `%j`, day of the year, `[001, 366]`
+    DayOfYear,
+    /// `%L`, milliseconds, `[000, 999]`
+    Milliseconds,
+    /// `%m`, month number, `[01, 12]`
+    MonthNumber,
+    /// `%M`, minutes, `[00, 59]`
+    Minutes,
+    /// `%p`, AM or PM
+    AmPm,
+    /// `%q`, quarter of the year, `[1, 4]`
+    Quarter,
+    /// `%Q`, milliseconds since the UNIX epoch
+    EpochMilliseconds,
+    /// `%s`, seconds since the UNIX epoch
+    EpochSeconds,
+    /// `%S`, seconds, `[00, 61]`
+    Seconds,
+    /// `%u`, Monday-based weekday, `[1, 7]`
+    MondayWeekday,
+    /// `%U`, Sunday-based week of the year, `[00, 53]`
+    SundayWeek,
+    /// `%V`, ISO 8601 week of the year, `[01, 53]`
+    IsoWeek,
+    /// `%w`, Sunday-based weekday, `[0, 6]`
+    SundayWeekday,
+    /// `%W`, Monday-based week of the year, `[00, 53]`
+    MondayWeek,
+    /// `%x`, the date format of the locale
+    LocaleDate,
+    /// `%X`, the time format of the locale
+    LocaleTime,
+    /// `%y`, year without century, `[00, 99]`
+    ShortYear,
+    /// `%Y`, year with century
+    Year,
+    /// `%Z`, time zone offset, such as `-0700`
+    TimeZone,
+}
+
+/// The padding of a directive, written between the `%` and its letter
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FormatPadding {
+    /// `0`, padded with zeros
+    Zero,
+    /// `_`, padded with spaces
+    Space,
+    /// `-`, not padded
+    None,
+}
+
+format_chars! {
+    Directive {
+        ShortWeekday => 'a',
+        Weekday => 'A',
+        ShortMonth => 'b',
+        Month => 'B',
+        LocaleDateTime => 'c',
+        Day => 'd',
+        SpacePaddedDay => 'e',
+        Microseconds => 'f',
+        IsoShortYear => 'g',
+        IsoYear => 'G',
+        Hour24 => 'H',
+        Hour12 => 'I',
+        DayOfYear => 'j',
+        Milliseconds => 'L',
+        MonthNumber => 'm',
+        Minutes => 'M',
+        AmPm => 'p',
+        Quarter => 'q',
+        EpochMilliseconds => 'Q',
+        EpochSeconds => 's',
+        Seconds => 'S',
+        MondayWeekday => 'u',
+        SundayWeek => 'U',
+        IsoWeek => 'V',
+        SundayWeekday => 'w',
+        MondayWeek => 'W',
+        LocaleDate => 'x',
+        LocaleTime => 'X',
+        ShortYear => 'y',
+        Year => 'Y',
+        TimeZone => 'Z',
+    }
+    FormatPadding {
+        Zero => '0',
+        Space => '_',
+        None => '-',
+    }
+}
+
+/// A part of a time format
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TimePart {
+    Text(String),
+    Directive(Directive, Option<FormatPadding>),
+}
+
+/// A d3-time-format time format, a sequence of directives and text
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct TimeFormat {
+    parts: Vec<TimePart>,
+}
+
+impl TimeFormat {
+    /// An empty time format
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a directive
+    pub fn then(mut self, directive: Directive) -> Self {
+        self.parts.push(TimePart::Directive(directive, None));
+        self
+    }
+
+    /// Add a directive with a padding
+    pub fn then_padded(mut self, directive: Directive, padding: FormatPadding) -> Self {
+        self.parts
+            .push(TimePart::Directive(directive, Some(padding)));
+        self
+    }
+
+    /// Add text, written as is
+    pub fn text(mut self, text: &str) -> Self {
+        match self.parts.last_mut() {
+            Some(TimePart::Text(last)) => last.push_str(text),
+            _ => self.parts.push(TimePart::Text(text.to_string())),
+        }
+        self
+    }
+}
+
+impl fmt::Display for TimeFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for part in &self.parts {
+            match part {
+                TimePart::Text(text) => write!(f, "{}", text.replace('%', "%%"))?,
+                TimePart::Directive(directive, padding) => {
+                    write!(f, "%")?;
+                    if let Some(padding) = padding {
+                        write!(f, "{}", padding.char())?;
+                    }
+                    write!(f, "{}", directive.char())?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for TimeFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid =
+            |reason: &str| Error::Validation(format!("invalid time format `{}`: {}", s, reason));
+        let mut format = TimeFormat::new();
+        let mut chars = s.chars();
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                format = format.text(&c.to_string());
+                continue;
+            }
+            let mut c = chars.next().ok_or_else(|| invalid("`%` at the end"))?;
+            if c == '%' {
+                format = format.text("%");
+                continue;
+            }
+            let padding = FormatPadding::from_char(c);
+            if padding.is_some() {
+                c = chars.next().ok_or_else(|| invalid("`%` at the end"))?;
+            }
+            let directive = Directive::from_char(c)
+                .ok_or_else(|| invalid(&format!("unknown directive `%{}`", c)))?;
+            format.parts.push(TimePart::Directive(directive, padding));
+        }
+        Ok(format)
+    }
+}
+
+/// A number or time format, for the properties that can take both along with a
+/// `format_type`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Format {
+    /// A number format
+    Number(NumberFormat),
+    /// A time format
+    Time(TimeFormat),
+}
+
+impl Format {
+    /// The type of the format
+    pub fn format_type(&self) -> FormatType {
+        match self {
+            Format::Number(_) => FormatType::Number,
+            Format::Time(_) => FormatType::Time,
+        }
+    }
+}
+
+impl fmt::Display for Format {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Format::Number(format) => format.fmt(f),
+            Format::Time(format) => format.fmt(f),
+        }
+    }
+}
+
+impl From<NumberFormat> for Format {
+    fn from(format: NumberFormat) -> Self {
+        Format::Number(format)
+    }
+}
+
+impl From<TimeFormat> for Format {
+    fn from(format: TimeFormat) -> Self {
+        Format::Time(format)
+    }
+}
+
+/// Setters of both the `format` and `format_type` of builders
+macro_rules! typed_format {
+    ($($builder:ident),* $(,)?) => {
+        $(
+            impl $builder {
+                /// Set the format along with its type
+                pub fn typed_format(&mut self, format: impl Into<Format>) -> &mut Self {
+                    let format = format.into();
+                    self.format_type(format.format_type());
+                    self.format(format.to_string())
+                }
+            }
+        )*
+    };
+}
+
+typed_format!(
+    AxisBuilder,
+    ConditionalPredicateValueDefClassBuilder,
+    DefWithConditionTextFieldDefValueBuilder,
+    HeaderBuilder,
+    HeaderConfigBuilder,
+    HrefClassBuilder,
+    LegendBuilder,
+    TextFieldDefBuilder,
+);
+
+impl ConfigBuilder {
+    /// Set the default number format
+    pub fn typed_number_format(&mut self, format: NumberFormat) -> &mut Self {
+        self.number_format(format.to_string())
+    }
+
+    /// Set the default time format
+    pub fn typed_time_format(&mut self, format: TimeFormat) -> &mut Self {
+        self.time_format(format.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_number_format() {
+        for format in &[
+            "", ",.2f", "$,.2f", ".0%", ".3~s", "+020,.3e", "*^12,d", "<8.1r", "(#x", ".2~%",
+        ] {
+            let parsed = format.parse::<NumberFormat>().unwrap();
+            assert_eq!(&parsed.to_string(), format);
+        }
+        let padded = NumberFormat::integer()
+            .fill('*')
+            .align(Alignment::Center)
+            .width(12);
+        assert_eq!(padded, "*^12,d".parse().unwrap());
+        assert_eq!(NumberFormat::si(3).to_string(), ".3~s");
+        assert_eq!(
+            NumberFormat::integer().sign(Sign::Always).to_string(),
+            "+,d"
+        );
+        for invalid in &["%Y", ".f", ",2f", "d."] {
+            assert!(invalid.parse::<NumberFormat>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_time_format() {
+        for format in &["%Y-%m-%d", "%-d %B %Y", "%H:%M:%S.%L", "%_I%p", "100%% %q"] {
+            let parsed = format.parse::<TimeFormat>().unwrap();
+            assert_eq!(&parsed.to_string(), format);
+        }
+        let format = TimeFormat::new()
+            .then_padded(Directive::Day, FormatPadding::None)
+            .text(" ")
+            .then(Directive::ShortMonth);
+        assert_eq!(format.to_string(), "%-d %b");
+        for invalid in &["%i", "%Y%", "%_"] {
+            assert!(invalid.parse::<TimeFormat>().is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_typed_format() {
+        let axis = AxisBuilder::default()
+            .typed_format(TimeFormat::new().then(Directive::Year))
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(axis).unwrap(),
+            json!({"format": "%Y", "formatType": "time"})
+        );
+        let config = ConfigBuilder::default()
+            .typed_number_format(NumberFormat::fixed(1))
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(config).unwrap(),
+            json!({"numberFormat": ".1f"})
+        );
+    }
+}
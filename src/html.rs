@@ -0,0 +1,97 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::error::Error;
+use crate::schema::*;
+use serde_json::json;
+
+impl Vegalite {
+    /// Render a standalone html page displaying the graph with
+    /// [vega-embed](https://github.com/vega/vega-embed)
+    pub fn to_html(&self) -> Result<String, Error> {
+        Ok(render_html(&self.themed())?)
+    }
+}
+
+/// Render a chart as html, with its vega-embed options (such as its [locale](locale/index.html))
+pub(crate) fn render_html(chart: &Vegalite) -> Result<String, serde_json::Error> {
+    let options = chart.embed_options().cloned().unwrap_or_else(|| json!({}));
+    let (head, rest) = split_template(VEGA_EMBED_HTML_TEMPLATE, "{{ spec_as_json }}");
+    let (middle, tail) = split_template(rest, "{{ options_as_json }}");
+    Ok([
+        head,
+        &script_json(chart)?,
+        middle,
+        &script_json(&options)?,
+        tail,
+    ]
+    .concat())
+}
+
+fn split_template<'a>(template: &'a str, placeholder: &str) -> (&'a str, &'a str) {
+    let start = template
+        .find(placeholder)
+        .expect("the template has the placeholder");
+    (&template[..start], &template[start + placeholder.len()..])
+}
+
+/// Serialize a value as json that can't close the `<script>` element it is written in
+fn script_json<T: serde::Serialize>(value: &T) -> Result<String, serde_json::Error> {
+    Ok(serde_json::to_string(value)?
+        .replace('&', "\\u0026")
+        .replace('<', "\\u003c")
+        .replace('>', "\\u003e"))
+}
+
+const VEGA_EMBED_HTML_TEMPLATE: &str = r#"
+<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <!-- Import Vega 5 & Vega-Lite 3 (does not have to be from CDN) -->
+  <script src="https://cdn.jsdelivr.net/npm/vega@5"></script>
+  <script src="https://cdn.jsdelivr.net/npm/vega-lite@3"></script>
+  <!-- Import vega-embed -->
+  <script src="https://cdn.jsdelivr.net/npm/vega-embed@6"></script>
+</head>
+<body>
+
+<div id="vis"></div>
+
+<script type="text/javascript">
+  var spec = {{ spec_as_json }};
+  var options = {{ options_as_json }};
+  vegaEmbed('#vis', spec, options).then(function(result) {
+    // Access the Vega view instance (https://vega.github.io/vega/docs/api/view/) as result.view
+  }).catch(console.error);
+</script>
+</body>
+</html>
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_html_escapes_script() {
+        let chart = VegaliteBuilder::default()
+            .title("</script><script>alert(1)")
+            .description("a & b")
+            .build()
+            .unwrap();
+        let html = chart.to_html().unwrap();
+        assert!(!html.contains("</script><script>alert"));
+        assert!(html.contains(r#""\u003c/script\u003e\u003cscript\u003ealert(1)""#));
+        assert!(html.contains(r#""a \u0026 b""#));
+        assert!(html.contains("var options = {};"));
+    }
+}
@@ -34,17 +34,58 @@
     missing_docs
 )]
 
+pub mod color;
+mod compose;
+mod composite;
 mod data;
+mod error;
+mod eval;
+pub mod event;
+pub mod expr;
+mod file;
+pub mod format;
+mod html;
+pub mod locale;
+mod merge;
+pub mod predicate;
+pub mod recipes;
 mod removable_value;
 mod schema;
+pub mod selection;
 mod string;
+pub mod themes;
+pub mod tooltip;
+mod transform;
+pub mod trend;
+pub mod widgets;
 
 #[cfg(feature = "show_vega")]
 mod show_vega;
 #[cfg(feature = "show_vega")]
 pub use showata::Showable;
 
+pub use compose::*;
+pub use composite::*;
 pub use data::*;
+pub use error::Error;
+pub use eval::Downsample;
+pub use merge::Merge;
 pub use removable_value::RemovableValue;
 pub use schema::*;
-pub use string::*;
+pub use transform::*;
+
+/// Run a closure on a thread with a big stack: deserializing a `Vegalite` needs more than the
+/// 2MiB of test threads in debug builds, the schema structs being large. Only the
+/// deserialization should run there, so that the code under test overflowing still shows.
+#[cfg(test)]
+pub(crate) fn with_big_stack<T: Send + 'static>(f: impl FnOnce() -> T + Send + 'static) -> T {
+    let result = std::thread::Builder::new()
+        .stack_size(64 * 1024 * 1024)
+        .spawn(f)
+        .expect("the test thread can be spawned")
+        .join();
+    match result {
+        Ok(value) => value,
+        Err(panic) => std::panic::resume_unwind(panic),
+    }
+}
@@ -0,0 +1,264 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! [d3-format](https://github.com/d3/d3-format#formatLocale) and
+//! [d3-time-format](https://github.com/d3/d3-time-format#timeFormatLocale) locale definitions,
+//! passed to [vega-embed](https://github.com/vega/vega-embed) as its `formatLocale` and
+//! `timeFormatLocale` options when rendering charts as html.
+//!
+//! ```
+//! use vega_lite_3::locale;
+//! use vega_lite_3::*;
+//!
+//! let mut chart = VegaliteBuilder::default().mark(Mark::Bar).build().unwrap();
+//! chart.set_locale(&locale::get("de-DE").unwrap());
+//! assert_eq!(chart.locale().unwrap().number.decimal, ",");
+//! ```
+
+use crate::schema::*;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// The tags of the built-in locales
+pub const BUILTIN: &[&str] = &["de-DE", "en-GB", "en-US", "es-ES", "fr-FR", "it-IT"];
+
+/// The key of the vega-embed options in the `usermeta` of a chart
+const EMBED_OPTIONS: &str = "embedOptions";
+
+/// How numbers are written, the `formatLocale` of vega-embed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NumberLocale {
+    /// The decimal point
+    pub decimal: String,
+    /// The group separator
+    pub thousands: String,
+    /// The sizes of the digit groups, from the decimal point, the last one being repeated
+    pub grouping: Vec<usize>,
+    /// The text before and after amounts of money
+    pub currency: [String; 2],
+}
+
+/// How dates and times are written, the `timeFormatLocale` of vega-embed
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeLocale {
+    /// The date and time format, `%c`
+    pub date_time: String,
+    /// The date format, `%x`
+    pub date: String,
+    /// The time format, `%X`
+    pub time: String,
+    /// The names of the morning and afternoon, `%p`
+    pub periods: [String; 2],
+    /// The names of the weekdays from Sunday, `%A`
+    pub days: [String; 7],
+    /// The abbreviated names of the weekdays from Sunday, `%a`
+    pub short_days: [String; 7],
+    /// The names of the months from January, `%B`
+    pub months: [String; 12],
+    /// The abbreviated names of the months from January, `%b`
+    pub short_months: [String; 12],
+}
+
+/// A locale, for both numbers and dates
+#[derive(Debug, Clone, PartialEq)]
+pub struct Locale {
+    /// How numbers are written
+    pub number: NumberLocale,
+    /// How dates and times are written
+    pub time: TimeLocale,
+}
+
+fn locale(number: Value, time: Value) -> Locale {
+    Locale {
+        number: serde_json::from_value(number).expect("built-in locales are valid"),
+        time: serde_json::from_value(time).expect("built-in locales are valid"),
+    }
+}
+
+fn english(currency: &str, date_time: &str, date: &str, time: &str) -> Locale {
+    locale(
+        json!({"decimal": ".", "thousands": ",", "grouping": [3], "currency": [currency, ""]}),
+        json!({
+            "dateTime": date_time,
+            "date": date,
+            "time": time,
+            "periods": ["AM", "PM"],
+            "days": ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"],
+            "shortDays": ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"],
+            "months": [
+                "January", "February", "March", "April", "May", "June", "July", "August",
+                "September", "October", "November", "December"
+            ],
+            "shortMonths": [
+                "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec"
+            ]
+        }),
+    )
+}
+
+/// Get a built-in locale by its tag
+pub fn get(tag: &str) -> Option<Locale> {
+    Some(match tag {
+        "en-US" => english("$", "%x, %X", "%-m/%-d/%Y", "%-I:%M:%S %p"),
+        "en-GB" => english("£", "%a %e %b %X %Y", "%d/%m/%Y", "%H:%M:%S"),
+        "de-DE" => locale(
+            json!({"decimal": ",", "thousands": ".", "grouping": [3], "currency": ["", "\u{a0}€"]}),
+            json!({
+                "dateTime": "%A, der %e. %B %Y, %X",
+                "date": "%d.%m.%Y",
+                "time": "%H:%M:%S",
+                "periods": ["AM", "PM"],
+                "days": [
+                    "Sonntag", "Montag", "Dienstag", "Mittwoch", "Donnerstag", "Freitag", "Samstag"
+                ],
+                "shortDays": ["So", "Mo", "Di", "Mi", "Do", "Fr", "Sa"],
+                "months": [
+                    "Januar", "Februar", "März", "April", "Mai", "Juni", "Juli", "August",
+                    "September", "Oktober", "November", "Dezember"
+                ],
+                "shortMonths": [
+                    "Jan", "Feb", "Mrz", "Apr", "Mai", "Jun", "Jul", "Aug", "Sep", "Okt", "Nov",
+                    "Dez"
+                ]
+            }),
+        ),
+        "es-ES" => locale(
+            json!({"decimal": ",", "thousands": ".", "grouping": [3], "currency": ["", "\u{a0}€"]}),
+            json!({
+                "dateTime": "%A, %e de %B de %Y, %X",
+                "date": "%d/%m/%Y",
+                "time": "%H:%M:%S",
+                "periods": ["AM", "PM"],
+                "days": ["domingo", "lunes", "martes", "miércoles", "jueves", "viernes", "sábado"],
+                "shortDays": ["dom", "lun", "mar", "mié", "jue", "vie", "sáb"],
+                "months": [
+                    "enero", "febrero", "marzo", "abril", "mayo", "junio", "julio", "agosto",
+                    "septiembre", "octubre", "noviembre", "diciembre"
+                ],
+                "shortMonths": [
+                    "ene", "feb", "mar", "abr", "may", "jun", "jul", "ago", "sep", "oct", "nov", "dic"
+                ]
+            }),
+        ),
+        "fr-FR" => locale(
+            json!({
+                "decimal": ",",
+                "thousands": "\u{a0}",
+                "grouping": [3],
+                "currency": ["", "\u{a0}€"]
+            }),
+            json!({
+                "dateTime": "%A %e %B %Y à %X",
+                "date": "%d/%m/%Y",
+                "time": "%H:%M:%S",
+                "periods": ["AM", "PM"],
+                "days": ["dimanche", "lundi", "mardi", "mercredi", "jeudi", "vendredi", "samedi"],
+                "shortDays": ["dim.", "lun.", "mar.", "mer.", "jeu.", "ven.", "sam."],
+                "months": [
+                    "janvier", "février", "mars", "avril", "mai", "juin", "juillet", "août",
+                    "septembre", "octobre", "novembre", "décembre"
+                ],
+                "shortMonths": [
+                    "janv.", "févr.", "mars", "avr.", "mai", "juin", "juil.", "août", "sept.",
+                    "oct.", "nov.", "déc."
+                ]
+            }),
+        ),
+        "it-IT" => locale(
+            json!({"decimal": ",", "thousands": ".", "grouping": [3], "currency": ["€", ""]}),
+            json!({
+                "dateTime": "%A %e %B %Y, %X",
+                "date": "%d/%m/%Y",
+                "time": "%H:%M:%S",
+                "periods": ["AM", "PM"],
+                "days": [
+                    "Domenica", "Lunedì", "Martedì", "Mercoledì", "Giovedì", "Venerdì", "Sabato"
+                ],
+                "shortDays": ["Dom", "Lun", "Mar", "Mer", "Gio", "Ven", "Sab"],
+                "months": [
+                    "Gennaio", "Febbraio", "Marzo", "Aprile", "Maggio", "Giugno", "Luglio",
+                    "Agosto", "Settembre", "Ottobre", "Novembre", "Dicembre"
+                ],
+                "shortMonths": [
+                    "Gen", "Feb", "Mar", "Apr", "Mag", "Giu", "Lug", "Ago", "Set", "Ott", "Nov",
+                    "Dic"
+                ]
+            }),
+        ),
+        _ => return None,
+    })
+}
+
+impl Vegalite {
+    /// Set the locale used to format numbers and dates when rendering the chart as html,
+    /// kept in the vega-embed options of its `usermeta`
+    pub fn set_locale(&mut self, locale: &Locale) {
+        let usermeta = self.usermeta.get_or_insert_with(Default::default);
+        let options = usermeta
+            .entry(EMBED_OPTIONS.to_string())
+            .or_insert(None)
+            .get_or_insert_with(|| json!({}));
+        if !options.is_object() {
+            *options = json!({});
+        }
+        options["formatLocale"] = json!(locale.number);
+        options["timeFormatLocale"] = json!(locale.time);
+    }
+
+    /// The locale of the chart, if any
+    pub fn locale(&self) -> Option<Locale> {
+        let options = self.embed_options()?;
+        Some(Locale {
+            number: serde_json::from_value(options.get("formatLocale")?.clone()).ok()?,
+            time: serde_json::from_value(options.get("timeFormatLocale")?.clone()).ok()?,
+        })
+    }
+
+    /// The vega-embed options in the `usermeta` of the chart
+    pub(crate) fn embed_options(&self) -> Option<&Value> {
+        self.usermeta.as_ref()?.get(EMBED_OPTIONS)?.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin() {
+        for tag in BUILTIN {
+            assert!(get(tag).is_some(), "{}", tag);
+        }
+        assert_eq!(get("fr-FR").unwrap().time.months[7], "août");
+        assert!(get("xx-XX").is_none());
+    }
+
+    #[test]
+    fn test_set_locale() {
+        let mut chart = VegaliteBuilder::default()
+            .usermeta(
+                vec![(EMBED_OPTIONS.to_string(), Some(json!({"actions": false})))]
+                    .into_iter()
+                    .collect::<std::collections::HashMap<_, _>>(),
+            )
+            .build()
+            .unwrap();
+        assert_eq!(chart.locale(), None);
+        let locale = get("de-DE").unwrap();
+        chart.set_locale(&locale);
+        assert_eq!(chart.locale(), Some(locale));
+        let options = chart.embed_options().unwrap();
+        assert_eq!(options["actions"], json!(false));
+        assert_eq!(options["formatLocale"]["currency"], json!(["", "\u{a0}€"]));
+        assert_eq!(options["timeFormatLocale"]["shortDays"][0], json!("So"));
+    }
+}
@@ -0,0 +1,202 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Deep merge of specifications, to layer settings over defaults.
+
+use crate::removable_value::RemovableValue;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// Merge of a value with overrides
+///
+/// Structs are merged field by field, and maps key by key. Unset overrides keep the base
+/// value, while [`RemovableValue::Remove`](enum.RemovableValue.html#variant.Remove) removes
+/// it. Enums are merged when both values are the same variant, and replaced otherwise;
+/// numbers, strings and arrays are replaced.
+///
+/// ```
+/// use vega_lite_3::*;
+///
+/// let base = AxisConfigBuilder::default().grid(true).grid_width(0.5).build().unwrap();
+/// let overrides = AxisConfigBuilder::default().grid_width(1.0).build().unwrap();
+/// let axis = base.merge(overrides);
+/// assert_eq!((axis.grid, axis.grid_width), (Some(true), Some(1.0)));
+/// ```
+pub trait Merge {
+    /// Merge `overrides` over `self`
+    fn merge(self, overrides: Self) -> Self;
+}
+
+/// Values replaced by their overrides
+macro_rules! replace {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl Merge for $ty {
+                fn merge(self, overrides: Self) -> Self {
+                    overrides
+                }
+            }
+        )*
+    };
+}
+
+replace!(bool, f64, String);
+
+impl<T> Merge for Vec<T> {
+    fn merge(self, overrides: Self) -> Self {
+        overrides
+    }
+}
+
+impl<T: Merge> Merge for Option<T> {
+    fn merge(self, overrides: Self) -> Self {
+        match (self, overrides) {
+            (Some(value), Some(overrides)) => Some(value.merge(overrides)),
+            (value, None) => value,
+            (None, overrides) => overrides,
+        }
+    }
+}
+
+impl<T: Merge + Clone> Merge for RemovableValue<T> {
+    fn merge(self, overrides: Self) -> Self {
+        match (self, overrides) {
+            (RemovableValue::Specified(value), RemovableValue::Specified(overrides)) => {
+                RemovableValue::Specified(value.merge(overrides))
+            }
+            (value, RemovableValue::Default) => value,
+            (_, overrides) => overrides,
+        }
+    }
+}
+
+impl<T: Merge> Merge for Box<T> {
+    fn merge(self, overrides: Self) -> Self {
+        Box::new((*self).merge(*overrides))
+    }
+}
+
+impl<K: Eq + Hash, V: Merge> Merge for HashMap<K, V> {
+    fn merge(mut self, overrides: Self) -> Self {
+        for (key, value) in overrides {
+            let value = match self.remove(&key) {
+                Some(base) => base.merge(value),
+                None => value,
+            };
+            self.insert(key, value);
+        }
+        self
+    }
+}
+
+impl Merge for serde_json::Value {
+    fn merge(self, overrides: Self) -> Self {
+        match (self, overrides) {
+            (serde_json::Value::Object(mut value), serde_json::Value::Object(overrides)) => {
+                for (key, override_value) in overrides {
+                    let merged = match value.remove(&key) {
+                        Some(base) => base.merge(override_value),
+                        None => override_value,
+                    };
+                    value.insert(key, merged);
+                }
+                serde_json::Value::Object(value)
+            }
+            (_, overrides) => overrides,
+        }
+    }
+}
+
+/// Structs merged field by field
+macro_rules! merge_structs {
+    ($($name:ident { $($field:ident),* $(,)? })*) => {
+        $(
+            impl Merge for $name {
+                #[allow(unused_variables)]
+                fn merge(self, overrides: Self) -> Self {
+                    $name {
+                        $($field: self.$field.merge(overrides.$field),)*
+                    }
+                }
+            }
+        )*
+    };
+}
+
+/// Enums merged when both values are the same variant, and replaced otherwise
+macro_rules! merge_enums {
+    ($($name:ident { $($variant:ident),* $(,)? })*) => {
+        $(
+            impl Merge for $name {
+                fn merge(self, overrides: Self) -> Self {
+                    #[allow(unreachable_patterns)]
+                    match (self, overrides) {
+                        $(($name::$variant(value), $name::$variant(overrides)) => {
+                            $name::$variant(value.merge(overrides))
+                        })*
+                        (_, overrides) => overrides,
+                    }
+                }
+            }
+        )*
+    };
+}
+
+mod schema;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_config() {
+        let base: Config = serde_json::from_value(json!({
+            "background": "#fff",
+            "axis": {"grid": true, "gridWidth": 0.5},
+            "view": {"stroke": "#ccc", "width": 300},
+            "range": {"category": ["#000", "#fff"]},
+            "style": {"guide-label": {"fill": "#333"}}
+        }))
+        .unwrap();
+        let overrides: Config = serde_json::from_value(json!({
+            "axis": {"gridWidth": 1},
+            "view": {"stroke": null},
+            "range": {"category": ["#f00"], "ordinal": ["#0f0"]},
+            "style": {"guide-label": {"fontSize": 12}}
+        }))
+        .unwrap();
+        assert_eq!(
+            serde_json::to_value(base.merge(overrides)).unwrap(),
+            json!({
+                "background": "#fff",
+                "axis": {"grid": true, "gridWidth": 1.0},
+                "view": {"stroke": null, "width": 300.0},
+                "range": {"category": ["#f00"], "ordinal": ["#0f0"]},
+                "style": {"guide-label": {"fill": "#333", "fontSize": 12.0}}
+            })
+        );
+    }
+
+    #[test]
+    fn test_merge_enums() {
+        let mark = |value| serde_json::from_value::<AnyMark>(value).unwrap();
+        let merged = mark(json!({"type": "line", "color": "red"}))
+            .merge(mark(json!({"type": "line", "point": true})));
+        assert_eq!(
+            serde_json::to_value(merged).unwrap(),
+            json!({"type": "line", "color": "red", "point": true})
+        );
+        let replaced = mark(json!({"type": "line", "color": "red"})).merge(mark(json!("bar")));
+        assert_eq!(serde_json::to_value(replaced).unwrap(), json!("bar"));
+    }
+}
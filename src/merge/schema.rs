@@ -0,0 +1,2120 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! `Merge` for the types of the schema, generated by `scripts/merge_impls.py`
+
+use super::Merge;
+use crate::schema::*;
+
+merge_structs! {
+    Vegalite {
+        schema,
+        align,
+        autosize,
+        background,
+        bounds,
+        center,
+        columns,
+        config,
+        data,
+        datasets,
+        description,
+        encoding,
+        height,
+        mark,
+        name,
+        padding,
+        projection,
+        resolve,
+        selection,
+        spacing,
+        title,
+        transform,
+        usermeta,
+        view,
+        width,
+        facet,
+        spec,
+        layer,
+        repeat,
+        concat,
+        vconcat,
+        hconcat,
+    }
+    RowColLayoutAlign {
+        column,
+        row,
+    }
+    AutoSizeParams {
+        contains,
+        resize,
+        auto_size_params_type,
+    }
+    RowColBoolean {
+        column,
+        row,
+    }
+    SpecClass {
+        data,
+        description,
+        encoding,
+        height,
+        layer,
+        name,
+        projection,
+        resolve,
+        title,
+        transform,
+        view,
+        width,
+        align,
+        bounds,
+        center,
+        columns,
+        mark,
+        selection,
+        spacing,
+        facet,
+        spec,
+        repeat,
+        concat,
+        vconcat,
+        hconcat,
+    }
+    Spec {
+        align,
+        bounds,
+        center,
+        columns,
+        data,
+        description,
+        encoding,
+        height,
+        mark,
+        name,
+        projection,
+        resolve,
+        selection,
+        spacing,
+        title,
+        transform,
+        view,
+        width,
+        layer,
+        facet,
+        spec,
+        repeat,
+        concat,
+        vconcat,
+        hconcat,
+    }
+    UrlData {
+        format,
+        name,
+        url,
+        values,
+        sequence,
+        sphere,
+        graticule,
+    }
+    DataFormat {
+        parse,
+        data_format_type,
+        delimiter,
+        property,
+        feature,
+        mesh,
+    }
+    GraticuleParams {
+        extent,
+        extent_major,
+        extent_minor,
+        precision,
+        step,
+        step_major,
+        step_minor,
+    }
+    SequenceParams {
+        sequence_params_as,
+        start,
+        step,
+        stop,
+    }
+    SphereClass {}
+    Encoding {
+        color,
+        column,
+        detail,
+        facet,
+        fill,
+        fill_opacity,
+        href,
+        key,
+        latitude,
+        latitude2,
+        longitude,
+        longitude2,
+        opacity,
+        order,
+        row,
+        shape,
+        size,
+        stroke,
+        stroke_opacity,
+        stroke_width,
+        text,
+        tooltip,
+        x,
+        x2,
+        x_error,
+        x_error2,
+        y,
+        y2,
+        y_error,
+        y_error2,
+    }
+    DefWithConditionMarkPropFieldDefStringNull {
+        aggregate,
+        bin,
+        condition,
+        field,
+        legend,
+        scale,
+        sort,
+        time_unit,
+        title,
+        def_with_condition_mark_prop_field_def_string_null_type,
+        value,
+    }
+    ArgmDef {
+        argmax,
+        argmin,
+    }
+    BinParams {
+        anchor,
+        base,
+        binned,
+        divide,
+        extent,
+        maxbins,
+        minstep,
+        nice,
+        step,
+        steps,
+    }
+    ConditionalStringValueDef {
+        test,
+        value,
+        selection,
+    }
+    Selection {
+        not,
+        and,
+        or,
+    }
+    Predicate {
+        not,
+        and,
+        or,
+        equal,
+        field,
+        time_unit,
+        range,
+        one_of,
+        lt,
+        gt,
+        lte,
+        gte,
+        valid,
+        selection,
+    }
+    DateTime {
+        date,
+        day,
+        hours,
+        milliseconds,
+        minutes,
+        month,
+        quarter,
+        seconds,
+        utc,
+        year,
+    }
+    ConditionalPredicateStringValueDefClass {
+        test,
+        value,
+        selection,
+        aggregate,
+        bin,
+        field,
+        legend,
+        scale,
+        sort,
+        time_unit,
+        title,
+        conditional_def_type,
+    }
+    RepeatRef {
+        repeat,
+    }
+    Legend {
+        clip_height,
+        column_padding,
+        columns,
+        corner_radius,
+        direction,
+        fill_color,
+        format,
+        format_type,
+        gradient_length,
+        gradient_opacity,
+        gradient_stroke_color,
+        gradient_stroke_width,
+        gradient_thickness,
+        grid_align,
+        label_align,
+        label_baseline,
+        label_color,
+        label_font,
+        label_font_size,
+        label_font_style,
+        label_font_weight,
+        label_limit,
+        label_offset,
+        label_opacity,
+        label_overlap,
+        label_padding,
+        label_separation,
+        legend_x,
+        legend_y,
+        offset,
+        orient,
+        padding,
+        row_padding,
+        stroke_color,
+        symbol_dash,
+        symbol_dash_offset,
+        symbol_fill_color,
+        symbol_offset,
+        symbol_opacity,
+        symbol_size,
+        symbol_stroke_color,
+        symbol_stroke_width,
+        symbol_type,
+        tick_count,
+        tick_min_step,
+        title,
+        title_align,
+        title_anchor,
+        title_baseline,
+        title_color,
+        title_font,
+        title_font_size,
+        title_font_style,
+        title_font_weight,
+        title_limit,
+        title_opacity,
+        title_orient,
+        title_padding,
+        legend_type,
+        values,
+        zindex,
+    }
+    Scale {
+        align,
+        base,
+        bins,
+        clamp,
+        constant,
+        domain,
+        exponent,
+        interpolate,
+        nice,
+        padding,
+        padding_inner,
+        padding_outer,
+        range,
+        range_step,
+        round,
+        scheme,
+        scale_type,
+        zero,
+    }
+    DomainClass {
+        field,
+        selection,
+        encoding,
+    }
+    ScaleInterpolateParams {
+        gamma,
+        scale_interpolate_params_type,
+    }
+    NiceClass {
+        interval,
+        step,
+    }
+    SchemeParams {
+        count,
+        extent,
+        name,
+    }
+    EncodingSortField {
+        field,
+        op,
+        order,
+        encoding,
+    }
+    FacetFieldDef {
+        aggregate,
+        bin,
+        field,
+        header,
+        sort,
+        time_unit,
+        title,
+        facet_field_def_type,
+    }
+    Header {
+        format,
+        format_type,
+        label_align,
+        label_anchor,
+        label_angle,
+        label_color,
+        label_font,
+        label_font_size,
+        label_font_style,
+        label_limit,
+        label_orient,
+        label_padding,
+        labels,
+        title,
+        title_align,
+        title_anchor,
+        title_angle,
+        title_baseline,
+        title_color,
+        title_font,
+        title_font_size,
+        title_font_style,
+        title_font_weight,
+        title_limit,
+        title_orient,
+        title_padding,
+    }
+    SortEncodingSortField {
+        field,
+        op,
+        order,
+    }
+    TypedFieldDef {
+        aggregate,
+        bin,
+        field,
+        time_unit,
+        title,
+        typed_field_def_type,
+    }
+    DefWithConditionMarkPropFieldDefNumber {
+        aggregate,
+        bin,
+        condition,
+        field,
+        legend,
+        scale,
+        sort,
+        time_unit,
+        title,
+        def_with_condition_mark_prop_field_def_number_type,
+        value,
+    }
+    ConditionalNumberValueDef {
+        test,
+        value,
+        selection,
+    }
+    ConditionalPredicateNumberValueDefClass {
+        test,
+        value,
+        selection,
+        aggregate,
+        bin,
+        field,
+        legend,
+        scale,
+        sort,
+        time_unit,
+        title,
+        conditional_def_type,
+    }
+    HrefClass {
+        aggregate,
+        bin,
+        condition,
+        field,
+        format,
+        format_type,
+        time_unit,
+        title,
+        def_with_condition_text_field_def_value_type,
+        value,
+    }
+    ConditionalValueDef {
+        test,
+        value,
+        selection,
+    }
+    ConditionalPredicateValueDefClass {
+        test,
+        value,
+        selection,
+        aggregate,
+        bin,
+        field,
+        format,
+        format_type,
+        time_unit,
+        title,
+        conditional_def_type,
+    }
+    LatitudeClass {
+        aggregate,
+        bin,
+        field,
+        time_unit,
+        title,
+        def_type,
+        value,
+    }
+    Latitude2Class {
+        aggregate,
+        bin,
+        field,
+        time_unit,
+        title,
+        value,
+    }
+    OrderFieldDef {
+        aggregate,
+        bin,
+        field,
+        sort,
+        time_unit,
+        title,
+        order_field_def_type,
+    }
+    OrderFieldDefClass {
+        aggregate,
+        bin,
+        field,
+        sort,
+        time_unit,
+        title,
+        def_type,
+        value,
+    }
+    DefWithConditionMarkPropFieldDefTypeForShapeStringNull {
+        aggregate,
+        bin,
+        condition,
+        field,
+        legend,
+        scale,
+        sort,
+        time_unit,
+        title,
+        def_with_condition_mark_prop_field_def_type_for_shape_string_null_type,
+        value,
+    }
+    Conditional {
+        test,
+        value,
+        selection,
+        aggregate,
+        bin,
+        field,
+        legend,
+        scale,
+        sort,
+        time_unit,
+        title,
+        conditional_type,
+    }
+    TextFieldDef {
+        aggregate,
+        bin,
+        field,
+        format,
+        format_type,
+        time_unit,
+        title,
+        text_field_def_type,
+    }
+    DefWithConditionTextFieldDefValue {
+        aggregate,
+        bin,
+        condition,
+        field,
+        format,
+        format_type,
+        time_unit,
+        title,
+        def_with_condition_text_field_def_value_type,
+        value,
+    }
+    XClass {
+        aggregate,
+        axis,
+        bin,
+        field,
+        impute,
+        scale,
+        sort,
+        stack,
+        time_unit,
+        title,
+        def_type,
+        value,
+    }
+    Axis {
+        band_position,
+        domain,
+        domain_color,
+        domain_dash,
+        domain_dash_offset,
+        domain_opacity,
+        domain_width,
+        format,
+        format_type,
+        grid,
+        grid_color,
+        grid_dash,
+        grid_dash_offset,
+        grid_opacity,
+        grid_width,
+        label_align,
+        label_angle,
+        label_baseline,
+        label_bound,
+        label_color,
+        label_flush,
+        label_flush_offset,
+        label_font,
+        label_font_size,
+        label_font_style,
+        label_font_weight,
+        label_limit,
+        label_opacity,
+        label_overlap,
+        label_padding,
+        labels,
+        label_separation,
+        max_extent,
+        min_extent,
+        offset,
+        orient,
+        position,
+        tick_color,
+        tick_count,
+        tick_dash,
+        tick_dash_offset,
+        tick_extra,
+        tick_min_step,
+        tick_offset,
+        tick_opacity,
+        tick_round,
+        ticks,
+        tick_size,
+        tick_width,
+        title,
+        title_align,
+        title_anchor,
+        title_angle,
+        title_baseline,
+        title_color,
+        title_font,
+        title_font_size,
+        title_font_style,
+        title_font_weight,
+        title_limit,
+        title_opacity,
+        title_padding,
+        title_x,
+        title_y,
+        values,
+        zindex,
+    }
+    ImputeParams {
+        frame,
+        keyvals,
+        method,
+        value,
+    }
+    ImputeSequence {
+        start,
+        step,
+        stop,
+    }
+    X2Class {
+        aggregate,
+        bin,
+        field,
+        time_unit,
+        title,
+        value,
+    }
+    YClass {
+        aggregate,
+        axis,
+        bin,
+        field,
+        impute,
+        scale,
+        sort,
+        stack,
+        time_unit,
+        title,
+        def_type,
+        value,
+    }
+    Y2Class {
+        aggregate,
+        bin,
+        field,
+        time_unit,
+        title,
+        value,
+    }
+    Facet {
+        aggregate,
+        bin,
+        field,
+        header,
+        sort,
+        time_unit,
+        title,
+        facet_type,
+        column,
+        row,
+    }
+    LayerSpec {
+        data,
+        description,
+        encoding,
+        height,
+        layer,
+        name,
+        projection,
+        resolve,
+        title,
+        transform,
+        view,
+        width,
+        mark,
+        selection,
+    }
+    LayerEncoding {
+        color,
+        detail,
+        fill,
+        fill_opacity,
+        href,
+        key,
+        latitude,
+        latitude2,
+        longitude,
+        longitude2,
+        opacity,
+        order,
+        shape,
+        size,
+        stroke,
+        stroke_opacity,
+        stroke_width,
+        text,
+        tooltip,
+        x,
+        x2,
+        x_error,
+        x_error2,
+        y,
+        y2,
+        y_error,
+        y_error2,
+    }
+    MarkDefClass {
+        def_box,
+        clip,
+        color,
+        extent,
+        median,
+        opacity,
+        orient,
+        outliers,
+        rule,
+        size,
+        ticks,
+        def_type,
+        band,
+        borders,
+        interpolate,
+        tension,
+        align,
+        angle,
+        baseline,
+        bin_spacing,
+        corner_radius,
+        cursor,
+        dir,
+        dx,
+        dy,
+        ellipsis,
+        fill,
+        filled,
+        fill_opacity,
+        font,
+        font_size,
+        font_style,
+        font_weight,
+        height,
+        href,
+        limit,
+        line,
+        order,
+        point,
+        radius,
+        shape,
+        stroke,
+        stroke_cap,
+        stroke_dash,
+        stroke_dash_offset,
+        stroke_join,
+        stroke_miter_limit,
+        stroke_opacity,
+        stroke_width,
+        style,
+        text,
+        theta,
+        thickness,
+        tooltip,
+        width,
+        x,
+        x2,
+        x2_offset,
+        x_offset,
+        y,
+        y2,
+        y2_offset,
+        y_offset,
+    }
+    MarkConfig {
+        align,
+        angle,
+        baseline,
+        color,
+        corner_radius,
+        cursor,
+        dir,
+        dx,
+        dy,
+        ellipsis,
+        fill,
+        filled,
+        fill_opacity,
+        font,
+        font_size,
+        font_style,
+        font_weight,
+        height,
+        href,
+        interpolate,
+        limit,
+        opacity,
+        order,
+        orient,
+        radius,
+        shape,
+        size,
+        stroke,
+        stroke_cap,
+        stroke_dash,
+        stroke_dash_offset,
+        stroke_join,
+        stroke_miter_limit,
+        stroke_opacity,
+        stroke_width,
+        tension,
+        text,
+        theta,
+        tooltip,
+        width,
+        x,
+        x2,
+        y,
+        y2,
+    }
+    TooltipContent {
+        content,
+    }
+    OverlayMarkDef {
+        align,
+        angle,
+        baseline,
+        clip,
+        color,
+        corner_radius,
+        cursor,
+        dir,
+        dx,
+        dy,
+        ellipsis,
+        fill,
+        filled,
+        fill_opacity,
+        font,
+        font_size,
+        font_style,
+        font_weight,
+        height,
+        href,
+        interpolate,
+        limit,
+        opacity,
+        order,
+        orient,
+        radius,
+        shape,
+        size,
+        stroke,
+        stroke_cap,
+        stroke_dash,
+        stroke_dash_offset,
+        stroke_join,
+        stroke_miter_limit,
+        stroke_opacity,
+        stroke_width,
+        style,
+        tension,
+        text,
+        theta,
+        tooltip,
+        width,
+        x,
+        x2,
+        x2_offset,
+        x_offset,
+        y,
+        y2,
+        y2_offset,
+        y_offset,
+    }
+    Projection {
+        center,
+        clip_angle,
+        clip_extent,
+        coefficient,
+        distance,
+        fraction,
+        lobes,
+        parallel,
+        precision,
+        radius,
+        ratio,
+        reflect_x,
+        reflect_y,
+        rotate,
+        scale,
+        spacing,
+        tilt,
+        translate,
+        projection_type,
+    }
+    Resolve {
+        axis,
+        legend,
+        scale,
+    }
+    AxisResolveMap {
+        x,
+        y,
+    }
+    LegendResolveMap {
+        color,
+        fill,
+        fill_opacity,
+        opacity,
+        shape,
+        size,
+        stroke,
+        stroke_opacity,
+        stroke_width,
+    }
+    ScaleResolveMap {
+        color,
+        fill,
+        fill_opacity,
+        opacity,
+        shape,
+        size,
+        stroke,
+        stroke_opacity,
+        stroke_width,
+        x,
+        y,
+    }
+    SelectionDef {
+        bind,
+        clear,
+        empty,
+        encodings,
+        fields,
+        init,
+        nearest,
+        on,
+        resolve,
+        selection_def_type,
+        toggle,
+        mark,
+        translate,
+        zoom,
+    }
+    Binding {
+        debounce,
+        element,
+        input,
+        name,
+        binding_type,
+        options,
+        max,
+        min,
+        step,
+        autocomplete,
+        placeholder,
+    }
+    BrushConfig {
+        fill,
+        fill_opacity,
+        stroke,
+        stroke_dash,
+        stroke_dash_offset,
+        stroke_opacity,
+        stroke_width,
+    }
+    TitleParams {
+        align,
+        anchor,
+        angle,
+        baseline,
+        color,
+        dx,
+        dy,
+        font,
+        font_size,
+        font_style,
+        font_weight,
+        frame,
+        limit,
+        offset,
+        orient,
+        style,
+        text,
+        zindex,
+    }
+    Transform {
+        aggregate,
+        groupby,
+        transform_as,
+        bin,
+        field,
+        calculate,
+        filter,
+        flatten,
+        fold,
+        frame,
+        impute,
+        key,
+        keyvals,
+        method,
+        value,
+        joinaggregate,
+        transform_default,
+        from,
+        lookup,
+        time_unit,
+        sample,
+        offset,
+        sort,
+        stack,
+        ignore_peers,
+        window,
+    }
+    AggregatedFieldDef {
+        aggregated_field_def_as,
+        field,
+        op,
+    }
+    LookupData {
+        data,
+        fields,
+        key,
+    }
+    Data {
+        format,
+        name,
+        url,
+        values,
+        sequence,
+        sphere,
+        graticule,
+    }
+    JoinAggregateFieldDef {
+        join_aggregate_field_def_as,
+        field,
+        op,
+    }
+    SortField {
+        field,
+        order,
+    }
+    WindowFieldDef {
+        window_field_def_as,
+        field,
+        op,
+        param,
+    }
+    ViewBackground {
+        corner_radius,
+        fill,
+        fill_opacity,
+        opacity,
+        stroke,
+        stroke_cap,
+        stroke_dash,
+        stroke_dash_offset,
+        stroke_join,
+        stroke_miter_limit,
+        stroke_opacity,
+        stroke_width,
+        style,
+    }
+    RepeatMapping {
+        column,
+        row,
+    }
+    RowColNumber {
+        column,
+        row,
+    }
+    Config {
+        area,
+        autosize,
+        axis,
+        axis_band,
+        axis_bottom,
+        axis_left,
+        axis_right,
+        axis_top,
+        axis_x,
+        axis_y,
+        background,
+        bar,
+        boxplot,
+        circle,
+        concat,
+        count_title,
+        errorband,
+        errorbar,
+        facet,
+        field_title,
+        geoshape,
+        header,
+        header_column,
+        header_facet,
+        header_row,
+        invalid_values,
+        legend,
+        line,
+        mark,
+        number_format,
+        padding,
+        point,
+        projection,
+        range,
+        rect,
+        repeat,
+        rule,
+        scale,
+        selection,
+        square,
+        stack,
+        style,
+        text,
+        tick,
+        time_format,
+        title,
+        trail,
+        view,
+    }
+    AreaConfig {
+        align,
+        angle,
+        baseline,
+        color,
+        corner_radius,
+        cursor,
+        dir,
+        dx,
+        dy,
+        ellipsis,
+        fill,
+        filled,
+        fill_opacity,
+        font,
+        font_size,
+        font_style,
+        font_weight,
+        height,
+        href,
+        interpolate,
+        limit,
+        line,
+        opacity,
+        order,
+        orient,
+        point,
+        radius,
+        shape,
+        size,
+        stroke,
+        stroke_cap,
+        stroke_dash,
+        stroke_dash_offset,
+        stroke_join,
+        stroke_miter_limit,
+        stroke_opacity,
+        stroke_width,
+        tension,
+        text,
+        theta,
+        tooltip,
+        width,
+        x,
+        x2,
+        y,
+        y2,
+    }
+    AxisConfig {
+        band_position,
+        domain,
+        domain_color,
+        domain_dash,
+        domain_dash_offset,
+        domain_opacity,
+        domain_width,
+        grid,
+        grid_color,
+        grid_dash,
+        grid_dash_offset,
+        grid_opacity,
+        grid_width,
+        label_align,
+        label_angle,
+        label_baseline,
+        label_bound,
+        label_color,
+        label_flush,
+        label_flush_offset,
+        label_font,
+        label_font_size,
+        label_font_style,
+        label_font_weight,
+        label_limit,
+        label_opacity,
+        label_overlap,
+        label_padding,
+        labels,
+        label_separation,
+        max_extent,
+        min_extent,
+        orient,
+        short_time_labels,
+        tick_color,
+        tick_dash,
+        tick_dash_offset,
+        tick_extra,
+        tick_offset,
+        tick_opacity,
+        tick_round,
+        ticks,
+        tick_size,
+        tick_width,
+        title,
+        title_align,
+        title_anchor,
+        title_angle,
+        title_baseline,
+        title_color,
+        title_font,
+        title_font_size,
+        title_font_style,
+        title_font_weight,
+        title_limit,
+        title_opacity,
+        title_padding,
+        title_x,
+        title_y,
+    }
+    RectConfig {
+        align,
+        angle,
+        baseline,
+        bin_spacing,
+        color,
+        continuous_band_size,
+        corner_radius,
+        cursor,
+        dir,
+        discrete_band_size,
+        dx,
+        dy,
+        ellipsis,
+        fill,
+        filled,
+        fill_opacity,
+        font,
+        font_size,
+        font_style,
+        font_weight,
+        height,
+        href,
+        interpolate,
+        limit,
+        opacity,
+        order,
+        orient,
+        radius,
+        shape,
+        size,
+        stroke,
+        stroke_cap,
+        stroke_dash,
+        stroke_dash_offset,
+        stroke_join,
+        stroke_miter_limit,
+        stroke_opacity,
+        stroke_width,
+        tension,
+        text,
+        theta,
+        tooltip,
+        width,
+        x,
+        x2,
+        y,
+        y2,
+    }
+    BoxPlotConfig {
+        box_plot_config_box,
+        extent,
+        median,
+        outliers,
+        rule,
+        size,
+        ticks,
+    }
+    CompositionConfig {
+        columns,
+        spacing,
+    }
+    ErrorBandConfig {
+        band,
+        borders,
+        extent,
+        interpolate,
+        tension,
+    }
+    ErrorBarConfig {
+        extent,
+        rule,
+        ticks,
+    }
+    HeaderConfig {
+        format,
+        format_type,
+        label_align,
+        label_anchor,
+        label_angle,
+        label_color,
+        label_font,
+        label_font_size,
+        label_font_style,
+        label_limit,
+        label_orient,
+        label_padding,
+        labels,
+        short_time_labels,
+        title,
+        title_align,
+        title_anchor,
+        title_angle,
+        title_baseline,
+        title_color,
+        title_font,
+        title_font_size,
+        title_font_style,
+        title_font_weight,
+        title_limit,
+        title_orient,
+        title_padding,
+    }
+    LegendConfig {
+        clip_height,
+        column_padding,
+        columns,
+        corner_radius,
+        fill_color,
+        gradient_direction,
+        gradient_horizontal_max_length,
+        gradient_horizontal_min_length,
+        gradient_label_limit,
+        gradient_label_offset,
+        gradient_length,
+        gradient_opacity,
+        gradient_stroke_color,
+        gradient_stroke_width,
+        gradient_thickness,
+        gradient_vertical_max_length,
+        gradient_vertical_min_length,
+        grid_align,
+        label_align,
+        label_baseline,
+        label_color,
+        label_font,
+        label_font_size,
+        label_font_style,
+        label_font_weight,
+        label_limit,
+        label_offset,
+        label_opacity,
+        label_overlap,
+        label_padding,
+        label_separation,
+        layout,
+        legend_x,
+        legend_y,
+        offset,
+        orient,
+        padding,
+        row_padding,
+        short_time_labels,
+        stroke_color,
+        stroke_dash,
+        stroke_width,
+        symbol_base_fill_color,
+        symbol_base_stroke_color,
+        symbol_dash,
+        symbol_dash_offset,
+        symbol_direction,
+        symbol_fill_color,
+        symbol_offset,
+        symbol_opacity,
+        symbol_size,
+        symbol_stroke_color,
+        symbol_stroke_width,
+        symbol_type,
+        title,
+        title_align,
+        title_anchor,
+        title_baseline,
+        title_color,
+        title_font,
+        title_font_size,
+        title_font_style,
+        title_font_weight,
+        title_limit,
+        title_opacity,
+        title_orient,
+        title_padding,
+    }
+    LegendLayout {
+        anchor,
+        bottom,
+        bottom_left,
+        bottom_right,
+        bounds,
+        center,
+        direction,
+        left,
+        margin,
+        offset,
+        right,
+        top,
+        top_left,
+        top_right,
+    }
+    BaseLegendLayout {
+        anchor,
+        bounds,
+        center,
+        direction,
+        margin,
+        offset,
+    }
+    SignalRef {
+        signal,
+    }
+    LineConfig {
+        align,
+        angle,
+        baseline,
+        color,
+        corner_radius,
+        cursor,
+        dir,
+        dx,
+        dy,
+        ellipsis,
+        fill,
+        filled,
+        fill_opacity,
+        font,
+        font_size,
+        font_style,
+        font_weight,
+        height,
+        href,
+        interpolate,
+        limit,
+        opacity,
+        order,
+        orient,
+        point,
+        radius,
+        shape,
+        size,
+        stroke,
+        stroke_cap,
+        stroke_dash,
+        stroke_dash_offset,
+        stroke_join,
+        stroke_miter_limit,
+        stroke_opacity,
+        stroke_width,
+        tension,
+        text,
+        theta,
+        tooltip,
+        width,
+        x,
+        x2,
+        y,
+        y2,
+    }
+    PaddingClass {
+        bottom,
+        left,
+        right,
+        top,
+    }
+    SchemeConfig {
+        count,
+        extent,
+        scheme,
+        step,
+    }
+    ScaleConfig {
+        band_padding_inner,
+        band_padding_outer,
+        bar_band_padding_inner,
+        bar_band_padding_outer,
+        clamp,
+        continuous_padding,
+        max_band_size,
+        max_font_size,
+        max_opacity,
+        max_size,
+        max_stroke_width,
+        min_band_size,
+        min_font_size,
+        min_opacity,
+        min_size,
+        min_stroke_width,
+        point_padding,
+        quantile_count,
+        quantize_count,
+        range_step,
+        rect_band_padding_inner,
+        rect_band_padding_outer,
+        round,
+        text_x_range_step,
+        use_unaggregated_domain,
+    }
+    SelectionConfig {
+        interval,
+        multi,
+        single,
+    }
+    IntervalSelectionConfig {
+        bind,
+        clear,
+        empty,
+        encodings,
+        fields,
+        init,
+        mark,
+        on,
+        resolve,
+        translate,
+        zoom,
+    }
+    MultiSelectionConfig {
+        clear,
+        empty,
+        encodings,
+        fields,
+        init,
+        nearest,
+        on,
+        resolve,
+        toggle,
+    }
+    SingleSelectionConfig {
+        bind,
+        clear,
+        empty,
+        encodings,
+        fields,
+        init,
+        nearest,
+        on,
+        resolve,
+    }
+    BaseMarkConfig {
+        align,
+        angle,
+        baseline,
+        corner_radius,
+        cursor,
+        dir,
+        dx,
+        dy,
+        ellipsis,
+        fill,
+        fill_opacity,
+        font,
+        font_size,
+        font_style,
+        font_weight,
+        height,
+        href,
+        interpolate,
+        limit,
+        opacity,
+        orient,
+        radius,
+        shape,
+        size,
+        stroke,
+        stroke_cap,
+        stroke_dash,
+        stroke_dash_offset,
+        stroke_join,
+        stroke_miter_limit,
+        stroke_opacity,
+        stroke_width,
+        tension,
+        text,
+        theta,
+        tooltip,
+        width,
+        x,
+        x2,
+        y,
+        y2,
+    }
+    TextConfig {
+        align,
+        angle,
+        baseline,
+        color,
+        corner_radius,
+        cursor,
+        dir,
+        dx,
+        dy,
+        ellipsis,
+        fill,
+        filled,
+        fill_opacity,
+        font,
+        font_size,
+        font_style,
+        font_weight,
+        height,
+        href,
+        interpolate,
+        limit,
+        opacity,
+        order,
+        orient,
+        radius,
+        shape,
+        short_time_labels,
+        size,
+        stroke,
+        stroke_cap,
+        stroke_dash,
+        stroke_dash_offset,
+        stroke_join,
+        stroke_miter_limit,
+        stroke_opacity,
+        stroke_width,
+        tension,
+        text,
+        theta,
+        tooltip,
+        width,
+        x,
+        x2,
+        y,
+        y2,
+    }
+    TickConfig {
+        align,
+        angle,
+        band_size,
+        baseline,
+        color,
+        corner_radius,
+        cursor,
+        dir,
+        dx,
+        dy,
+        ellipsis,
+        fill,
+        filled,
+        fill_opacity,
+        font,
+        font_size,
+        font_style,
+        font_weight,
+        height,
+        href,
+        interpolate,
+        limit,
+        opacity,
+        order,
+        orient,
+        radius,
+        shape,
+        size,
+        stroke,
+        stroke_cap,
+        stroke_dash,
+        stroke_dash_offset,
+        stroke_join,
+        stroke_miter_limit,
+        stroke_opacity,
+        stroke_width,
+        tension,
+        text,
+        theta,
+        thickness,
+        tooltip,
+        width,
+        x,
+        x2,
+        y,
+        y2,
+    }
+    BaseTitleConfig {
+        align,
+        anchor,
+        angle,
+        baseline,
+        color,
+        dx,
+        dy,
+        font,
+        font_size,
+        font_style,
+        font_weight,
+        frame,
+        limit,
+        offset,
+        orient,
+    }
+    ViewConfig {
+        clip,
+        corner_radius,
+        fill,
+        fill_opacity,
+        height,
+        opacity,
+        stroke,
+        stroke_cap,
+        stroke_dash,
+        stroke_dash_offset,
+        stroke_join,
+        stroke_miter_limit,
+        stroke_opacity,
+        stroke_width,
+        width,
+    }
+}
+
+merge_enums! {
+    AlignUnion {
+        Enum,
+        RowColLayoutAlign,
+    }
+    Autosize {
+        AutoSizeParams,
+        Enum,
+    }
+    VegaliteCenter {
+        Bool,
+        RowColBoolean,
+    }
+    Graticule {
+        Bool,
+        GraticuleParams,
+    }
+    SphereUnion {
+        Bool,
+        SphereClass,
+    }
+    UrlDataInlineDataset {
+        AnythingMap,
+        String,
+        UnionArray,
+    }
+    Aggregate {
+        ArgmDef,
+        Enum,
+    }
+    PurpleBin {
+        BinParams,
+        Bool,
+    }
+    ColorCondition {
+        ConditionalPredicateStringValueDefClass,
+        ConditionalStringValueDefArray,
+    }
+    SelectionOperandElement {
+        Selection,
+        String,
+    }
+    PurpleSelectionOperand {
+        Selection,
+        String,
+    }
+    LogicalOperandPredicateElement {
+        Predicate,
+        String,
+    }
+    PurpleLogicalOperandPredicate {
+        Predicate,
+        String,
+    }
+    EqualUnion {
+        Bool,
+        DateTime,
+        Double,
+        String,
+    }
+    Day {
+        Double,
+        String,
+    }
+    Month {
+        Double,
+        String,
+    }
+    Lt {
+        DateTime,
+        Double,
+        String,
+    }
+    SelectionInitIntervalElement {
+        Bool,
+        DateTime,
+        Double,
+        String,
+    }
+    RangeElement {
+        DateTime,
+        Double,
+    }
+    Field {
+        RepeatRef,
+        String,
+    }
+    FontWeight {
+        Double,
+        Enum,
+    }
+    LabelOverlap {
+        Bool,
+        Enum,
+    }
+    DomainUnion {
+        DomainClass,
+        Enum,
+        UnionArray,
+    }
+    InterpolateUnion {
+        Enum,
+        ScaleInterpolateParams,
+    }
+    NiceUnion {
+        Bool,
+        Double,
+        Enum,
+        NiceClass,
+    }
+    ScaleRange {
+        String,
+        UnionArray,
+    }
+    RangeRange {
+        Double,
+        String,
+    }
+    Scheme {
+        SchemeParams,
+        String,
+    }
+    Sort {
+        EncodingSortField,
+        Enum,
+        UnionArray,
+    }
+    SortArray {
+        Enum,
+        SortEncodingSortField,
+        UnionArray,
+    }
+    Detail {
+        TypedFieldDef,
+        TypedFieldDefArray,
+    }
+    FluffyBin {
+        BinParams,
+        Bool,
+        Enum,
+    }
+    FillOpacityCondition {
+        ConditionalNumberValueDefArray,
+        ConditionalPredicateNumberValueDefClass,
+    }
+    HrefCondition {
+        ConditionalPredicateValueDefClass,
+        ConditionalValueDefArray,
+    }
+    Value {
+        Bool,
+        Double,
+        String,
+    }
+    Order {
+        OrderFieldDefArray,
+        OrderFieldDefClass,
+    }
+    ConditionUnion {
+        Conditional,
+        ConditionalStringValueDefArray,
+    }
+    Tooltip {
+        DefWithConditionTextFieldDefValue,
+        TextFieldDefArray,
+    }
+    Label {
+        Bool,
+        Double,
+    }
+    Keyvals {
+        AnythingArray,
+        ImputeSequence,
+    }
+    Stack {
+        Bool,
+        Enum,
+    }
+    XUnion {
+        Double,
+        Enum,
+    }
+    YUnion {
+        Double,
+        Enum,
+    }
+    AnyMark {
+        MarkDefClass,
+        Enum,
+    }
+    DefBox {
+        Bool,
+        MarkConfig,
+    }
+    TooltipUnion {
+        Bool,
+        Double,
+        String,
+        TooltipContent,
+    }
+    BoxPlotDefExtent {
+        Double,
+        Enum,
+    }
+    Line {
+        Bool,
+        OverlayMarkDef,
+    }
+    Style {
+        String,
+        StringArray,
+    }
+    PointUnion {
+        Bool,
+        Enum,
+        OverlayMarkDef,
+    }
+    SelectionDefBind {
+        Enum,
+        UnionMap,
+    }
+    BindValue {
+        AnythingArray,
+        Binding,
+        Double,
+        String,
+    }
+    SelectionDefInit {
+        UnionMap,
+        UnionMapArray,
+    }
+    InitSelectionInitMapping {
+        Bool,
+        DateTime,
+        Double,
+        String,
+        UnionArray,
+    }
+    Translate {
+        Bool,
+        String,
+    }
+    Title {
+        String,
+        TitleParams,
+    }
+    RepeatUnion {
+        RepeatMapping,
+        StringArray,
+    }
+    Spacing {
+        Double,
+        RowColNumber,
+    }
+    BoxplotExtent {
+        Double,
+        Enum,
+    }
+    LayoutBounds {
+        Enum,
+        SignalRef,
+    }
+    BottomCenter {
+        Bool,
+        SignalRef,
+    }
+    Direction {
+        Enum,
+        SignalRef,
+    }
+    Margin {
+        Double,
+        SignalRef,
+    }
+    Padding {
+        Double,
+        PaddingClass,
+    }
+    RangeValue {
+        SchemeConfig,
+        UnionArray,
+    }
+    MultiInit {
+        UnionMap,
+        UnionMapArray,
+    }
+    InlineDatasetValue {
+        AnythingMap,
+        String,
+        UnionArray,
+    }
+    LayoutAlign {}
+    AutosizeType {}
+    Contains {}
+    BoundsEnum {}
+    DataFormatType {}
+    AggregateOp {}
+    TimeUnit {}
+    StandardType {}
+    RepeatEnum {}
+    Orientation {}
+    FormatType {}
+    Align {}
+    TextBaseline {}
+    FontWeightEnum {}
+    LabelOverlapEnum {}
+    LegendType {}
+    LegendOrient {}
+    TitleAnchor {}
+    Orient {}
+    Domain {}
+    ScaleInterpolateParamsType {}
+    ScaleInterpolate {}
+    NiceTime {}
+    ScaleType {}
+    SingleDefUnitChannel {}
+    SortOrder {}
+    BinEnum {}
+    LatitudeType {}
+    TypeForShape {}
+    ImputeMethod {}
+    StackOffset {}
+    XEnum {}
+    YEnum {}
+    Cursor {}
+    Dir {}
+    Interpolate {}
+    StrokeCap {}
+    StrokeJoin {}
+    Content {}
+    Mark {}
+    ExtentExtent {}
+    PointEnum {}
+    ProjectionType {}
+    ResolveMode {}
+    BindEnum {}
+    Empty {}
+    SelectionResolution {}
+    SelectionDefType {}
+    TitleFrame {}
+    TitleOrient {}
+    Op {}
+    ExtentEnum {}
+    ErrorbandExtent {}
+    FieldTitle {}
+    InvalidValues {}
+}
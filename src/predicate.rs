@@ -0,0 +1,451 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Combinators to build the [`Predicate`](../struct.Predicate.html) tree used by
+//! `Transform.filter` and by the `test` and `selection` of conditional encodings.
+//!
+//! ```
+//! use vega_lite_3::predicate::*;
+//! use vega_lite_3::*;
+//!
+//! let filter = field("Horsepower").gt(100.0) & !field("Origin").one_of(vec!["USA", "Japan"])
+//!     | selection("brush");
+//! let transform = TransformBuilder::default().filter(filter).build().unwrap();
+//! ```
+
+use crate::expr::Expr;
+use crate::schema::*;
+use std::ops;
+
+/// A predicate, that can be used as a filter or as the `test` of a condition
+#[derive(Debug, Clone)]
+pub struct Pred(PurpleLogicalOperandPredicate);
+
+/// A selection predicate, that can also be used as the `selection` of a condition
+#[derive(Debug, Clone)]
+pub struct SelectionPred(PurpleSelectionOperand);
+
+/// A field to test in a field predicate, built with [`field`](fn.field.html)
+#[derive(Debug, Clone)]
+pub struct FieldPred {
+    field: String,
+    time_unit: Option<TimeUnit>,
+}
+
+/// Test the value of a field
+pub fn field(name: &str) -> FieldPred {
+    FieldPred {
+        field: name.to_string(),
+        time_unit: None,
+    }
+}
+
+/// Test whether a data value is included in a selection
+pub fn selection(name: &str) -> SelectionPred {
+    SelectionPred(PurpleSelectionOperand::String(name.to_string()))
+}
+
+/// Test a [Vega expression](../expr/index.html)
+pub fn test(expr: impl Into<Expr>) -> Pred {
+    Pred(expr.into().into())
+}
+
+impl FieldPred {
+    /// Apply a time unit to the field value before testing it
+    pub fn time_unit(mut self, time_unit: TimeUnit) -> Self {
+        self.time_unit = Some(time_unit);
+        self
+    }
+
+    fn predicate(self, set: impl FnOnce(&mut Predicate)) -> Pred {
+        let mut predicate = Predicate {
+            field: Some(self.field),
+            time_unit: self.time_unit,
+            ..Default::default()
+        };
+        set(&mut predicate);
+        Pred(predicate.into())
+    }
+
+    /// The field is equal to `value`
+    pub fn eq(self, value: impl Into<EqualUnion>) -> Pred {
+        self.predicate(|p| p.equal = Some(value.into()))
+    }
+
+    /// The field is less than `value`
+    pub fn lt(self, value: impl Into<Lt>) -> Pred {
+        self.predicate(|p| p.lt = Some(value.into()))
+    }
+
+    /// The field is less than or equal to `value`
+    pub fn lte(self, value: impl Into<Lt>) -> Pred {
+        self.predicate(|p| p.lte = Some(value.into()))
+    }
+
+    /// The field is greater than `value`
+    pub fn gt(self, value: impl Into<Lt>) -> Pred {
+        self.predicate(|p| p.gt = Some(value.into()))
+    }
+
+    /// The field is greater than or equal to `value`
+    pub fn gte(self, value: impl Into<Lt>) -> Pred {
+        self.predicate(|p| p.gte = Some(value.into()))
+    }
+
+    /// The field is between `min` and `max`, inclusive
+    pub fn range(self, min: impl Into<RangeElement>, max: impl Into<RangeElement>) -> Pred {
+        self.predicate(|p| p.range = Some(vec![Some(min.into()), Some(max.into())]))
+    }
+
+    /// The field is one of `values`
+    pub fn one_of<T: Into<SelectionInitIntervalElement>>(self, values: Vec<T>) -> Pred {
+        self.predicate(|p| p.one_of = Some(values.into_iter().map(Into::into).collect()))
+    }
+
+    /// The field is neither `null` nor `NaN`
+    pub fn valid(self) -> Pred {
+        self.predicate(|p| p.valid = Some(true))
+    }
+}
+
+fn into_element(predicate: PurpleLogicalOperandPredicate) -> LogicalOperandPredicateElement {
+    match predicate {
+        PurpleLogicalOperandPredicate::Predicate(p) => LogicalOperandPredicateElement::Predicate(p),
+        PurpleLogicalOperandPredicate::String(s) => LogicalOperandPredicateElement::String(s),
+    }
+}
+
+fn into_selection_element(selection: PurpleSelectionOperand) -> SelectionOperandElement {
+    match selection {
+        PurpleSelectionOperand::Selection(s) => SelectionOperandElement::Selection(s),
+        PurpleSelectionOperand::String(s) => SelectionOperandElement::String(s),
+    }
+}
+
+/// Operands of a predicate that only sets `and` (or `or`), to flatten nested compositions
+fn logical_operands(
+    predicate: &PurpleLogicalOperandPredicate,
+    and: bool,
+) -> Option<Vec<LogicalOperandPredicateElement>> {
+    match predicate {
+        PurpleLogicalOperandPredicate::Predicate(Predicate {
+            not: None,
+            and: and_operands,
+            or: or_operands,
+            equal: None,
+            field: None,
+            time_unit: None,
+            range: None,
+            one_of: None,
+            lt: None,
+            gt: None,
+            lte: None,
+            gte: None,
+            valid: None,
+            selection: None,
+        }) => match (and_operands, or_operands) {
+            (Some(operands), None) if and => Some(operands.clone()),
+            (None, Some(operands)) if !and => Some(operands.clone()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+impl Pred {
+    fn combine(self, other: Pred, and: bool) -> Pred {
+        let mut operands = vec![];
+        for predicate in [self.0, other.0] {
+            match logical_operands(&predicate, and) {
+                Some(nested) => operands.extend(nested),
+                None => operands.push(into_element(predicate)),
+            }
+        }
+        let predicate = if and {
+            Predicate {
+                and: Some(operands),
+                ..Default::default()
+            }
+        } else {
+            Predicate {
+                or: Some(operands),
+                ..Default::default()
+            }
+        };
+        Pred(predicate.into())
+    }
+}
+
+impl<T: Into<Pred>> ops::BitAnd<T> for Pred {
+    type Output = Pred;
+
+    fn bitand(self, other: T) -> Pred {
+        self.combine(other.into(), true)
+    }
+}
+
+impl<T: Into<Pred>> ops::BitOr<T> for Pred {
+    type Output = Pred;
+
+    fn bitor(self, other: T) -> Pred {
+        self.combine(other.into(), false)
+    }
+}
+
+impl ops::Not for Pred {
+    type Output = Pred;
+
+    fn not(self) -> Pred {
+        Pred(
+            Predicate {
+                not: Some(Box::new(self.0)),
+                ..Default::default()
+            }
+            .into(),
+        )
+    }
+}
+
+impl SelectionPred {
+    fn combine(self, other: SelectionPred, and: bool) -> SelectionPred {
+        let mut operands = vec![];
+        for selection in [self.0, other.0] {
+            match selection {
+                PurpleSelectionOperand::Selection(Selection {
+                    not: None,
+                    and: Some(nested),
+                    or: None,
+                }) if and => operands.extend(nested),
+                PurpleSelectionOperand::Selection(Selection {
+                    not: None,
+                    and: None,
+                    or: Some(nested),
+                }) if !and => operands.extend(nested),
+                selection => operands.push(into_selection_element(selection)),
+            }
+        }
+        let selection = if and {
+            Selection {
+                and: Some(operands),
+                ..Default::default()
+            }
+        } else {
+            Selection {
+                or: Some(operands),
+                ..Default::default()
+            }
+        };
+        SelectionPred(selection.into())
+    }
+}
+
+impl ops::BitAnd for SelectionPred {
+    type Output = SelectionPred;
+
+    fn bitand(self, other: SelectionPred) -> SelectionPred {
+        self.combine(other, true)
+    }
+}
+
+impl ops::BitOr for SelectionPred {
+    type Output = SelectionPred;
+
+    fn bitor(self, other: SelectionPred) -> SelectionPred {
+        self.combine(other, false)
+    }
+}
+
+impl ops::BitAnd<Pred> for SelectionPred {
+    type Output = Pred;
+
+    fn bitand(self, other: Pred) -> Pred {
+        Pred::from(self) & other
+    }
+}
+
+impl ops::BitOr<Pred> for SelectionPred {
+    type Output = Pred;
+
+    fn bitor(self, other: Pred) -> Pred {
+        Pred::from(self) | other
+    }
+}
+
+impl ops::Not for SelectionPred {
+    type Output = SelectionPred;
+
+    fn not(self) -> SelectionPred {
+        SelectionPred(
+            Selection {
+                not: Some(Box::new(self.0)),
+                ..Default::default()
+            }
+            .into(),
+        )
+    }
+}
+
+impl From<SelectionPred> for Pred {
+    fn from(v: SelectionPred) -> Self {
+        Pred(
+            Predicate {
+                selection: Some(Box::new(v.0)),
+                ..Default::default()
+            }
+            .into(),
+        )
+    }
+}
+
+impl From<Expr> for Pred {
+    fn from(v: Expr) -> Self {
+        test(v)
+    }
+}
+
+impl From<Predicate> for Pred {
+    fn from(v: Predicate) -> Self {
+        Pred(v.into())
+    }
+}
+
+impl From<Pred> for PurpleLogicalOperandPredicate {
+    fn from(v: Pred) -> Self {
+        v.0
+    }
+}
+
+impl From<Pred> for Box<PurpleLogicalOperandPredicate> {
+    fn from(v: Pred) -> Self {
+        Box::new(v.0)
+    }
+}
+
+impl From<Pred> for LogicalOperandPredicateElement {
+    fn from(v: Pred) -> Self {
+        into_element(v.0)
+    }
+}
+
+impl From<SelectionPred> for PurpleLogicalOperandPredicate {
+    fn from(v: SelectionPred) -> Self {
+        Pred::from(v).0
+    }
+}
+
+impl From<SelectionPred> for Box<PurpleLogicalOperandPredicate> {
+    fn from(v: SelectionPred) -> Self {
+        Box::new(v.into())
+    }
+}
+
+impl From<SelectionPred> for PurpleSelectionOperand {
+    fn from(v: SelectionPred) -> Self {
+        v.0
+    }
+}
+
+impl From<SelectionPred> for Box<PurpleSelectionOperand> {
+    fn from(v: SelectionPred) -> Self {
+        Box::new(v.0)
+    }
+}
+
+impl From<SelectionPred> for SelectionOperandElement {
+    fn from(v: SelectionPred) -> Self {
+        into_selection_element(v.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::expr::datum;
+    use serde_json::json;
+
+    fn to_json(predicate: impl Into<PurpleLogicalOperandPredicate>) -> serde_json::Value {
+        serde_json::to_value(predicate.into()).unwrap()
+    }
+
+    #[test]
+    fn test_field_predicates() {
+        assert_eq!(
+            to_json(field("x").gt(3.0)),
+            json!({"field": "x", "gt": 3.0})
+        );
+        assert_eq!(
+            to_json(field("date").time_unit(TimeUnit::Year).eq(2000.0)),
+            json!({"field": "date", "timeUnit": "year", "equal": 2000.0})
+        );
+        assert_eq!(
+            to_json(field("x").range(0.0, 100.0)),
+            json!({"field": "x", "range": [0.0, 100.0]})
+        );
+        assert_eq!(
+            to_json(field("c").one_of(vec![4.0, 6.0])),
+            json!({"field": "c", "oneOf": [4.0, 6.0]})
+        );
+        assert_eq!(
+            to_json(field("x").valid()),
+            json!({"field": "x", "valid": true})
+        );
+    }
+
+    #[test]
+    fn test_composition() {
+        assert_eq!(
+            to_json(field("x").gt(3.0) & selection("brush") & test(datum("y").lt(2))),
+            json!({"and": [
+                {"field": "x", "gt": 3.0},
+                {"selection": "brush"},
+                "datum.y < 2"
+            ]})
+        );
+        assert_eq!(
+            to_json(!(field("a").eq("b") | field("a").eq("c"))),
+            json!({"not": {"or": [
+                {"field": "a", "equal": "b"},
+                {"field": "a", "equal": "c"}
+            ]}})
+        );
+        assert_eq!(
+            serde_json::to_value(PurpleSelectionOperand::from(
+                selection("a") & !selection("b")
+            ))
+            .unwrap(),
+            json!({"and": ["a", {"not": "b"}]})
+        );
+    }
+
+    #[test]
+    fn test_into_schema() {
+        let condition = ConditionalPredicateStringValueDefClassBuilder::default()
+            .selection(selection("brush") | selection("click"))
+            .field("weather")
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(condition).unwrap(),
+            json!({"selection": {"or": ["brush", "click"]}, "field": "weather"})
+        );
+
+        let condition = ConditionalPredicateStringValueDefClassBuilder::default()
+            .test(field("x").valid())
+            .value("black")
+            .build()
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(condition).unwrap(),
+            json!({"test": {"field": "x", "valid": true}, "value": "black"})
+        );
+    }
+}
@@ -0,0 +1,579 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Complete specifications of common statistical charts, from a data source and the names of
+//! its fields.
+//!
+//! The kernel density estimates, quantile-quantile plots and correlation matrices are computed
+//! from the inline values of the data, Vega-Lite 3 having no transform for them.
+//!
+//! ```
+//! use vega_lite_3::recipes;
+//! use vega_lite_3::*;
+//!
+//! let data = UrlData {
+//!     url: Some("data/movies.json".to_string()),
+//!     ..Default::default()
+//! };
+//! let bin = BinParams {
+//!     maxbins: Some(20.0),
+//!     ..Default::default()
+//! };
+//! let chart = recipes::histogram(data, "IMDB_Rating", bin);
+//! ```
+
+use crate::color::Diverging;
+use crate::error::Error;
+use crate::eval::{field_path, get_path, inline_rows, quantile, set};
+use crate::removable_value::RemovableValue;
+use crate::schema::*;
+use serde_json::{json, Value};
+
+/// The number of points at which a density is estimated
+const DENSITY_STEPS: usize = 100;
+
+fn field(name: &str) -> Option<Field> {
+    Some(Field::String(name.to_string()))
+}
+
+fn count() -> Option<Aggregate> {
+    Some(Aggregate::Enum(AggregateOp::Count))
+}
+
+fn x(name: &str, field_type: StandardType) -> XClass {
+    XClass {
+        field: field(name),
+        def_type: Some(field_type),
+        ..Default::default()
+    }
+}
+
+fn y(name: &str, field_type: StandardType) -> YClass {
+    YClass {
+        field: field(name),
+        def_type: Some(field_type),
+        ..Default::default()
+    }
+}
+
+fn inline(rows: Vec<Value>) -> UrlData {
+    UrlData {
+        values: Some(UrlDataInlineDataset::UnionArray(rows)),
+        ..Default::default()
+    }
+}
+
+/// The rows of inline data, as read by Vega-Lite
+pub(crate) fn rows(data: &UrlData) -> Result<Vec<Value>, Error> {
+    inline_rows(data).ok_or_else(|| {
+        Error::Validation("this chart is computed from inline data rows".to_string())
+    })
+}
+
+/// The value of a numeric field in each row of inline data, `None` when it is missing
+pub(crate) fn column(rows: &[Value], name: &str) -> Result<Vec<Option<f64>>, Error> {
+    let path = field_path(name);
+    rows.iter()
+        .enumerate()
+        .map(|(i, row)| match get_path(row, &path) {
+            None | Some(Value::Null) => Ok(None),
+            Some(value) => value.as_f64().map(Some).ok_or_else(|| {
+                Error::Validation(format!("`{}` is not a number in row {}", name, i))
+            }),
+        })
+        .collect()
+}
+
+/// Fail when the values of a field would be overwritten by computed fields
+fn check_computed(name: &str, computed: &[&str]) -> Result<(), Error> {
+    let property = &field_path(name)[0];
+    if computed.contains(&property.as_str()) {
+        return Err(Error::Validation(format!(
+            "`{}` is the name of a computed field",
+            property
+        )));
+    }
+    Ok(())
+}
+
+/// A row of computed fields, with the value of a field nested along its path
+fn computed_row(name: &str, value: f64, computed: &[(&str, f64)]) -> Value {
+    let mut row = field_path(name)
+        .iter()
+        .rev()
+        .fold(json!(value), |value, property| json!({ property: value }));
+    for (property, value) in computed {
+        set(&mut row, property, json!(value));
+    }
+    row
+}
+
+/// The values of a numeric field of inline data, sorted
+fn sorted(data: &UrlData, name: &str) -> Result<Vec<f64>, Error> {
+    let mut values = column(&rows(data)?, name)?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    if values.len() < 2 {
+        return Err(Error::Validation(format!(
+            "`{}` needs at least two values",
+            name
+        )));
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).expect("json numbers are not NaN"));
+    Ok(values)
+}
+
+fn mean_and_deviation(values: &[f64]) -> (f64, f64) {
+    let n = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (n - 1.0);
+    (mean, variance.sqrt())
+}
+
+/// The quantile function of the standard normal distribution, with the rational approximation
+/// of Peter Acklam
+pub(crate) fn normal_quantile(p: f64) -> f64 {
+    const A: [f64; 6] = [
+        -3.969_683_028_665_376e1,
+        2.209_460_984_245_205e2,
+        -2.759_285_104_469_687e2,
+        1.383_577_518_672_69e2,
+        -3.066_479_806_614_716e1,
+        2.506_628_277_459_239,
+    ];
+    const B: [f64; 5] = [
+        -5.447_609_879_822_406e1,
+        1.615_858_368_580_409e2,
+        -1.556_989_798_598_866e2,
+        6.680_131_188_771_972e1,
+        -1.328_068_155_288_572e1,
+    ];
+    const C: [f64; 6] = [
+        -7.784_894_002_430_293e-3,
+        -3.223_964_580_411_365e-1,
+        -2.400_758_277_161_838,
+        -2.549_732_539_343_734,
+        4.374_664_141_464_968,
+        2.938_163_982_698_783,
+    ];
+    const D: [f64; 4] = [
+        7.784_695_709_041_462e-3,
+        3.224_671_290_700_398e-1,
+        2.445_134_137_142_996,
+        3.754_408_661_907_416,
+    ];
+    let polynomial = |coefficients: &[f64], x: f64| {
+        coefficients
+            .iter()
+            .fold(0.0, |sum, coefficient| sum * x + coefficient)
+    };
+    let tail = |p: f64| {
+        let q = (-2.0 * p.ln()).sqrt();
+        polynomial(&C, q) / (polynomial(&D, q) * q + 1.0)
+    };
+    if p < 0.02425 {
+        tail(p)
+    } else if p > 1.0 - 0.02425 {
+        -tail(1.0 - p)
+    } else {
+        let q = p - 0.5;
+        let r = q * q;
+        polynomial(&A, r) * q / (polynomial(&B, r) * r + 1.0)
+    }
+}
+
+/// A histogram of the `field` values, counted in bins
+pub fn histogram(
+    data: impl Into<RemovableValue<UrlData>>,
+    field: &str,
+    bin: BinParams,
+) -> Vegalite {
+    Vegalite {
+        data: data.into(),
+        mark: Some(Mark::Bar.into()),
+        encoding: Some(Encoding {
+            x: Some(XClass {
+                bin: Some(FluffyBin::BinParams(bin)),
+                ..x(field, StandardType::Quantitative)
+            }),
+            y: Some(YClass {
+                aggregate: count(),
+                def_type: Some(StandardType::Quantitative),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// A heatmap of the number of data points in 2-D bins of the `x` and `y` values
+pub fn heatmap(
+    data: impl Into<RemovableValue<UrlData>>,
+    (x_field, x_bin): (&str, BinParams),
+    (y_field, y_bin): (&str, BinParams),
+) -> Vegalite {
+    Vegalite {
+        data: data.into(),
+        mark: Some(Mark::Rect.into()),
+        encoding: Some(Encoding {
+            x: Some(XClass {
+                bin: Some(FluffyBin::BinParams(x_bin)),
+                ..x(x_field, StandardType::Quantitative)
+            }),
+            y: Some(YClass {
+                bin: Some(FluffyBin::BinParams(y_bin)),
+                ..y(y_field, StandardType::Quantitative)
+            }),
+            color: Some(DefWithConditionMarkPropFieldDefStringNull {
+                aggregate: count(),
+                def_with_condition_mark_prop_field_def_string_null_type: Some(
+                    StandardType::Quantitative,
+                ),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// The kernel density estimate of the `field` values of inline data, with a gaussian kernel
+/// of the given bandwidth or, by default, of the bandwidth given by Silverman's rule of thumb.
+/// The estimates are in a `density` field.
+pub fn density(data: &UrlData, field: &str, bandwidth: Option<f64>) -> Result<Vegalite, Error> {
+    check_computed(field, &["density"])?;
+    let values = sorted(data, field)?;
+    let n = values.len() as f64;
+    let bandwidth = bandwidth.unwrap_or_else(|| {
+        let (_, deviation) = mean_and_deviation(&values);
+        let iqr = quantile(&values, 0.75)
+            .zip(quantile(&values, 0.25))
+            .map_or(0.0, |(q3, q1)| q3 - q1);
+        let spread = if iqr > 0.0 {
+            deviation.min(iqr / 1.34)
+        } else {
+            deviation
+        };
+        1.06 * spread * n.powf(-0.2)
+    });
+    if bandwidth.is_nan() || bandwidth <= 0.0 {
+        return Err(Error::Validation(format!(
+            "the density of `{}` needs a positive bandwidth",
+            field
+        )));
+    }
+    let (min, max) = (values[0], values[values.len() - 1]);
+    let rows = (0..DENSITY_STEPS)
+        .map(|step| {
+            let at = min + (max - min) * step as f64 / (DENSITY_STEPS - 1) as f64;
+            let density = values
+                .iter()
+                .map(|value| (-0.5 * ((at - value) / bandwidth).powi(2)).exp())
+                .sum::<f64>()
+                / (n * bandwidth * (2.0 * std::f64::consts::PI).sqrt());
+            computed_row(field, at, &[("density", density)])
+        })
+        .collect();
+    Ok(Vegalite {
+        data: inline(rows).into(),
+        mark: Some(Mark::Area.into()),
+        encoding: Some(Encoding {
+            x: Some(x(field, StandardType::Quantitative)),
+            y: Some(y("density", StandardType::Quantitative)),
+            ..Default::default()
+        }),
+        ..Default::default()
+    })
+}
+
+/// The empirical cumulative distribution function of the `field` values, as a step line
+pub fn ecdf(data: impl Into<RemovableValue<UrlData>>, field: &str) -> Vegalite {
+    Vegalite {
+        data: data.into(),
+        transform: Some(vec![Transform::window(vec![WindowFieldDef {
+            op: Some(Op::CumeDist),
+            window_field_def_as: Some("ecdf".to_string()),
+            ..Default::default()
+        }])
+        .sort(vec![SortField {
+            field: Some(field.to_string()),
+            ..Default::default()
+        }])
+        .into()]),
+        mark: Some(
+            MarkDefClass {
+                def_type: Some(Mark::Line),
+                interpolate: Some(Interpolate::StepAfter),
+                ..Default::default()
+            }
+            .into(),
+        ),
+        encoding: Some(Encoding {
+            x: Some(x(field, StandardType::Quantitative)),
+            y: Some(y("ecdf", StandardType::Quantitative)),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+}
+
+/// A normal quantile-quantile plot of the `field` values of inline data, with the line of the
+/// normal distribution of the same mean and standard deviation. The quantiles of the normal
+/// distribution are in a `theoretical` field and the values on its line in a `normal` field.
+pub fn qq(data: &UrlData, field: &str) -> Result<Vegalite, Error> {
+    check_computed(field, &["theoretical", "normal"])?;
+    let values = sorted(data, field)?;
+    let (mean, deviation) = mean_and_deviation(&values);
+    let n = values.len() as f64;
+    let rows = values
+        .iter()
+        .enumerate()
+        .map(|(i, value)| {
+            let theoretical = normal_quantile((i as f64 + 0.5) / n);
+            computed_row(
+                field,
+                *value,
+                &[
+                    ("theoretical", theoretical),
+                    ("normal", mean + deviation * theoretical),
+                ],
+            )
+        })
+        .collect();
+    let layer = |mark: Mark, y_field: &str| LayerSpec {
+        mark: Some(mark.into()),
+        encoding: Some(LayerEncoding {
+            y: Some(YClass {
+                title: field.to_string().into(),
+                ..y(y_field, StandardType::Quantitative)
+            }),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    Ok(Vegalite {
+        data: inline(rows).into(),
+        encoding: Some(Encoding {
+            x: Some(x("theoretical", StandardType::Quantitative)),
+            ..Default::default()
+        }),
+        layer: Some(vec![layer(Mark::Point, field), layer(Mark::Line, "normal")]),
+        ..Default::default()
+    })
+}
+
+/// The matrix of the Pearson correlations between the numeric `fields` of inline data, each
+/// one computed from the rows where both fields are set
+pub fn correlation(data: &UrlData, fields: &[&str]) -> Result<Vegalite, Error> {
+    let rows = rows(data)?;
+    let columns = fields
+        .iter()
+        .map(|name| column(&rows, name))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut matrix = Vec::new();
+    for (a, column_a) in fields.iter().zip(&columns) {
+        for (b, column_b) in fields.iter().zip(&columns) {
+            let pairs = column_a
+                .iter()
+                .zip(column_b)
+                .filter_map(|pair| match pair {
+                    (Some(a), Some(b)) => Some((*a, *b)),
+                    _ => None,
+                })
+                .collect::<Vec<_>>();
+            let (a_values, b_values): (Vec<_>, Vec<_>) = pairs.iter().cloned().unzip();
+            let (a_mean, a_deviation) = mean_and_deviation(&a_values);
+            let (b_mean, b_deviation) = mean_and_deviation(&b_values);
+            let covariance = pairs
+                .iter()
+                .map(|(a, b)| (a - a_mean) * (b - b_mean))
+                .sum::<f64>()
+                / (pairs.len() as f64 - 1.0);
+            let correlation = covariance / (a_deviation * b_deviation);
+            if !correlation.is_finite() {
+                return Err(Error::Validation(format!(
+                    "the correlation of `{}` and `{}` needs at least two distinct values of each",
+                    a, b
+                )));
+            }
+            matrix.push(json!({"x": a, "y": b, "correlation": correlation}));
+        }
+    }
+    let nominal_x = XClass {
+        title: RemovableValue::Remove,
+        ..x("x", StandardType::Nominal)
+    };
+    let nominal_y = YClass {
+        title: RemovableValue::Remove,
+        ..y("y", StandardType::Nominal)
+    };
+    Ok(Vegalite {
+        data: inline(matrix).into(),
+        encoding: Some(Encoding {
+            x: Some(nominal_x),
+            y: Some(nominal_y),
+            ..Default::default()
+        }),
+        layer: Some(vec![
+            LayerSpec {
+                mark: Some(Mark::Rect.into()),
+                encoding: Some(LayerEncoding {
+                    color: Some(DefWithConditionMarkPropFieldDefStringNull {
+                        field: field("correlation"),
+                        def_with_condition_mark_prop_field_def_string_null_type: Some(
+                            StandardType::Quantitative,
+                        ),
+                        scale: Scale {
+                            domain: Some(DomainUnion::UnionArray(vec![(-1.0).into(), 1.0.into()])),
+                            scheme: Some(Diverging::RedBlue.scheme()),
+                            ..Default::default()
+                        }
+                        .into(),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+            LayerSpec {
+                mark: Some(Mark::Text.into()),
+                encoding: Some(LayerEncoding {
+                    text: Some(HrefClass {
+                        field: field("correlation"),
+                        def_with_condition_text_field_def_value_type: Some(
+                            StandardType::Quantitative,
+                        ),
+                        format: Some(".2f".to_string()),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            },
+        ]),
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(rows: Value) -> UrlData {
+        serde_json::from_value(json!({ "values": rows })).unwrap()
+    }
+
+    #[test]
+    fn test_binned() {
+        let bin = BinParams {
+            maxbins: Some(10.0),
+            ..Default::default()
+        };
+        let chart = heatmap(
+            data(json!([])),
+            ("a", bin.clone()),
+            ("b", BinParams::default()),
+        );
+        assert_eq!(
+            serde_json::to_value(chart.encoding).unwrap(),
+            json!({
+                "x": {"field": "a", "type": "quantitative", "bin": {"maxbins": 10.0}},
+                "y": {"field": "b", "type": "quantitative", "bin": {}},
+                "color": {"aggregate": "count", "type": "quantitative"}
+            })
+        );
+        let chart = histogram(data(json!([])), "a", bin);
+        assert_eq!(
+            serde_json::to_value(chart.encoding.unwrap().y).unwrap(),
+            json!({"aggregate": "count", "type": "quantitative"})
+        );
+        let chart = ecdf(data(json!([])), "a");
+        assert_eq!(
+            serde_json::to_value(chart.transform).unwrap(),
+            json!([{"window": [{"op": "cume_dist", "as": "ecdf"}], "sort": [{"field": "a"}]}])
+        );
+    }
+
+    /// The rows computed for a chart, the chart being dropped in its own stack frame
+    fn computed(chart: impl FnOnce() -> Result<Vegalite, Error>) -> Result<Vec<Value>, Error> {
+        let chart = serde_json::to_value(chart()?)?;
+        Ok(chart["data"]["values"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    #[test]
+    fn test_computed() {
+        let values = data(json!([
+            {"a": 1, "b": 2, "c": 5},
+            {"a": 2, "b": 4, "c": 1},
+            {"a": 3, "b": null, "c": 3},
+            {"a": 4, "b": 8, "c": 2}
+        ]));
+        let rows = computed(|| density(&values, "a", None)).unwrap();
+        assert_eq!(rows.len(), DENSITY_STEPS);
+        assert_eq!(rows[0]["a"], json!(1.0));
+        let area = rows
+            .iter()
+            .map(|row| row["density"].as_f64().unwrap())
+            .sum::<f64>()
+            * 3.0
+            / (DENSITY_STEPS - 1) as f64;
+        assert!(area > 0.6 && area < 1.0, "{}", area);
+
+        let rows = computed(|| qq(&values, "b")).unwrap();
+        assert_eq!(rows.len(), 3);
+        assert!((rows[1]["theoretical"].as_f64().unwrap()).abs() < 1e-9);
+        assert!((rows[0]["theoretical"].as_f64().unwrap() + 0.967_421_566).abs() < 1e-6);
+        assert_eq!(rows[1]["normal"], json!(14.0 / 3.0));
+
+        let correlations = computed(|| correlation(&values, &["a", "b", "c"]))
+            .unwrap()
+            .iter()
+            .map(|row| (row["correlation"].as_f64().unwrap() * 1000.0).round() / 1000.0)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            correlations,
+            vec![1.0, 1.0, -0.529, 1.0, 1.0, -0.577, -0.529, -0.577, 1.0]
+        );
+
+        assert!(computed(|| density(&data(json!([{"a": "x"}, {"a": 1}])), "a", None)).is_err());
+        assert!(computed(|| qq(&data(json!([{"a": 1}])), "a")).is_err());
+        assert!(computed(|| correlation(
+            &data(json!([{"a": 1, "b": 1}, {"a": 2, "b": 1}])),
+            &["a", "b"]
+        ))
+        .is_err());
+        let url = UrlData {
+            url: Some("data.csv".to_string()),
+            ..Default::default()
+        };
+        assert!(computed(|| density(&url, "a", Some(1.0))).is_err());
+    }
+
+    #[test]
+    fn test_computed_fields() {
+        let values = data(json!([{"a": {"b": 1}}, {"a": {"b": 3}}, {"a": {"c": 2}}]));
+        let rows = computed(|| qq(&values, "a.b")).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1]["a"], json!({"b": 3.0}));
+        let rows = computed(|| density(&data(json!([1, 2, 4])), "data", None)).unwrap();
+        assert_eq!(rows[0]["data"], json!(1.0));
+
+        let values = data(json!([{"density": 1}, {"density": 2}]));
+        assert!(computed(|| density(&values, "density", None)).is_err());
+        let values = data(json!([{"normal": {"value": 1}}, {"normal": {"value": 2}}]));
+        assert!(computed(|| qq(&values, "normal.value")).is_err());
+    }
+}
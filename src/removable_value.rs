@@ -15,12 +15,14 @@ use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use std::fmt;
 use std::marker::PhantomData;
 
+use crate::color::Color;
 use crate::schema::*;
 
 /// Wrapper for a field that can be either the default value, null or specified
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Default)]
 pub enum RemovableValue<T: Clone> {
     /// The default value for this field
+    #[default]
     Default,
     /// This field should be removed
     Remove,
@@ -41,10 +43,7 @@ pub enum RemovableValue<T: Clone> {
 
 impl<T: Clone> RemovableValue<T> {
     pub(crate) fn is_default(&self) -> bool {
-        match self {
-            RemovableValue::Default => true,
-            _ => false,
-        }
+        matches!(self, RemovableValue::Default)
     }
 }
 
@@ -69,6 +68,7 @@ macro_rules! from_into_with_removable{
 
 from_into_with_removable! {
     &str => String,
+    Color => String,
 
     SortOrder => Sort,
     EncodingSortField => Sort,
@@ -83,12 +83,6 @@ from_into_with_removable! {
     TooltipContent => TooltipUnion,
 }
 
-impl<T: Clone> Default for RemovableValue<T> {
-    fn default() -> Self {
-        RemovableValue::Default
-    }
-}
-
 impl<T> Serialize for RemovableValue<T>
 where
     T: Serialize + Clone,
@@ -169,3 +163,16 @@ where
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removable_value_round_trip() {
+        for json in &[r#"{}"#, r#"{"stroke":null}"#, r#"{"stroke":"red"}"#] {
+            let view: ViewConfig = serde_json::from_str(json).unwrap();
+            assert_eq!(&serde_json::to_string(&view).unwrap(), json);
+        }
+    }
+}
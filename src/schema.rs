@@ -10,7 +10,11 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-#![allow(missing_docs, clippy::large_enum_variant)]
+#![allow(
+    missing_docs,
+    clippy::large_enum_variant,
+    clippy::doc_lazy_continuation
+)]
 
 use crate::removable_value::RemovableValue;
 use derive_builder::Builder;
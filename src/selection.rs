@@ -0,0 +1,535 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Typed builders for the [`SelectionDef`](../struct.SelectionDef.html) of a chart, and
+//! references to a selection from predicates, scale domains and conditional encodings.
+//!
+//! ```
+//! use vega_lite_3::selection::*;
+//! use vega_lite_3::*;
+//!
+//! let brush = NamedSelection::interval("brush").encodings(vec![SingleDefUnitChannel::X]);
+//! let year = NamedSelection::single("year")
+//!     .fields(vec!["year"])
+//!     .bind(Input::range(1955.0, 2005.0).step(5.0));
+//! let color = DefWithConditionMarkPropFieldDefStringNullBuilder::default()
+//!     .condition(brush.condition("steelblue"))
+//!     .value("lightgray")
+//!     .build()
+//!     .unwrap();
+//! let chart = VegaliteBuilder::default()
+//!     .selection(selections(vec![brush, year]))
+//!     .build()
+//!     .unwrap();
+//! ```
+
+use crate::error::Error;
+use crate::event::{self, EventStream};
+use crate::predicate::{self, SelectionPred};
+use crate::schema::*;
+use std::collections::HashMap;
+use std::convert::TryFrom;
+
+/// A named selection, built with [`NamedSelection::single`](#method.single),
+/// [`NamedSelection::multi`](#method.multi) or [`NamedSelection::interval`](#method.interval)
+#[derive(Debug, Clone)]
+pub struct NamedSelection {
+    name: String,
+    def: SelectionDef,
+}
+
+/// An input element bound to a selection, built with [`Input::range`](#method.range),
+/// [`Input::select`](#method.select), [`Input::radio`](#method.radio) or
+/// [`Input::checkbox`](#method.checkbox)
+#[derive(Debug, Clone)]
+pub struct Input(Binding);
+
+/// A value of an encoding channel when a selection contains the data value, built with
+/// [`NamedSelection::condition`](struct.NamedSelection.html#method.condition)
+#[derive(Debug, Clone)]
+pub struct SelectionCondition<V> {
+    selection: PurpleSelectionOperand,
+    value: V,
+}
+
+/// The selections of a chart, by name
+pub fn selections(selections: Vec<NamedSelection>) -> HashMap<String, SelectionDef> {
+    selections
+        .into_iter()
+        .map(|selection| (selection.name, selection.def))
+        .collect()
+}
+
+impl NamedSelection {
+    fn new(name: &str, selection_type: SelectionDefType) -> Self {
+        NamedSelection {
+            name: name.to_string(),
+            def: SelectionDef {
+                selection_def_type: Some(selection_type),
+                ..Default::default()
+            },
+        }
+    }
+
+    /// Select a single data value, on click by default
+    pub fn single(name: &str) -> Self {
+        NamedSelection::new(name, SelectionDefType::Single)
+    }
+
+    /// Select several data values, on click by default, shift-click toggling values
+    pub fn multi(name: &str) -> Self {
+        NamedSelection::new(name, SelectionDefType::Multi)
+    }
+
+    /// Select a continuous range of data values, on drag by default
+    pub fn interval(name: &str) -> Self {
+        NamedSelection::new(name, SelectionDefType::Interval)
+    }
+
+    /// The name of the selection
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Rename the selection, keeping its definition
+    pub(crate) fn renamed(mut self, name: &str) -> Self {
+        self.name = name.to_string();
+        self
+    }
+
+    /// The events that trigger the selection
+    pub fn on(mut self, on: impl Into<EventStream>) -> Self {
+        self.def.on = Some(on.into().into());
+        self
+    }
+
+    /// The events that clear the selection
+    pub fn clear(mut self, clear: impl Into<EventStream>) -> Self {
+        self.def.clear = Some(clear.into().into());
+        self
+    }
+
+    /// Never clear the selection
+    pub fn no_clear(mut self) -> Self {
+        self.def.clear = Some(serde_json::Value::Bool(false));
+        self
+    }
+
+    /// Whether data values are selected when the selection is empty
+    pub fn empty(mut self, empty: Empty) -> Self {
+        self.def.empty = Some(empty);
+        self
+    }
+
+    /// Project the selection over encoding channels
+    pub fn encodings(mut self, encodings: Vec<SingleDefUnitChannel>) -> Self {
+        self.def.encodings = Some(encodings);
+        self
+    }
+
+    /// Project the selection over data fields
+    pub fn fields<S: Into<String>>(mut self, fields: Vec<S>) -> Self {
+        self.def.fields = Some(fields.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /// Initial value of a projected field or encoding; a two values array for an interval
+    pub fn init(mut self, field: &str, value: impl Into<InitSelectionInitMapping>) -> Self {
+        let mut init = match self.def.init.take() {
+            Some(SelectionDefInit::UnionMap(init)) => init,
+            _ => HashMap::new(),
+        };
+        init.insert(field.to_string(), value.into());
+        self.def.init = Some(SelectionDefInit::UnionMap(init));
+        self
+    }
+
+    /// Select the data value nearest to the mouse cursor
+    pub fn nearest(mut self, nearest: bool) -> Self {
+        self.def.nearest = Some(nearest);
+        self
+    }
+
+    /// How the selection is resolved across the views of a composed chart
+    pub fn resolve(mut self, resolve: SelectionResolution) -> Self {
+        self.def.resolve = Some(resolve);
+        self
+    }
+
+    /// For multi selections, an [expression](../expr/index.html) telling whether an event
+    /// toggles a value instead of replacing the selection, or `false`
+    pub fn toggle(mut self, toggle: impl Into<Translate>) -> Self {
+        self.def.toggle = Some(toggle.into());
+        self
+    }
+
+    /// For interval selections, the event stream that moves the interval, or `false`
+    pub fn translate(mut self, translate: impl Into<Translate>) -> Self {
+        self.def.translate = Some(translate.into());
+        self
+    }
+
+    /// For interval selections, the event stream that resizes the interval, or `false`
+    pub fn zoom(mut self, zoom: impl Into<Translate>) -> Self {
+        self.def.zoom = Some(zoom.into());
+        self
+    }
+
+    /// For interval selections, the appearance of the rectangle depicting the interval
+    pub fn mark(mut self, mark: BrushConfig) -> Self {
+        self.def.mark = Some(mark);
+        self
+    }
+
+    /// For interval selections, pan and zoom the scales of the view instead of drawing an
+    /// interval
+    pub fn bind_scales(mut self) -> Self {
+        self.def.bind = Some(SelectionDefBind::Enum(BindEnum::Scales));
+        self
+    }
+
+    /// For single selections, drive the projected fields of the selection with an input element
+    pub fn bind(mut self, input: Input) -> Self {
+        let binding = serde_json::to_value(input.0)
+            .and_then(serde_json::from_value)
+            .expect("a binding is a map of binding values");
+        self.def.bind = Some(SelectionDefBind::UnionMap(binding));
+        self
+    }
+
+    /// For single selections, drive a projected field of the selection with its own input
+    /// element
+    pub fn bind_field(mut self, field: &str, input: Input) -> Self {
+        let mut bind = match self.def.bind.take() {
+            Some(SelectionDefBind::UnionMap(bind))
+                if bind
+                    .values()
+                    .all(|value| matches!(value, BindValue::Binding(_))) =>
+            {
+                bind
+            }
+            _ => HashMap::new(),
+        };
+        bind.insert(field.to_string(), BindValue::Binding(input.0));
+        self.def.bind = Some(SelectionDefBind::UnionMap(bind));
+        self
+    }
+
+    /// A predicate testing whether the selection contains a data value, to filter data or as
+    /// the `selection` of a condition
+    pub fn pred(&self) -> SelectionPred {
+        predicate::selection(&self.name)
+    }
+
+    /// A scale domain following the selection, for a selection projected over a single field
+    /// or encoding
+    pub fn domain(&self) -> DomainUnion {
+        DomainUnion::DomainClass(DomainClass {
+            selection: Some(self.name.clone()),
+            ..Default::default()
+        })
+    }
+
+    /// A scale domain following a field of the selection
+    pub fn domain_field(&self, field: &str) -> DomainUnion {
+        DomainUnion::DomainClass(DomainClass {
+            selection: Some(self.name.clone()),
+            field: Some(field.to_string()),
+            ..Default::default()
+        })
+    }
+
+    /// A scale domain following an encoding of the selection
+    pub fn domain_encoding(&self, encoding: &str) -> DomainUnion {
+        DomainUnion::DomainClass(DomainClass {
+            selection: Some(self.name.clone()),
+            encoding: Some(encoding.to_string()),
+            ..Default::default()
+        })
+    }
+
+    /// The `condition` of an encoding channel giving `value` to the data values contained in
+    /// the selection
+    pub fn condition<V>(&self, value: V) -> SelectionCondition<V> {
+        SelectionCondition {
+            selection: self.pred().into(),
+            value,
+        }
+    }
+}
+
+impl From<NamedSelection> for SelectionDef {
+    fn from(v: NamedSelection) -> Self {
+        v.def
+    }
+}
+
+impl SelectionDef {
+    /// Check the event streams of the selection, given as json objects or selector strings,
+    /// and its `toggle` expression
+    pub fn validate(&self) -> Result<(), Error> {
+        let invalid = |property: &str, err: Error| {
+            Error::Validation(format!(
+                "`{}`: {}",
+                property,
+                match err {
+                    Error::Validation(reason) => reason,
+                    err => err.to_string(),
+                }
+            ))
+        };
+        for (property, events) in [("on", &self.on), ("clear", &self.clear)] {
+            match events {
+                None | Some(serde_json::Value::Bool(false)) => {}
+                Some(events) => {
+                    EventStream::try_from(events).map_err(|err| invalid(property, err))?;
+                }
+            }
+        }
+        for (property, events) in [("translate", &self.translate), ("zoom", &self.zoom)] {
+            if let Some(Translate::String(selector)) = events {
+                event::parse(selector).map_err(|err| {
+                    invalid(
+                        property,
+                        Error::Validation(format!("event selector: {}", err)),
+                    )
+                })?;
+            }
+        }
+        if let Some(Translate::String(toggle)) = &self.toggle {
+            crate::expr::parse(toggle).map_err(|err| {
+                invalid("toggle", Error::Validation(format!("expression: {}", err)))
+            })?;
+        }
+        Ok(())
+    }
+}
+
+impl Input {
+    fn new(input: &str) -> Self {
+        Input(Binding {
+            input: Some(input.to_string()),
+            ..Default::default()
+        })
+    }
+
+    /// A slider between `min` and `max`
+    pub fn range(min: f64, max: f64) -> Self {
+        let mut input = Input::new("range");
+        input.0.min = Some(min);
+        input.0.max = Some(max);
+        input
+    }
+
+    /// A drop-down menu of `options`
+    pub fn select<T: Into<serde_json::Value>>(options: Vec<T>) -> Self {
+        let mut input = Input::new("select");
+        input.0.options = Some(options.into_iter().map(|o| Some(o.into())).collect());
+        input
+    }
+
+    /// Radio buttons for `options`
+    pub fn radio<T: Into<serde_json::Value>>(options: Vec<T>) -> Self {
+        let mut input = Input::select(options);
+        input.0.input = Some("radio".to_string());
+        input
+    }
+
+    /// A checkbox
+    pub fn checkbox() -> Self {
+        Input::new("checkbox")
+    }
+
+    /// For sliders, the increment between two values
+    pub fn step(mut self, step: f64) -> Self {
+        self.0.step = Some(step);
+        self
+    }
+
+    /// The label of the input element
+    pub fn name(mut self, name: &str) -> Self {
+        self.0.name = Some(name.to_string());
+        self
+    }
+
+    /// A CSS selector of the element to add the input element to
+    pub fn element(mut self, element: &str) -> Self {
+        self.0.element = Some(element.to_string());
+        self
+    }
+
+    /// Number of milliseconds to wait for the input to stop changing before updating the
+    /// selection
+    pub fn debounce(mut self, debounce: f64) -> Self {
+        self.0.debounce = Some(debounce);
+        self
+    }
+}
+
+impl From<Input> for Binding {
+    fn from(v: Input) -> Self {
+        v.0
+    }
+}
+
+impl<S: Into<String>> From<SelectionCondition<S>> for ColorCondition {
+    fn from(v: SelectionCondition<S>) -> Self {
+        ColorCondition::ConditionalPredicateStringValueDefClass(
+            ConditionalPredicateStringValueDefClass {
+                selection: Some(Box::new(v.selection)),
+                value: Some(v.value.into()),
+                ..Default::default()
+            },
+        )
+    }
+}
+
+impl<S: Into<String>> From<SelectionCondition<S>> for ConditionUnion {
+    fn from(v: SelectionCondition<S>) -> Self {
+        ConditionUnion::Conditional(Conditional {
+            selection: Some(Box::new(v.selection)),
+            value: Some(v.value.into()),
+            ..Default::default()
+        })
+    }
+}
+
+impl From<SelectionCondition<f64>> for FillOpacityCondition {
+    fn from(v: SelectionCondition<f64>) -> Self {
+        FillOpacityCondition::ConditionalPredicateNumberValueDefClass(
+            ConditionalPredicateNumberValueDefClass {
+                selection: Some(Box::new(v.selection)),
+                value: Some(v.value),
+                ..Default::default()
+            },
+        )
+    }
+}
+
+impl<V: Into<Value>> From<SelectionCondition<V>> for HrefCondition {
+    fn from(v: SelectionCondition<V>) -> Self {
+        HrefCondition::ConditionalPredicateValueDefClass(ConditionalPredicateValueDefClass {
+            selection: Some(Box::new(v.selection)),
+            value: Some(v.value.into()),
+            ..Default::default()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::event::EventSource;
+    use serde_json::json;
+
+    #[test]
+    fn test_selection_defs() {
+        let brush = NamedSelection::interval("brush")
+            .encodings(vec![SingleDefUnitChannel::X])
+            .on(EventStream::event("mousemove").between(
+                EventStream::event("mousedown"),
+                EventStream::event("mouseup").source(EventSource::Window),
+            ))
+            .translate(false)
+            .init(
+                "x",
+                vec![SelectionInitIntervalElement::Double(1.0), 2.0.into()],
+            );
+        let pick = NamedSelection::single("pick")
+            .fields(vec!["Cylinders", "Year"])
+            .bind_field("Cylinders", Input::range(3.0, 8.0).step(1.0))
+            .bind_field("Year", Input::select(vec![1970, 1980]).name("year "))
+            .no_clear();
+        let slider = NamedSelection::single("year").bind(Input::radio(vec!["a", "b"]));
+        assert_eq!(
+            serde_json::to_value(selections(vec![brush, pick, slider])).unwrap(),
+            json!({
+                "brush": {
+                    "type": "interval",
+                    "encodings": ["x"],
+                    "on": {
+                        "type": "mousemove",
+                        "between": [{"type": "mousedown"}, {"source": "window", "type": "mouseup"}]
+                    },
+                    "translate": false,
+                    "init": {"x": [1.0, 2.0]}
+                },
+                "pick": {
+                    "type": "single",
+                    "fields": ["Cylinders", "Year"],
+                    "bind": {
+                        "Cylinders": {"input": "range", "min": 3.0, "max": 8.0, "step": 1.0},
+                        "Year": {"input": "select", "options": [1970, 1980], "name": "year "}
+                    },
+                    "clear": false
+                },
+                "year": {"type": "single", "bind": {"input": "radio", "options": ["a", "b"]}}
+            })
+        );
+    }
+
+    #[test]
+    fn test_selection_references() {
+        let brush = NamedSelection::interval("brush").bind_scales();
+        assert_eq!(
+            serde_json::to_value(SelectionDef::from(brush.clone()).bind).unwrap(),
+            json!("scales")
+        );
+        assert_eq!(
+            serde_json::to_value(brush.domain_encoding("x")).unwrap(),
+            json!({"selection": "brush", "encoding": "x"})
+        );
+        assert_eq!(
+            serde_json::to_value(ColorCondition::from(brush.condition("red"))).unwrap(),
+            json!({"selection": "brush", "value": "red"})
+        );
+        assert_eq!(
+            serde_json::to_value(FillOpacityCondition::from(brush.condition(0.5))).unwrap(),
+            json!({"selection": "brush", "value": 0.5})
+        );
+        let filter: PurpleLogicalOperandPredicate = (!brush.pred()).into();
+        assert_eq!(
+            serde_json::to_value(filter).unwrap(),
+            json!({"selection": {"not": "brush"}})
+        );
+    }
+
+    #[test]
+    fn test_validate_selection_def() {
+        let def: SelectionDef = serde_json::from_value(json!({
+            "type": "interval",
+            "on": "[mousedown, window:mouseup] > window:mousemove!",
+            "clear": {"type": "dblclick", "markname": "brush"},
+            "translate": "[mousedown, mouseup] > mousemove",
+            "zoom": "wheel!"
+        }))
+        .unwrap();
+        assert!(def.validate().is_ok());
+        let drag: EventStream = "[mousedown, mouseup] > mousemove".parse().unwrap();
+        let translated = SelectionDef::from(
+            NamedSelection::interval("brush").translate(Translate::try_from(drag).unwrap()),
+        );
+        assert!(translated.validate().is_ok());
+
+        let def: SelectionDef =
+            serde_json::from_value(json!({"type": "single", "on": "[mousedown] > mousemove"}))
+                .unwrap();
+        assert_eq!(
+            def.validate().unwrap_err().to_string(),
+            "invalid specification: `on`: event selector: `between` needs two event streams at \
+             position 0"
+        );
+        let def: SelectionDef =
+            serde_json::from_value(json!({"type": "multi", "toggle": "event.shiftKey &&"}))
+                .unwrap();
+        assert!(def.validate().is_err());
+    }
+}
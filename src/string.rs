@@ -9,32 +9,32 @@
 // WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
 // See the License for the specific language governing permissions and
 // limitations under the License.
+use crate::error::Error;
 use crate::schema::*;
-// use std::str::FromStr;
-// use std::convert::TryFrom;
-use serde_json;
+use std::convert::TryFrom;
+use std::str::FromStr;
 
-// impl FromStr for Vegalite {
-//     type Err = serde_json::Error;
+impl FromStr for Vegalite {
+    type Err = Error;
 
-//     fn from_str(s: &str) -> Result<Self, Self::Err> {
-//         serde_json::from_str(s)
-//     }
-// }
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(serde_json::from_str(s)?)
+    }
+}
 
 impl Vegalite {
-    /// Render the json for a graph
+    /// Render the json for a graph, with the [default theme](themes/fn.set_default.html) applied
     pub fn to_string(&self) -> Result<String, serde_json::Error> {
-        serde_json::to_string(self)
+        serde_json::to_string(&*self.themed())
     }
 }
 
-// impl TryFrom<&Vegalite> for String {
-//     type Error = serde_json::Error;
-//     fn try_from(v: &Vegalite) -> Result<Self, Self::Error> {
-//         v.to_string()
-//     }
-// }
+impl TryFrom<&Vegalite> for String {
+    type Error = Error;
+    fn try_from(v: &Vegalite) -> Result<Self, Self::Error> {
+        Ok(v.to_string()?)
+    }
+}
 
 // for every enum with String(String)
 macro_rules! from_into_string{
@@ -75,30 +75,44 @@ from_into_string!(
     UrlDataInlineDataset,
 );
 
-// #[cfg(test)]
-// mod tests {
-//     use super::*;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-//     #[test]
-//     fn test_from_string_to_string() {
-//         let json1 = r#"
-//             {
-//                 "$schema": "https://vega.github.io/schema/vega-lite/v3.json",
-//                 "description": "Google's stock price over time.",
-//                 "data": {"url": "data/stocks.csv"},
-//                 "transform": [{"filter": "datum.symbol==='GOOG'"}],
-//                 "mark": "line",
-//                 "encoding": {
-//                     "x": {"field": "date", "type": "temporal"},
-//                     "y": {"field": "price", "type": "quantitative"}
-//                 }
-//             }
-//         "#;
-//         let vega1 = Vegalite::from_str(json1).unwrap();
-//         //dbg!(vega1);
-//         //let json2 = vega1.to_string().unwrap();
-//         //let vega2 = Vegalite::from_str(json2).unwrap();
-//         //assert_eq!(json1, json2);
-//         //assert_eq!(vega1, vega2);
-//     }
-// }
+    #[test]
+    fn test_from_string_to_string() {
+        crate::with_big_stack(|| {
+            let json1 = r#"
+            {
+                "$schema": "https://vega.github.io/schema/vega-lite/v3.json",
+                "description": "Google's stock price over time.",
+                "data": {"url": "data/stocks.csv"},
+                "transform": [{"filter": "datum.symbol==='GOOG'"}],
+                "mark": "line",
+                "encoding": {
+                    "x": {"field": "date", "type": "temporal"},
+                    "y": {"field": "price", "type": "quantitative"}
+                }
+            }
+        "#;
+            let vega1 = Vegalite::from_str(json1).unwrap();
+            let json2 = String::try_from(&vega1).unwrap();
+            let vega2 = Vegalite::from_str(&json2).unwrap();
+            assert_eq!(json2, vega2.to_string().unwrap());
+            assert_eq!(
+                serde_json::from_str::<serde_json::Value>(json1).unwrap(),
+                serde_json::from_str::<serde_json::Value>(&json2).unwrap()
+            );
+        });
+    }
+
+    #[test]
+    fn test_from_invalid_string() {
+        crate::with_big_stack(|| {
+            assert!(matches!(
+                Vegalite::from_str(r#"{"mark": 3}"#),
+                Err(Error::Json(_))
+            ));
+        });
+    }
+}
@@ -0,0 +1,435 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Ready-made [`Config`](../struct.Config.html) themes, equivalent to the
+//! [vega-themes](https://github.com/vega/vega-themes), and a registry of named themes.
+//!
+//! The configurations of Vega marks without a Vega-Lite counterpart (`arc`, `path`, `shape`,
+//! `group`) are dropped, and `symbol` is used for points.
+//!
+//! ```
+//! use vega_lite_3::themes;
+//! use vega_lite_3::*;
+//!
+//! let mut corporate = themes::quartz();
+//! corporate.background = Some("#fafafa".to_string());
+//! themes::register("corporate", corporate);
+//! // applied to every chart rendered as json or html from now on
+//! themes::set_default(Some("corporate")).unwrap();
+//!
+//! let mut chart = VegaliteBuilder::default().mark(Mark::Bar).build().unwrap();
+//! chart.apply_theme(&themes::dark());
+//! ```
+
+use crate::error::Error;
+use crate::merge::Merge;
+use crate::schema::*;
+use serde_json::{json, Value};
+use std::borrow::Cow;
+use std::sync::Mutex;
+
+/// The names of the built-in themes
+pub const BUILTIN: &[&str] = &[
+    "dark",
+    "excel",
+    "fivethirtyeight",
+    "ggplot2",
+    "latimes",
+    "quartz",
+    "vox",
+];
+
+/// Themes registered by name, and the name of the theme applied when rendering charts
+struct Registry {
+    themes: Vec<(String, Config)>,
+    default: Option<String>,
+}
+
+static REGISTRY: Mutex<Registry> = Mutex::new(Registry {
+    themes: Vec::new(),
+    default: None,
+});
+
+fn registry() -> std::sync::MutexGuard<'static, Registry> {
+    REGISTRY
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+fn config(theme: Value) -> Config {
+    serde_json::from_value(theme).expect("built-in themes are valid configurations")
+}
+
+/// Light text and axes on a dark background
+pub fn dark() -> Config {
+    let (light, medium) = ("#fff", "#888");
+    config(json!({
+        "background": "#333",
+        "title": {"color": light},
+        "style": {
+            "guide-label": {"fill": light},
+            "guide-title": {"fill": light}
+        },
+        "axis": {"domainColor": light, "gridColor": medium, "tickColor": light}
+    }))
+}
+
+/// The look of Microsoft Excel charts
+pub fn excel() -> Config {
+    let mark = "#4572a7";
+    config(json!({
+        "background": "#fff",
+        "area": {"fill": mark},
+        "line": {"stroke": mark, "strokeWidth": 2},
+        "rect": {"fill": mark},
+        "bar": {"fill": mark},
+        "point": {"fill": mark, "strokeWidth": 1.5, "size": 50},
+        "axis": {
+            "bandPosition": 0.5,
+            "grid": true,
+            "gridColor": "#000000",
+            "gridOpacity": 1,
+            "gridWidth": 0.5,
+            "labelPadding": 10,
+            "tickSize": 5,
+            "tickWidth": 0.5
+        },
+        "axisBand": {"grid": false, "tickExtra": true},
+        "legend": {
+            "labelBaseline": "middle",
+            "labelFontSize": 11,
+            "symbolSize": 50,
+            "symbolType": "square"
+        },
+        "range": {
+            "category": [
+                "#4572a7", "#aa4643", "#8aa453", "#71598e", "#4598ae",
+                "#d98445", "#94aace", "#d09393", "#b9cc98", "#a99cbc"
+            ]
+        }
+    }))
+}
+
+/// The look of the FiveThirtyEight website
+pub fn fivethirtyeight() -> Config {
+    let (mark, axis, label, title, background) = ("#30a2da", "#cbcbcb", "#999", "#333", "#f0f0f0");
+    let side_axis = json!({
+        "domainColor": axis,
+        "domainWidth": 1,
+        "grid": true,
+        "gridColor": axis,
+        "gridWidth": 1,
+        "labelColor": label,
+        "labelFontSize": 10,
+        "labelPadding": 4,
+        "tickColor": axis,
+        "tickSize": 10,
+        "ticks": true,
+        "titleFontSize": 14,
+        "titlePadding": 10
+    });
+    config(json!({
+        "background": background,
+        "view": {"fill": background},
+        "area": {"fill": mark},
+        "line": {"stroke": mark, "strokeWidth": 2},
+        "rect": {"fill": mark},
+        "point": {"filled": true, "shape": "circle"},
+        "axisBand": {"grid": false},
+        "axisBottom": {
+            "domain": false,
+            "domainColor": title,
+            "domainWidth": 3,
+            "grid": true,
+            "gridColor": axis,
+            "gridWidth": 1,
+            "labelColor": label,
+            "labelFontSize": 10,
+            "labelPadding": 4,
+            "tickColor": axis,
+            "tickSize": 10,
+            "titleFontSize": 14,
+            "titlePadding": 10
+        },
+        "axisLeft": side_axis,
+        "axisRight": side_axis,
+        "legend": {
+            "labelColor": title,
+            "labelFontSize": 11,
+            "padding": 1,
+            "symbolSize": 30,
+            "symbolType": "square",
+            "titleColor": title,
+            "titleFontSize": 14,
+            "titlePadding": 10
+        },
+        "range": {
+            "category": [
+                "#30a2da", "#fc4f30", "#e5ae38", "#6d904f", "#8b8b8b", "#b96db8",
+                "#ff9e27", "#56cc60", "#52d2ca", "#52689e", "#545454", "#9fe4f8"
+            ],
+            "diverging": ["#cc0020", "#e77866", "#f6e7e1", "#d6e8ed", "#91bfd9", "#1d78b5"],
+            "heatmap": ["#d6e8ed", "#cee0e5", "#91bfd9", "#549cc6", "#1d78b5"]
+        },
+        "bar": {"binSpacing": 2, "fill": mark},
+        "title": {"anchor": "start", "fontSize": 24, "fontWeight": 600, "offset": 20}
+    }))
+}
+
+/// The look of the ggplot2 R package
+pub fn ggplot2() -> Config {
+    let mark = "#000";
+    config(json!({
+        "view": {"fill": "#e5e5e5"},
+        "area": {"fill": mark},
+        "line": {"stroke": mark},
+        "rect": {"fill": mark},
+        "point": {"fill": mark, "size": 40},
+        "axis": {
+            "domain": false,
+            "grid": true,
+            "gridColor": "#FFFFFF",
+            "gridOpacity": 1,
+            "labelColor": "#7F7F7F",
+            "labelPadding": 4,
+            "tickColor": "#7F7F7F",
+            "tickSize": 5.67,
+            "titleFontSize": 16,
+            "titleFontWeight": "normal"
+        },
+        "legend": {"labelBaseline": "middle", "labelFontSize": 11, "symbolSize": 40},
+        "range": {
+            "category": [
+                "#000000", "#7F7F7F", "#1A1A1A", "#999999", "#333333",
+                "#B0B0B0", "#4D4D4D", "#C9C9C9", "#666666", "#DCDCDC"
+            ]
+        }
+    }))
+}
+
+/// The look of the Los Angeles Times graphics
+pub fn latimes() -> Config {
+    let mark = "#82c6df";
+    let (label_font, label_font_size) = ("Benton Gothic, sans-serif", 11.5);
+    let (title_font, title_font_size) = ("Benton Gothic Bold, sans-serif", 13);
+    let fire = json!(["#fbf2c7", "#f9e39c", "#f8d36e", "#f4bb6a", "#e68a4f", "#d15a40", "#ab4232"]);
+    config(json!({
+        "background": "#ffffff",
+        "title": {
+            "anchor": "start",
+            "font": title_font,
+            "color": "#000000",
+            "fontSize": 22,
+            "fontWeight": "normal"
+        },
+        "area": {"fill": mark},
+        "line": {"stroke": mark, "strokeWidth": 2},
+        "rect": {"fill": mark},
+        "point": {"fill": mark, "size": 30},
+        "axis": {
+            "labelFont": label_font,
+            "labelFontSize": label_font_size,
+            "labelFontWeight": "normal",
+            "titleFont": title_font,
+            "titleFontSize": title_font_size,
+            "titleFontWeight": "normal"
+        },
+        "axisX": {"labelAngle": 0, "labelPadding": 4, "tickSize": 3},
+        "axisY": {
+            "labelBaseline": "middle",
+            "maxExtent": 45,
+            "minExtent": 45,
+            "tickSize": 2,
+            "titleAlign": "left",
+            "titleAngle": 0,
+            "titleX": -45,
+            "titleY": -11
+        },
+        "legend": {
+            "labelFont": label_font,
+            "labelFontSize": label_font_size,
+            "symbolType": "square",
+            "titleFont": title_font,
+            "titleFontSize": title_font_size,
+            "titleFontWeight": "normal"
+        },
+        "range": {
+            "category": ["#ec8431", "#829eb1", "#c89d29", "#3580b1", "#adc839", "#ab7fb4"],
+            "diverging": ["#e68a4f", "#f4bb6a", "#f9e39c", "#dadfe2", "#a6b7c6", "#849eae"],
+            "heatmap": fire,
+            "ordinal": fire,
+            "ramp": fire
+        }
+    }))
+}
+
+/// The look of the Quartz website
+pub fn quartz() -> Config {
+    let (mark, axis) = ("#ab5787", "#979797");
+    config(json!({
+        "background": "#f9f9f9",
+        "area": {"fill": mark},
+        "line": {"stroke": mark},
+        "rect": {"fill": mark},
+        "point": {"fill": mark, "size": 30},
+        "axis": {
+            "domainColor": axis,
+            "domainWidth": 0.5,
+            "gridWidth": 0.2,
+            "labelColor": axis,
+            "tickColor": axis,
+            "tickWidth": 0.2,
+            "titleColor": axis
+        },
+        "axisBand": {"grid": false},
+        "axisX": {"grid": true, "tickSize": 10},
+        "axisY": {"domain": false, "grid": true, "tickSize": 0},
+        "legend": {"labelFontSize": 11, "padding": 1, "symbolSize": 30, "symbolType": "square"},
+        "range": {
+            "category": [
+                "#ab5787", "#51b2e5", "#703c5c", "#168dd9", "#d190b6",
+                "#00609f", "#d365ba", "#154866", "#666666", "#c4c4c4"
+            ]
+        }
+    }))
+}
+
+/// The look of the Vox website
+pub fn vox() -> Config {
+    let mark = "#3e5c69";
+    config(json!({
+        "background": "#fff",
+        "area": {"fill": mark},
+        "line": {"stroke": mark},
+        "rect": {"fill": mark},
+        "point": {"fill": mark},
+        "axis": {
+            "domainWidth": 0.5,
+            "grid": true,
+            "labelPadding": 2,
+            "tickSize": 5,
+            "tickWidth": 0.5,
+            "titleFontWeight": "normal"
+        },
+        "axisBand": {"grid": false},
+        "axisX": {"gridWidth": 0.2},
+        "axisY": {"gridDash": [3], "gridWidth": 0.4},
+        "legend": {"labelFontSize": 11, "padding": 1, "symbolType": "square"},
+        "range": {
+            "category": [
+                "#3e5c69", "#6793a6", "#182429", "#0570b0",
+                "#3690c0", "#74a9cf", "#a6bddb", "#e2ddf2"
+            ]
+        }
+    }))
+}
+
+/// Register a theme under a name, replacing a registered or built-in theme with the same name
+pub fn register(name: &str, theme: Config) {
+    let mut registry = registry();
+    registry.themes.retain(|(registered, _)| registered != name);
+    registry.themes.push((name.to_string(), theme));
+}
+
+/// The theme registered under a name, or the built-in theme with this name
+pub fn get(name: &str) -> Option<Config> {
+    let registered = registry()
+        .themes
+        .iter()
+        .find(|(registered, _)| registered == name)
+        .map(|(_, theme)| theme.clone());
+    registered.or_else(|| match name {
+        "dark" => Some(dark()),
+        "excel" => Some(excel()),
+        "fivethirtyeight" => Some(fivethirtyeight()),
+        "ggplot2" => Some(ggplot2()),
+        "latimes" => Some(latimes()),
+        "quartz" => Some(quartz()),
+        "vox" => Some(vox()),
+        _ => None,
+    })
+}
+
+/// Set the theme applied to charts rendered as json or html, or stop applying one
+pub fn set_default(name: Option<&str>) -> Result<(), Error> {
+    if let Some(name) = name {
+        if get(name).is_none() {
+            return Err(Error::Validation(format!("unknown theme `{}`", name)));
+        }
+    }
+    registry().default = name.map(String::from);
+    Ok(())
+}
+
+/// The theme applied to charts rendered as json or html
+pub(crate) fn default_theme() -> Option<Config> {
+    let name = registry().default.clone()?;
+    get(&name)
+}
+
+impl Vegalite {
+    /// Apply a theme, the configuration of the chart taking precedence over the theme
+    pub fn apply_theme(&mut self, theme: &Config) {
+        self.config = Some(theme.clone()).merge(self.config.take());
+    }
+
+    /// The chart as rendered, with the [default theme](fn.set_default.html) applied if any
+    pub(crate) fn themed(&self) -> Cow<'_, Vegalite> {
+        match default_theme() {
+            Some(theme) => {
+                let mut chart = self.clone();
+                chart.apply_theme(&theme);
+                Cow::Owned(chart)
+            }
+            None => Cow::Borrowed(self),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_themes() {
+        for name in BUILTIN {
+            let theme = serde_json::to_value(get(name).unwrap()).unwrap();
+            assert!(theme["range"].is_object() || theme["style"].is_object());
+        }
+        assert_eq!(
+            serde_json::to_value(fivethirtyeight()).unwrap()["axisLeft"]["titlePadding"],
+            json!(10.0)
+        );
+        assert!(get("solarized").is_none());
+        assert!(set_default(Some("solarized")).is_err());
+    }
+
+    fn chart() -> Vegalite {
+        crate::with_big_stack(|| {
+            serde_json::from_value(json!({
+                "mark": "bar",
+                "config": {"background": "#000", "axis": {"gridWidth": 1}}
+            }))
+            .unwrap()
+        })
+    }
+
+    #[test]
+    fn test_apply_theme() {
+        let mut chart = chart();
+        register("test-corporate", quartz());
+        chart.apply_theme(&get("test-corporate").unwrap());
+        let config = serde_json::to_value(&chart.config).unwrap();
+        assert_eq!(config["background"], json!("#000"));
+        assert_eq!(config["axis"]["gridWidth"], json!(1.0));
+        assert_eq!(config["axis"]["domainColor"], json!("#979797"));
+    }
+}
@@ -0,0 +1,407 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Tooltips listing the fields of a chart, either the fields of its encoding channels or the
+//! columns of its inline data.
+//!
+//! ```
+//! use vega_lite_3::tooltip::AutoTooltip;
+//! use vega_lite_3::*;
+//!
+//! let mut chart: Vegalite = serde_json::from_str(
+//!     r#"{
+//!         "data": {"values": [{"a": "A", "b": 28}, {"a": "B", "b": 55}]},
+//!         "mark": "bar",
+//!         "encoding": {
+//!             "x": {"field": "a", "type": "nominal"},
+//!             "y": {"field": "b", "type": "quantitative", "aggregate": "sum"}
+//!         }
+//!     }"#,
+//! )
+//! .unwrap();
+//! chart.set_auto_tooltip(&AutoTooltip::channels()).unwrap();
+//! ```
+
+use crate::error::Error;
+use crate::eval::derived_title;
+use crate::schema::*;
+use serde_json::{json, Map, Value};
+use std::collections::HashSet;
+
+/// The channels listed in a tooltip, in order
+const CHANNELS: &[&str] = &[
+    "x",
+    "x2",
+    "y",
+    "y2",
+    "longitude",
+    "latitude",
+    "longitude2",
+    "latitude2",
+    "row",
+    "column",
+    "facet",
+    "color",
+    "fill",
+    "stroke",
+    "shape",
+    "size",
+    "opacity",
+    "fillOpacity",
+    "strokeOpacity",
+    "strokeWidth",
+    "text",
+    "href",
+    "detail",
+    "key",
+    "order",
+    "xError",
+    "xError2",
+    "yError",
+    "yError2",
+];
+
+/// The properties of a channel definition copied to a tooltip
+const PROPERTIES: &[&str] = &["field", "type", "aggregate", "bin", "timeUnit"];
+
+/// Where the fields of a tooltip come from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Content {
+    Channels,
+    Data,
+}
+
+/// A tooltip generated from a chart, built with [`AutoTooltip::channels`](#method.channels)
+/// or [`AutoTooltip::data`](#method.data)
+#[derive(Debug, Clone)]
+pub struct AutoTooltip {
+    content: Content,
+    exclude: Vec<String>,
+}
+
+impl AutoTooltip {
+    /// A tooltip with the fields of the encoding channels of the chart (and of its layers), with
+    /// their aggregation, binning, time unit, title and axis or legend format
+    pub fn channels() -> Self {
+        AutoTooltip {
+            content: Content::Channels,
+            exclude: Vec::new(),
+        }
+    }
+
+    /// A tooltip with all the columns of the inline data of the chart, typed like the channels
+    /// using them or from their values
+    pub fn data() -> Self {
+        AutoTooltip {
+            content: Content::Data,
+            exclude: Vec::new(),
+        }
+    }
+
+    /// Leave a field out of the tooltip
+    pub fn exclude(mut self, field: &str) -> Self {
+        self.exclude.push(field.to_string());
+        self
+    }
+
+    /// The fields of the tooltip for a chart
+    pub fn fields(&self, chart: &Vegalite) -> Result<Vec<TextFieldDef>, Error> {
+        let config = chart.config.as_ref();
+        let chart = serde_json::to_value(chart)?;
+        let mut channels = Vec::new();
+        channel_fields(&chart, config, &mut channels);
+        let fields = match self.content {
+            Content::Channels => channels,
+            Content::Data => data_fields(&chart, &channels)?,
+        };
+        let mut seen = HashSet::new();
+        fields
+            .into_iter()
+            .filter(|field| match field.get("field").and_then(Value::as_str) {
+                Some(name) => !self.exclude.iter().any(|excluded| excluded == name),
+                None => true,
+            })
+            .filter(|field| {
+                let key = PROPERTIES
+                    .iter()
+                    .map(|property| field.get(*property).cloned().unwrap_or_default())
+                    .collect::<Vec<_>>();
+                seen.insert(Value::Array(key).to_string())
+            })
+            .map(|field| Ok(serde_json::from_value(Value::Object(field))?))
+            .collect()
+    }
+}
+
+impl Vegalite {
+    /// Set the tooltip of the chart to the fields of an
+    /// [`AutoTooltip`](tooltip/struct.AutoTooltip.html)
+    pub fn set_auto_tooltip(&mut self, tooltip: &AutoTooltip) -> Result<(), Error> {
+        let fields = tooltip.fields(self)?;
+        self.encoding.get_or_insert_with(Default::default).tooltip =
+            Tooltip::TextFieldDefArray(fields).into();
+        Ok(())
+    }
+}
+
+/// Collect the tooltip fields of the channels of a chart and of its layers
+fn channel_fields(chart: &Value, config: Option<&Config>, fields: &mut Vec<Map<String, Value>>) {
+    for channel in CHANNELS {
+        match &chart["encoding"][*channel] {
+            Value::Array(defs) => {
+                fields.extend(defs.iter().filter_map(|def| channel_field(def, config)))
+            }
+            def => fields.extend(channel_field(def, config)),
+        }
+    }
+    for layer in chart["layer"].as_array().into_iter().flatten() {
+        channel_fields(layer, config, fields);
+    }
+}
+
+/// The tooltip field of a channel definition, if it has a field or counts the data
+fn channel_field(def: &Value, config: Option<&Config>) -> Option<Map<String, Value>> {
+    if def["field"].is_null() && def["aggregate"] != "count" {
+        return None;
+    }
+    let mut field = PROPERTIES
+        .iter()
+        .filter(|property| !def[**property].is_null())
+        .map(|property| (property.to_string(), def[*property].clone()))
+        .collect::<Map<_, _>>();
+    if !matches!(
+        def["type"].as_str(),
+        Some("quantitative" | "nominal" | "ordinal" | "temporal")
+    ) {
+        field.remove("type");
+    }
+    let guides = [&def["axis"], &def["legend"], &def["header"]];
+    let title = Some(&def["title"])
+        .into_iter()
+        .chain(guides.iter().map(|guide| &guide["title"]))
+        .find_map(|title| title.as_str().map(ToString::to_string))
+        .or_else(|| derived_title(def.as_object()?, config));
+    if let Some(title) = title {
+        field.insert("title".to_string(), title.into());
+    }
+    if let Some(guide) = Some(def)
+        .into_iter()
+        .chain(guides.iter().cloned())
+        .find(|guide| guide["format"].is_string())
+    {
+        field.insert("format".to_string(), guide["format"].clone());
+        if !guide["formatType"].is_null() {
+            field.insert("formatType".to_string(), guide["formatType"].clone());
+        }
+    }
+    Some(field)
+}
+
+/// The tooltip fields of the columns of the inline data of a chart, typed like the channels
+/// showing them unchanged or from their values
+fn data_fields(
+    chart: &Value,
+    channels: &[Map<String, Value>],
+) -> Result<Vec<Map<String, Value>>, Error> {
+    let rows = match (&chart["data"]["values"], chart["data"]["name"].as_str()) {
+        (Value::Array(rows), _) => rows,
+        (Value::Null, Some(name)) => chart["datasets"][name].as_array().ok_or_else(|| {
+            Error::Validation(format!("the dataset `{}` is not an array of rows", name))
+        })?,
+        _ => {
+            return Err(Error::Validation(
+                "a data tooltip needs the inline data of the chart".to_string(),
+            ))
+        }
+    };
+    let mut columns = Vec::<&String>::new();
+    for row in rows {
+        for column in row.as_object().into_iter().flat_map(Map::keys) {
+            if !columns.contains(&column) {
+                columns.push(column);
+            }
+        }
+    }
+    Ok(columns
+        .into_iter()
+        .map(|column| {
+            channels
+                .iter()
+                .find(|field| {
+                    field.get("field") == Some(&json!(column))
+                        && ["aggregate", "bin", "timeUnit"]
+                            .iter()
+                            .all(|property| !field.contains_key(*property))
+                })
+                .cloned()
+                .unwrap_or_else(|| {
+                    let values = rows.iter().map(|row| &row[column]).filter(|v| !v.is_null());
+                    let mut field = Map::new();
+                    field.insert("field".to_string(), json!(column));
+                    field.insert("type".to_string(), json!(infer_type(values)));
+                    field
+                })
+        })
+        .collect())
+}
+
+/// The type of the values of a column: quantitative for numbers, temporal for ISO 8601 dates,
+/// nominal otherwise
+fn infer_type<'a>(mut values: impl Iterator<Item = &'a Value> + Clone) -> &'static str {
+    let is_date = |value: &str| {
+        let bytes = value.as_bytes();
+        bytes.len() >= 10
+            && bytes[..10].iter().enumerate().all(|(i, byte)| match i {
+                4 | 7 => *byte == b'-',
+                _ => byte.is_ascii_digit(),
+            })
+    };
+    if values.clone().all(|value| value.is_number()) {
+        "quantitative"
+    } else if values.all(|value| value.as_str().is_some_and(is_date)) {
+        "temporal"
+    } else {
+        "nominal"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart(spec: Value) -> Vegalite {
+        crate::with_big_stack(move || serde_json::from_value(spec).unwrap())
+    }
+
+    fn titles(chart: &Vegalite) -> Vec<Value> {
+        AutoTooltip::channels()
+            .fields(chart)
+            .unwrap()
+            .into_iter()
+            .map(|field| serde_json::to_value(field).unwrap()["title"].clone())
+            .collect()
+    }
+
+    #[test]
+    fn test_channels() {
+        let mut chart = chart(json!({
+            "mark": "bar",
+            "encoding": {
+                "x": {"field": "date", "type": "temporal", "timeUnit": "month"},
+                "y": {
+                    "field": "price",
+                    "type": "quantitative",
+                    "aggregate": "mean",
+                    "axis": {"format": "$.2f"}
+                },
+                "color": {"field": "symbol", "type": "nominal", "legend": {"title": "Stock"}},
+                "detail": [{"field": "id", "type": "nominal"}]
+            },
+            "layer": [{
+                "mark": "rule",
+                "encoding": {
+                    "x": {"field": "date", "type": "temporal", "timeUnit": "month"},
+                    "size": {"aggregate": "count", "type": "quantitative"}
+                }
+            }]
+        }));
+        chart
+            .set_auto_tooltip(&AutoTooltip::channels().exclude("id"))
+            .unwrap();
+        assert_eq!(
+            serde_json::to_value(&chart.encoding.unwrap().tooltip).unwrap(),
+            json!([
+                {
+                    "field": "date",
+                    "type": "temporal",
+                    "timeUnit": "month",
+                    "title": "date (month)"
+                },
+                {
+                    "field": "price",
+                    "type": "quantitative",
+                    "aggregate": "mean",
+                    "title": "Mean of price",
+                    "format": "$.2f"
+                },
+                {"field": "symbol", "type": "nominal", "title": "Stock"},
+                {"aggregate": "count", "type": "quantitative", "title": "Count of Records"}
+            ])
+        );
+    }
+
+    #[test]
+    fn test_titles() {
+        let encoding = json!({
+            "x": {"field": "date", "type": "temporal", "timeUnit": "yearmonth"},
+            "y": {"field": "price", "type": "quantitative", "bin": true},
+            "size": {"aggregate": "count", "type": "quantitative"},
+            "text": {"field": "symbol", "type": "nominal"}
+        });
+        let chart = chart(json!({"mark": "point", "encoding": encoding}));
+        assert_eq!(
+            titles(&chart),
+            vec![
+                json!("date (year-month)"),
+                json!("price (binned)"),
+                json!("Count of Records"),
+                Value::Null
+            ]
+        );
+        let chart = self::chart(json!({
+            "mark": "point",
+            "encoding": encoding,
+            "config": {"fieldTitle": "plain", "countTitle": "Rows"}
+        }));
+        assert_eq!(
+            titles(&chart),
+            vec![json!("date"), json!("price"), json!("Rows"), Value::Null]
+        );
+        let chart = self::chart(json!({
+            "mark": "point",
+            "encoding": encoding,
+            "config": {"fieldTitle": "functional"}
+        }));
+        assert_eq!(
+            titles(&chart),
+            vec![
+                json!("YEARMONTH(date)"),
+                json!("BIN(price)"),
+                json!("COUNT(*)"),
+                Value::Null
+            ]
+        );
+    }
+
+    #[test]
+    fn test_data() {
+        let chart = chart(json!({
+            "data": {"name": "stocks"},
+            "datasets": {"stocks": [
+                {"symbol": "MSFT", "date": "2000-01-01", "price": 39.81, "note": null},
+                {"symbol": "AMZN", "date": "2000-02-01", "price": 64.56, "note": "split"}
+            ]},
+            "mark": "line",
+            "encoding": {"y": {"field": "price", "type": "quantitative", "title": "Price"}}
+        }));
+        let fields = AutoTooltip::data().exclude("note").fields(&chart).unwrap();
+        assert_eq!(
+            serde_json::to_value(fields).unwrap(),
+            json!([
+                {"field": "date", "type": "temporal"},
+                {"field": "price", "type": "quantitative", "title": "Price"},
+                {"field": "symbol", "type": "nominal"}
+            ])
+        );
+        let chart = self::chart(json!({"data": {"url": "data.csv"}, "mark": "point"}));
+        assert!(AutoTooltip::data().fields(&chart).is_err());
+    }
+}
@@ -0,0 +1,579 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+use crate::error::Error;
+use crate::schema::*;
+
+/// The kind of a [`Transform`](struct.Transform.html), given by the property that is set
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransformKind {
+    /// `aggregate`
+    Aggregate,
+    /// `bin`
+    Bin,
+    /// `calculate`
+    Calculate,
+    /// `filter`
+    Filter,
+    /// `flatten`
+    Flatten,
+    /// `fold`
+    Fold,
+    /// `impute`
+    Impute,
+    /// `joinaggregate`
+    JoinAggregate,
+    /// `lookup`
+    Lookup,
+    /// `sample`
+    Sample,
+    /// `stack`
+    Stack,
+    /// `timeUnit`
+    TimeUnit,
+    /// `window`
+    Window,
+}
+
+/// For each kind of transform, the property identifying it, its required and its optional
+/// properties
+const TRANSFORM_PROPERTIES: &[(TransformKind, &str, &[&str], &[&str])] = &[
+    (TransformKind::Aggregate, "aggregate", &[], &["groupby"]),
+    (TransformKind::Bin, "bin", &["field", "as"], &[]),
+    (TransformKind::Calculate, "calculate", &["as"], &[]),
+    (TransformKind::Filter, "filter", &[], &[]),
+    (TransformKind::Flatten, "flatten", &[], &["as"]),
+    (TransformKind::Fold, "fold", &[], &["as"]),
+    (
+        TransformKind::Impute,
+        "impute",
+        &["key"],
+        &["keyvals", "frame", "groupby", "method", "value"],
+    ),
+    (
+        TransformKind::JoinAggregate,
+        "joinaggregate",
+        &[],
+        &["groupby"],
+    ),
+    (
+        TransformKind::Lookup,
+        "lookup",
+        &["from"],
+        &["as", "default"],
+    ),
+    (TransformKind::Sample, "sample", &[], &[]),
+    (
+        TransformKind::Stack,
+        "stack",
+        &["groupby", "as"],
+        &["offset", "sort"],
+    ),
+    (TransformKind::TimeUnit, "timeUnit", &["field", "as"], &[]),
+    (
+        TransformKind::Window,
+        "window",
+        &[],
+        &["frame", "ignorePeers", "groupby", "sort"],
+    ),
+];
+
+macro_rules! typed_transform {
+    ( $( $(#[$doc:meta])* $name:ident ),* $(,)? ) => {
+        $(
+            $(#[$doc])*
+            #[derive(Debug, Clone)]
+            pub struct $name(Transform);
+
+            impl From<$name> for Transform {
+                fn from(v: $name) -> Self {
+                    v.0
+                }
+            }
+        )*
+    };
+}
+
+typed_transform! {
+    /// An `aggregate` transform, built with [`Transform::aggregate`](struct.Transform.html#method.aggregate)
+    AggregateTransform,
+    /// A `bin` transform, built with [`Transform::bin`](struct.Transform.html#method.bin)
+    BinTransform,
+    /// A `flatten` transform, built with [`Transform::flatten`](struct.Transform.html#method.flatten)
+    FlattenTransform,
+    /// A `fold` transform, built with [`Transform::fold`](struct.Transform.html#method.fold)
+    FoldTransform,
+    /// An `impute` transform, built with [`Transform::impute`](struct.Transform.html#method.impute)
+    ImputeTransform,
+    /// A `joinaggregate` transform, built with
+    /// [`Transform::join_aggregate`](struct.Transform.html#method.join_aggregate)
+    JoinAggregateTransform,
+    /// A `lookup` transform, built with [`Transform::lookup`](struct.Transform.html#method.lookup)
+    LookupTransform,
+    /// A `stack` transform, built with [`Transform::stack`](struct.Transform.html#method.stack)
+    StackTransform,
+    /// A `window` transform, built with [`Transform::window`](struct.Transform.html#method.window)
+    WindowTransform,
+}
+
+fn strings<S: Into<String>>(values: Vec<S>) -> Vec<String> {
+    values.into_iter().map(Into::into).collect()
+}
+
+impl Transform {
+    /// Summarize a table as one record for each group
+    pub fn aggregate(aggregate: Vec<AggregatedFieldDef>) -> AggregateTransform {
+        AggregateTransform(Transform {
+            aggregate: Some(aggregate),
+            ..Default::default()
+        })
+    }
+
+    /// Discretize `field` into bins, stored in `as` (start of the bin, or start and end)
+    pub fn bin(field: &str, transform_as: impl Into<Style>) -> BinTransform {
+        BinTransform(Transform {
+            bin: Some(PurpleBin::Bool(true)),
+            field: Some(field.to_string()),
+            transform_as: Some(transform_as.into()),
+            ..Default::default()
+        })
+    }
+
+    /// Add a field computed from an [expression](expr/index.html)
+    pub fn calculate(calculate: impl Into<String>, transform_as: &str) -> Transform {
+        Transform {
+            calculate: Some(calculate.into()),
+            transform_as: Some(transform_as.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Remove objects that don't satisfy a [predicate](predicate/index.html)
+    pub fn filter(filter: impl Into<PurpleLogicalOperandPredicate>) -> Transform {
+        Transform {
+            filter: Some(filter.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Map array-valued fields to one object per element
+    pub fn flatten<S: Into<String>>(flatten: Vec<S>) -> FlattenTransform {
+        FlattenTransform(Transform {
+            flatten: Some(strings(flatten)),
+            ..Default::default()
+        })
+    }
+
+    /// Collapse fields into key-value pairs
+    pub fn fold<S: Into<String>>(fold: Vec<S>) -> FoldTransform {
+        FoldTransform(Transform {
+            fold: Some(strings(fold)),
+            ..Default::default()
+        })
+    }
+
+    /// Fill missing values of `impute` for each value of `key`
+    pub fn impute(impute: &str, key: &str) -> ImputeTransform {
+        ImputeTransform(Transform {
+            impute: Some(impute.to_string()),
+            key: Some(key.to_string()),
+            ..Default::default()
+        })
+    }
+
+    /// Add aggregated values to each object
+    pub fn join_aggregate(joinaggregate: Vec<JoinAggregateFieldDef>) -> JoinAggregateTransform {
+        JoinAggregateTransform(Transform {
+            joinaggregate: Some(joinaggregate),
+            ..Default::default()
+        })
+    }
+
+    /// Extend objects with values from another data source, matching `lookup` with `from.key`
+    pub fn lookup(lookup: &str, from: LookupData) -> LookupTransform {
+        LookupTransform(Transform {
+            lookup: Some(lookup.to_string()),
+            from: Some(from),
+            ..Default::default()
+        })
+    }
+
+    /// Keep a random sample of at most `sample` objects
+    pub fn sample(sample: f64) -> Transform {
+        Transform {
+            sample: Some(sample),
+            ..Default::default()
+        }
+    }
+
+    /// Stack `stack` within each group, storing the start and end of each object
+    pub fn stack<S: Into<String>>(
+        stack: &str,
+        groupby: Vec<S>,
+        start: &str,
+        end: &str,
+    ) -> StackTransform {
+        StackTransform(Transform {
+            stack: Some(stack.to_string()),
+            groupby: Some(strings(groupby)),
+            transform_as: Some(Style::StringArray(vec![start.to_string(), end.to_string()])),
+            ..Default::default()
+        })
+    }
+
+    /// Discretize a temporal `field` to a time unit
+    pub fn time_unit(time_unit: TimeUnit, field: &str, transform_as: &str) -> Transform {
+        Transform {
+            time_unit: Some(time_unit),
+            field: Some(field.to_string()),
+            transform_as: Some(transform_as.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Compute window operations over sorted groups of objects
+    pub fn window(window: Vec<WindowFieldDef>) -> WindowTransform {
+        WindowTransform(Transform {
+            window: Some(window),
+            ..Default::default()
+        })
+    }
+
+    /// Check that exactly one kind of transform is set, with its required properties and
+    /// without properties of other kinds
+    pub fn validate(&self) -> Result<TransformKind, Error> {
+        let properties = match serde_json::to_value(self)? {
+            serde_json::Value::Object(properties) => properties,
+            _ => unreachable!("a transform is serialized to an object"),
+        };
+        let mut kinds = TRANSFORM_PROPERTIES
+            .iter()
+            .filter(|(_, key, _, _)| properties.contains_key(*key));
+        let (kind, key, required, optional) =
+            match (kinds.next(), kinds.next()) {
+                (Some(kind), None) => kind,
+                (None, _) => return Err(Error::Validation(
+                    "transform has no kind, expected one of aggregate, bin, calculate, filter, \
+                     flatten, fold, impute, joinaggregate, lookup, sample, stack, timeUnit or \
+                     window"
+                        .to_string(),
+                )),
+                (Some((_, first, _, _)), Some((_, second, _, _))) => {
+                    return Err(Error::Validation(format!(
+                        "transform has several kinds: `{}` and `{}`",
+                        first, second
+                    )))
+                }
+            };
+        if let Some(missing) = required.iter().find(|p| !properties.contains_key(**p)) {
+            return Err(Error::Validation(format!(
+                "`{}` transform requires `{}`",
+                key, missing
+            )));
+        }
+        if let Some(extra) = properties.keys().find(|p| {
+            *p != key && !required.contains(&p.as_str()) && !optional.contains(&p.as_str())
+        }) {
+            return Err(Error::Validation(format!(
+                "`{}` is not a property of a `{}` transform",
+                extra, key
+            )));
+        }
+        if let Some(from) = &self.from {
+            if from.data.is_none() || from.key.is_none() {
+                return Err(Error::Validation(format!(
+                    "`lookup` transform requires `from.{}`",
+                    if from.data.is_none() { "data" } else { "key" }
+                )));
+            }
+        }
+        Ok(*kind)
+    }
+}
+
+impl AggregateTransform {
+    /// Fields to group by
+    pub fn groupby<S: Into<String>>(mut self, groupby: Vec<S>) -> Self {
+        self.0.groupby = Some(strings(groupby));
+        self
+    }
+}
+
+impl BinTransform {
+    /// Parameters of the binning, instead of the default ones
+    pub fn params(mut self, params: BinParams) -> Self {
+        self.0.bin = Some(PurpleBin::BinParams(params));
+        self
+    }
+}
+
+impl FlattenTransform {
+    /// Output field names, defaults to the flattened field names
+    pub fn transform_as<S: Into<String>>(mut self, transform_as: Vec<S>) -> Self {
+        self.0.transform_as = Some(Style::StringArray(strings(transform_as)));
+        self
+    }
+}
+
+impl FoldTransform {
+    /// Output field names for the key and the value, defaults to `key` and `value`
+    pub fn transform_as(mut self, key: &str, value: &str) -> Self {
+        self.0.transform_as = Some(Style::StringArray(vec![key.to_string(), value.to_string()]));
+        self
+    }
+}
+
+impl ImputeTransform {
+    /// Key values that should be present, defaults to all the values of `key`
+    pub fn keyvals(mut self, keyvals: impl Into<Keyvals>) -> Self {
+        self.0.keyvals = Some(keyvals.into());
+        self
+    }
+
+    /// Frame of the window used by the imputation method, `None` meaning unbounded
+    pub fn frame(mut self, start: Option<f64>, end: Option<f64>) -> Self {
+        self.0.frame = Some(vec![start, end]);
+        self
+    }
+
+    /// Fields to group by
+    pub fn groupby<S: Into<String>>(mut self, groupby: Vec<S>) -> Self {
+        self.0.groupby = Some(strings(groupby));
+        self
+    }
+
+    /// Imputation method, defaults to `value`
+    pub fn method(mut self, method: ImputeMethod) -> Self {
+        self.0.method = Some(method);
+        self
+    }
+
+    /// Value used by the `value` method
+    pub fn value(mut self, value: impl Into<serde_json::Value>) -> Self {
+        self.0.value = Some(value.into());
+        self
+    }
+}
+
+impl JoinAggregateTransform {
+    /// Fields to group by
+    pub fn groupby<S: Into<String>>(mut self, groupby: Vec<S>) -> Self {
+        self.0.groupby = Some(strings(groupby));
+        self
+    }
+}
+
+impl LookupTransform {
+    /// Output field names for the looked up fields
+    pub fn transform_as(mut self, transform_as: impl Into<Style>) -> Self {
+        self.0.transform_as = Some(transform_as.into());
+        self
+    }
+
+    /// Default value when the key is not found
+    pub fn default_value(mut self, default: &str) -> Self {
+        self.0.transform_default = Some(default.to_string());
+        self
+    }
+}
+
+impl StackTransform {
+    /// Stack offset, defaults to `zero`
+    pub fn offset(mut self, offset: StackOffset) -> Self {
+        self.0.offset = Some(offset);
+        self
+    }
+
+    /// Order of the stacked objects within each group
+    pub fn sort(mut self, sort: Vec<SortField>) -> Self {
+        self.0.sort = Some(sort);
+        self
+    }
+}
+
+impl WindowTransform {
+    /// Frame of the window, `None` meaning unbounded. Defaults to `[None, Some(0.0)]`
+    pub fn frame(mut self, start: Option<f64>, end: Option<f64>) -> Self {
+        self.0.frame = Some(vec![start, end]);
+        self
+    }
+
+    /// Whether to ignore objects with the same sort value when computing the frame
+    pub fn ignore_peers(mut self, ignore_peers: bool) -> Self {
+        self.0.ignore_peers = Some(ignore_peers);
+        self
+    }
+
+    /// Fields to group by
+    pub fn groupby<S: Into<String>>(mut self, groupby: Vec<S>) -> Self {
+        self.0.groupby = Some(strings(groupby));
+        self
+    }
+
+    /// Order of the objects within each group
+    pub fn sort(mut self, sort: Vec<SortField>) -> Self {
+        self.0.sort = Some(sort);
+        self
+    }
+}
+
+/// Builder for a list of transforms, checking each of them with
+/// [`Transform::validate`](struct.Transform.html#method.validate)
+///
+/// ```
+/// # use vega_lite_3::*;
+/// # use vega_lite_3::expr::datum;
+/// # use vega_lite_3::predicate::field;
+/// let transforms = TransformPipeline::new()
+///     .then(Transform::filter(field("year").eq(2000.0)))
+///     .then(Transform::calculate(datum("sex").eq(2).then("Female", "Male"), "gender"))
+///     .build()
+///     .unwrap();
+/// let chart = VegaliteBuilder::default().transform(transforms).build().unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TransformPipeline {
+    transforms: Vec<Transform>,
+}
+
+impl TransformPipeline {
+    /// An empty pipeline
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a transform to the pipeline
+    pub fn then(mut self, transform: impl Into<Transform>) -> Self {
+        self.transforms.push(transform.into());
+        self
+    }
+
+    /// Validate every transform of the pipeline and return them
+    pub fn build(self) -> Result<Vec<Transform>, Error> {
+        for (i, transform) in self.transforms.iter().enumerate() {
+            transform.validate().map_err(|err| match err {
+                Error::Validation(reason) => {
+                    Error::Validation(format!("transform {}: {}", i, reason))
+                }
+                err => err,
+            })?;
+        }
+        Ok(self.transforms)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_constructors() {
+        let window = Transform::window(vec![WindowFieldDefBuilder::default()
+            .op(Op::Mean)
+            .field("temp_max")
+            .window_field_def_as("rolling_mean")
+            .build()
+            .unwrap()])
+        .frame(Some(-15.0), Some(15.0));
+        let window = Transform::from(window);
+        assert_eq!(window.validate().unwrap(), TransformKind::Window);
+        assert_eq!(
+            serde_json::to_value(&window).unwrap(),
+            json!({
+                "window": [{"field": "temp_max", "op": "mean", "as": "rolling_mean"}],
+                "frame": [-15.0, 15.0]
+            })
+        );
+
+        let fold = Transform::from(Transform::fold(vec!["gold", "silver"]).transform_as("k", "v"));
+        assert_eq!(
+            serde_json::to_value(&fold).unwrap(),
+            json!({"fold": ["gold", "silver"], "as": ["k", "v"]})
+        );
+
+        for transform in vec![
+            Transform::from(Transform::aggregate(vec![]).groupby(vec!["a"])),
+            Transform::from(Transform::bin("a", "binned")),
+            Transform::calculate("datum.a * 2", "b"),
+            Transform::from(Transform::flatten(vec!["a"])),
+            Transform::from(Transform::impute("a", "b").method(ImputeMethod::Mean)),
+            Transform::from(Transform::join_aggregate(vec![])),
+            Transform::from(Transform::lookup(
+                "a",
+                LookupDataBuilder::default()
+                    .data(DataBuilder::default().name("other").build().unwrap())
+                    .key("a")
+                    .build()
+                    .unwrap(),
+            )),
+            Transform::sample(100.0),
+            Transform::from(Transform::stack("a", vec!["b"], "start", "end")),
+            Transform::time_unit(TimeUnit::Year, "date", "year"),
+        ] {
+            transform.validate().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_validation() {
+        let transform = TransformBuilder::default()
+            .calculate("datum.a")
+            .build()
+            .unwrap();
+        assert_eq!(
+            transform.validate().unwrap_err().to_string(),
+            "invalid specification: `calculate` transform requires `as`"
+        );
+
+        let transform = TransformBuilder::default()
+            .calculate("datum.a")
+            .transform_as("b")
+            .frame(vec![None, Some(0.0)])
+            .build()
+            .unwrap();
+        assert_eq!(
+            transform.validate().unwrap_err().to_string(),
+            "invalid specification: `frame` is not a property of a `calculate` transform"
+        );
+
+        let transform = TransformBuilder::default()
+            .sample(3.0)
+            .fold(vec!["a".to_string()])
+            .build()
+            .unwrap();
+        assert_eq!(
+            transform.validate().unwrap_err().to_string(),
+            "invalid specification: transform has several kinds: `fold` and `sample`"
+        );
+
+        let transform: Transform = Transform::lookup("id", LookupData::default()).into();
+        assert_eq!(
+            transform.validate().unwrap_err().to_string(),
+            "invalid specification: `lookup` transform requires `from.data`"
+        );
+        let from = LookupDataBuilder::default()
+            .data(DataBuilder::default().name("other").build().unwrap())
+            .build()
+            .unwrap();
+        let transform: Transform = Transform::lookup("id", from).into();
+        assert_eq!(
+            transform.validate().unwrap_err().to_string(),
+            "invalid specification: `lookup` transform requires `from.key`"
+        );
+
+        assert!(TransformPipeline::new()
+            .then(Transform::sample(3.0))
+            .then(Transform::default())
+            .build()
+            .is_err());
+    }
+}
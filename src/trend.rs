@@ -0,0 +1,749 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Trend lines fitted on the inline data of a chart, with an optional confidence band, Vega-Lite
+//! 3 having no `regression` or `loess` transform.
+//!
+//! ```
+//! use vega_lite_3::trend::Trend;
+//! use vega_lite_3::*;
+//!
+//! let mut chart: Vegalite = serde_json::from_str(
+//!     r#"{
+//!         "data": {"values": [{"a": 1, "b": 2.1}, {"a": 2, "b": 3.9}, {"a": 3, "b": 6.2}]},
+//!         "mark": "point",
+//!         "encoding": {
+//!             "x": {"field": "a", "type": "quantitative"},
+//!             "y": {"field": "b", "type": "quantitative"}
+//!         }
+//!     }"#,
+//! )
+//! .unwrap();
+//! chart.add_trend(&Trend::linear().confidence(0.95)).unwrap();
+//! ```
+
+use crate::composite::ErrorBand;
+use crate::error::Error;
+use crate::eval::transformed_rows;
+use crate::recipes::{column, normal_quantile, rows};
+use crate::removable_value::RemovableValue;
+use crate::schema::*;
+use serde_json::{json, Value};
+use std::convert::TryFrom;
+
+/// The number of points at which parametric models are evaluated
+const SAMPLES: usize = 100;
+
+/// The model fitted by a trend
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Model {
+    Polynomial(usize),
+    Exponential,
+    Loess(f64),
+}
+
+/// A trend line, built with [`Trend::linear`](#method.linear),
+/// [`Trend::polynomial`](#method.polynomial), [`Trend::exponential`](#method.exponential) or
+/// [`Trend::loess`](#method.loess)
+#[derive(Debug, Clone)]
+pub struct Trend {
+    model: Model,
+    confidence: Option<f64>,
+    color: Option<String>,
+}
+
+/// A fitted value with its confidence interval, if any
+struct Fitted {
+    x: f64,
+    y: f64,
+    interval: Option<(f64, f64)>,
+}
+
+impl Trend {
+    fn new(model: Model) -> Self {
+        Trend {
+            model,
+            confidence: None,
+            color: None,
+        }
+    }
+
+    /// A least squares line, `y = a + b x`
+    pub fn linear() -> Self {
+        Trend::new(Model::Polynomial(1))
+    }
+
+    /// A least squares polynomial of the given degree
+    pub fn polynomial(degree: usize) -> Self {
+        Trend::new(Model::Polynomial(degree))
+    }
+
+    /// An exponential curve, `y = a e^(b x)`, fitted by least squares on the logarithm of the
+    /// positive `y` values
+    pub fn exponential() -> Self {
+        Trend::new(Model::Exponential)
+    }
+
+    /// A locally weighted linear regression, each point being fitted from the given fraction of
+    /// the data nearest to it
+    pub fn loess(bandwidth: f64) -> Self {
+        Trend::new(Model::Loess(bandwidth))
+    }
+
+    /// Draw the confidence interval of the fitted values at the given level, such as 0.95, as
+    /// an error band
+    pub fn confidence(mut self, level: f64) -> Self {
+        self.confidence = Some(level);
+        self
+    }
+
+    /// The color of the line and of the band
+    pub fn color(mut self, color: impl Into<String>) -> Self {
+        self.color = Some(color.into());
+        self
+    }
+
+    /// The layers drawing the trend of the `y` field against the `x` field of inline data: an
+    /// error band, if a confidence level is set, and a line.
+    ///
+    /// The fitted values are in `x` and `y` fields, and the bounds of their confidence interval
+    /// in `lower` and `upper` fields; the channels are titled with the names of the fitted fields.
+    pub fn layers(&self, data: &UrlData, x: &str, y: &str) -> Result<Vec<LayerSpec>, Error> {
+        self.fit(&rows(data)?, x, y)
+    }
+
+    /// The layers drawing the trend of the `y` field against the `x` field of rows
+    fn fit(&self, rows: &[Value], x: &str, y: &str) -> Result<Vec<LayerSpec>, Error> {
+        let points = column(rows, x)?
+            .into_iter()
+            .zip(column(rows, y)?)
+            .filter_map(|point| match point {
+                (Some(x), Some(y)) => Some((x, y)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        let quantile = match self.confidence {
+            Some(level) if level > 0.0 && level < 1.0 => Some(1.0 - (1.0 - level) / 2.0),
+            Some(level) => {
+                return Err(Error::Validation(format!(
+                    "the confidence level {} is not between 0 and 1",
+                    level
+                )))
+            }
+            None => None,
+        };
+        let fitted = match self.model {
+            Model::Polynomial(degree) => polynomial(&points, degree, quantile)?,
+            Model::Exponential => {
+                if points.iter().any(|(_, y)| *y <= 0.0) {
+                    return Err(Error::Validation(format!(
+                        "an exponential trend needs positive `{}` values",
+                        y
+                    )));
+                }
+                let logarithms = points.iter().map(|(x, y)| (*x, y.ln())).collect::<Vec<_>>();
+                polynomial(&logarithms, 1, quantile)?
+                    .into_iter()
+                    .map(|fitted| Fitted {
+                        y: fitted.y.exp(),
+                        interval: fitted
+                            .interval
+                            .map(|(lower, upper)| (lower.exp(), upper.exp())),
+                        ..fitted
+                    })
+                    .collect()
+            }
+            Model::Loess(bandwidth) => loess(&points, bandwidth, quantile)?,
+        };
+
+        let values = fitted
+            .iter()
+            .map(|fitted| {
+                let mut row = json!({"x": fitted.x, "y": fitted.y});
+                if let Some((lower, upper)) = fitted.interval {
+                    row["lower"] = json!(lower);
+                    row["upper"] = json!(upper);
+                }
+                row
+            })
+            .collect();
+        let data = UrlData {
+            values: Some(UrlDataInlineDataset::UnionArray(values)),
+            ..Default::default()
+        };
+        let position = XClass {
+            field: Some(Field::String("x".to_string())),
+            def_type: Some(StandardType::Quantitative),
+            title: x.to_string().into(),
+            ..Default::default()
+        };
+        let line = MarkDefClass {
+            def_type: Some(Mark::Line),
+            color: self.color.clone(),
+            ..Default::default()
+        };
+        let mut layers = vec![LayerSpec {
+            data: data.clone().into(),
+            mark: Some(line.into()),
+            encoding: Some(LayerEncoding {
+                x: Some(position.clone()),
+                y: Some(YClass {
+                    field: Some(Field::String("y".to_string())),
+                    def_type: Some(StandardType::Quantitative),
+                    title: y.to_string().into(),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }];
+        if quantile.is_some() {
+            let band = match &self.color {
+                Some(color) => ErrorBand::new().color(color.as_str()),
+                None => ErrorBand::new(),
+            };
+            layers.insert(
+                0,
+                LayerSpec {
+                    data: data.into(),
+                    mark: Some(band.into()),
+                    encoding: Some(LayerEncoding {
+                        x: Some(position),
+                        y: Some(YClass {
+                            field: Some(Field::String("lower".to_string())),
+                            def_type: Some(StandardType::Quantitative),
+                            title: y.to_string().into(),
+                            ..Default::default()
+                        }),
+                        y2: Some(Y2Class {
+                            field: Some(Field::String("upper".to_string())),
+                            ..Default::default()
+                        }),
+                        ..Default::default()
+                    }),
+                    ..Default::default()
+                },
+            );
+        }
+        Ok(layers)
+    }
+}
+
+/// The name of a field, unless it is repeated
+fn field_name(field: &Option<Field>) -> Option<&str> {
+    match field {
+        Some(Field::String(name)) => Some(name),
+        _ => None,
+    }
+}
+
+impl Vegalite {
+    /// Add the layers of a trend of the `y` field against the `x` field of the chart, a unit
+    /// chart being turned into a layered chart.
+    ///
+    /// The trend is fitted on the first view setting both fields, a layer or the chart itself,
+    /// from the inline data of the view after its transforms, which must all be evaluated in
+    /// Rust.
+    pub fn add_trend(&mut self, trend: &Trend) -> Result<(), Error> {
+        if self.mark.is_none() && self.layer.is_none() {
+            return Err(Error::Validation(
+                "a trend is added to a single view or layered chart".to_string(),
+            ));
+        }
+        let layers = self.fit_trend(trend)?;
+        if self.mark.is_some() {
+            let encoding = self
+                .encoding
+                .clone()
+                .map(LayerEncoding::try_from)
+                .transpose()?;
+            self.encoding = None;
+            self.layer = Some(vec![LayerSpec {
+                mark: self.mark.take(),
+                encoding,
+                selection: self.selection.take(),
+                ..Default::default()
+            }]);
+        }
+        self.layer.get_or_insert_with(Vec::new).extend(layers);
+        Ok(())
+    }
+
+    /// Fit a trend on the first view of the chart setting the `x` and `y` fields
+    fn fit_trend(&self, trend: &Trend) -> Result<Vec<LayerSpec>, Error> {
+        let encoding = self.encoding.as_ref();
+        let parent = (
+            encoding.and_then(|encoding| field_name(&encoding.x.as_ref()?.field)),
+            encoding.and_then(|encoding| field_name(&encoding.y.as_ref()?.field)),
+        );
+        let views = match &self.layer {
+            Some(layers) => layers.iter().map(Some).collect(),
+            None => vec![None],
+        };
+        let (x, y, layer) = views
+            .into_iter()
+            .find_map(|layer: Option<&LayerSpec>| {
+                let encoding = layer.and_then(|layer| layer.encoding.as_ref());
+                let x = encoding.and_then(|encoding| field_name(&encoding.x.as_ref()?.field));
+                let y = encoding.and_then(|encoding| field_name(&encoding.y.as_ref()?.field));
+                Some((x.or(parent.0)?, y.or(parent.1)?, layer))
+            })
+            .ok_or_else(|| {
+                Error::Validation("a trend needs the `x` and `y` fields of the chart".to_string())
+            })?;
+        let mut transforms = Vec::new();
+        let data = match layer.map(|layer| &layer.data) {
+            Some(RemovableValue::Specified(data)) => data,
+            _ => {
+                transforms.extend(self.transform.iter().flatten().cloned());
+                match &self.data {
+                    RemovableValue::Specified(data) => data,
+                    _ => {
+                        return Err(Error::Validation(
+                            "a trend is fitted on the inline data of the chart".to_string(),
+                        ))
+                    }
+                }
+            }
+        };
+        transforms.extend(
+            layer
+                .and_then(|layer| layer.transform.clone())
+                .into_iter()
+                .flatten(),
+        );
+        trend.fit(&transformed_rows(data, &transforms)?, x, y)
+    }
+}
+
+/// The quantile of the Student's t distribution with `df` degrees of freedom, with the
+/// Cornish-Fisher expansion around the normal quantile
+fn t_quantile(p: f64, df: f64) -> f64 {
+    let z = normal_quantile(p);
+    let (z3, z5, z7) = (z.powi(3), z.powi(5), z.powi(7));
+    z + (z3 + z) / (4.0 * df)
+        + (5.0 * z5 + 16.0 * z3 + 3.0 * z) / (96.0 * df.powi(2))
+        + (3.0 * z7 + 19.0 * z5 + 17.0 * z3 - 15.0 * z) / (384.0 * df.powi(3))
+}
+
+/// Invert a square matrix with Gauss-Jordan elimination, `None` if it is singular
+fn invert(mut matrix: Vec<Vec<f64>>) -> Option<Vec<Vec<f64>>> {
+    let n = matrix.len();
+    let mut inverse = (0..n)
+        .map(|i| (0..n).map(|j| if i == j { 1.0 } else { 0.0 }).collect())
+        .collect::<Vec<Vec<f64>>>();
+    for column in 0..n {
+        let pivot = (column..n).max_by(|a, b| {
+            matrix[*a][column]
+                .abs()
+                .total_cmp(&matrix[*b][column].abs())
+        })?;
+        let magnitude = matrix[pivot][column].abs();
+        if magnitude.is_nan() || magnitude < 1e-12 {
+            return None;
+        }
+        matrix.swap(column, pivot);
+        inverse.swap(column, pivot);
+        let scale = matrix[column][column];
+        for j in 0..n {
+            matrix[column][j] /= scale;
+            inverse[column][j] /= scale;
+        }
+        for row in (0..n).filter(|row| *row != column) {
+            let factor = matrix[row][column];
+            for j in 0..n {
+                matrix[row][j] -= factor * matrix[column][j];
+                inverse[row][j] -= factor * inverse[column][j];
+            }
+        }
+    }
+    Some(inverse)
+}
+
+/// The range of the `x` values of points
+fn extent(points: &[(f64, f64)]) -> (f64, f64) {
+    points
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), (x, _)| {
+            (min.min(*x), max.max(*x))
+        })
+}
+
+/// Fit a least squares polynomial, evaluated at evenly spaced `x` values, with the confidence
+/// interval of the mean at the given quantile
+fn polynomial(
+    points: &[(f64, f64)],
+    degree: usize,
+    quantile: Option<f64>,
+) -> Result<Vec<Fitted>, Error> {
+    let parameters = degree + 1;
+    if degree == 0 || points.len() <= parameters {
+        return Err(Error::Validation(format!(
+            "a polynomial trend of degree {} needs more than {} points",
+            degree, parameters
+        )));
+    }
+    let distinct_x = || {
+        Error::Validation(format!(
+            "a polynomial trend of degree {} needs more distinct `x` values",
+            degree
+        ))
+    };
+    let mut xs = points.iter().map(|(x, _)| *x).collect::<Vec<_>>();
+    xs.sort_by(f64::total_cmp);
+    xs.dedup();
+    if xs.len() < parameters {
+        return Err(distinct_x());
+    }
+    // x is centered and scaled for the normal equations to be well conditioned
+    let (min, max) = extent(points);
+    let (center, scale) = ((min + max) / 2.0, (max - min) / 2.0);
+    let powers = |x: f64| {
+        let u = (x - center) / scale;
+        (0..parameters)
+            .map(|power| u.powi(power as i32))
+            .collect::<Vec<_>>()
+    };
+    let mut normal = vec![vec![0.0; parameters]; parameters];
+    let mut moments = vec![0.0; parameters];
+    for (x, y) in points {
+        let row = powers(*x);
+        for i in 0..parameters {
+            moments[i] += row[i] * y;
+            for j in 0..parameters {
+                normal[i][j] += row[i] * row[j];
+            }
+        }
+    }
+    let inverse = invert(normal).ok_or_else(distinct_x)?;
+    let coefficients = inverse
+        .iter()
+        .map(|row| row.iter().zip(&moments).map(|(a, b)| a * b).sum::<f64>())
+        .collect::<Vec<_>>();
+    let predict = |x: f64| {
+        let row = powers(x);
+        let y = row
+            .iter()
+            .zip(&coefficients)
+            .map(|(a, b)| a * b)
+            .sum::<f64>();
+        let leverage = (0..parameters)
+            .map(|i| {
+                (0..parameters)
+                    .map(|j| row[i] * inverse[i][j] * row[j])
+                    .sum::<f64>()
+            })
+            .sum::<f64>();
+        (y, leverage)
+    };
+    let df = (points.len() - parameters) as f64;
+    let variance = points
+        .iter()
+        .map(|(x, y)| (y - predict(*x).0).powi(2))
+        .sum::<f64>()
+        / df;
+    let t = quantile.map(|quantile| t_quantile(quantile, df));
+    Ok((0..SAMPLES)
+        .map(|i| {
+            let x = min + (max - min) * i as f64 / (SAMPLES - 1) as f64;
+            let (y, leverage) = predict(x);
+            let interval = t.map(|t| {
+                let margin = t * (variance * leverage).sqrt();
+                (y - margin, y + margin)
+            });
+            Fitted { x, y, interval }
+        })
+        .collect())
+}
+
+/// The weights of the points of a local linear regression at `x0`, which fitted value is the
+/// weighted sum of the `y` values
+fn loess_weights(points: &[(f64, f64)], x0: f64, neighbors: usize) -> Vec<f64> {
+    let mut distances = points
+        .iter()
+        .map(|(x, _)| (x - x0).abs())
+        .collect::<Vec<_>>();
+    let mut sorted = distances.clone();
+    sorted.sort_by(|a, b| a.partial_cmp(b).expect("json numbers are not NaN"));
+    let radius = sorted[neighbors - 1] * 1.000_001;
+    for distance in &mut distances {
+        *distance = if radius > 0.0 {
+            (1.0 - (*distance / radius).powi(3)).max(0.0).powi(3)
+        } else if *distance == 0.0 {
+            1.0
+        } else {
+            0.0
+        };
+    }
+    let (mut s0, mut s1, mut s2) = (0.0, 0.0, 0.0);
+    for ((x, _), weight) in points.iter().zip(&distances) {
+        let d = x - x0;
+        s0 += weight;
+        s1 += weight * d;
+        s2 += weight * d * d;
+    }
+    let determinant = s0 * s2 - s1 * s1;
+    points
+        .iter()
+        .zip(&distances)
+        .map(|((x, _), weight)| {
+            if determinant.abs() > 1e-12 * s0 * s2.max(1.0) {
+                weight * (s2 - s1 * (x - x0)) / determinant
+            } else {
+                weight / s0
+            }
+        })
+        .collect()
+}
+
+/// Fit a locally weighted linear regression with tricube weights, evaluated at each distinct
+/// `x` value, with the confidence interval of the mean at the given quantile
+fn loess(
+    points: &[(f64, f64)],
+    bandwidth: f64,
+    quantile: Option<f64>,
+) -> Result<Vec<Fitted>, Error> {
+    if bandwidth.is_nan() || bandwidth <= 0.0 || bandwidth > 1.0 {
+        return Err(Error::Validation(format!(
+            "the loess bandwidth {} is not in (0, 1]",
+            bandwidth
+        )));
+    }
+    if points.len() < 3 {
+        return Err(Error::Validation(
+            "a loess trend needs at least 3 points".to_string(),
+        ));
+    }
+    let neighbors = ((bandwidth * points.len() as f64).ceil() as usize).clamp(2, points.len());
+    let fit = |x0: f64| {
+        let weights = loess_weights(points, x0, neighbors);
+        let y = weights
+            .iter()
+            .zip(points)
+            .map(|(w, (_, y))| w * y)
+            .sum::<f64>();
+        (y, weights)
+    };
+    let mut xs = points.iter().map(|(x, _)| *x).collect::<Vec<_>>();
+    xs.sort_by(|a, b| a.partial_cmp(b).expect("json numbers are not NaN"));
+    xs.dedup();
+
+    let t = match quantile {
+        Some(quantile) => {
+            let (mut residuals, mut trace) = (0.0, 0.0);
+            for (i, (x, y)) in points.iter().enumerate() {
+                let (fitted, weights) = fit(*x);
+                residuals += (y - fitted).powi(2);
+                trace += weights[i];
+            }
+            let df = points.len() as f64 - trace;
+            if df <= 0.0 {
+                return Err(Error::Validation(
+                    "the confidence interval of a loess trend needs a larger bandwidth".to_string(),
+                ));
+            }
+            Some((t_quantile(quantile, df), residuals / df))
+        }
+        None => None,
+    };
+    Ok(xs
+        .into_iter()
+        .map(|x| {
+            let (y, weights) = fit(x);
+            let interval = t.map(|(t, variance)| {
+                let margin = t * (variance * weights.iter().map(|w| w * w).sum::<f64>()).sqrt();
+                (y - margin, y + margin)
+            });
+            Fitted { x, y, interval }
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn data(points: &[(f64, f64)]) -> UrlData {
+        let rows = points
+            .iter()
+            .map(|(x, y)| json!({"x": x, "y": y}))
+            .collect();
+        UrlData {
+            values: Some(UrlDataInlineDataset::UnionArray(rows)),
+            ..Default::default()
+        }
+    }
+
+    fn fitted(layer: &LayerSpec) -> Vec<Value> {
+        let data = serde_json::to_value(&layer.data).unwrap();
+        data["values"].as_array().unwrap().clone()
+    }
+
+    #[test]
+    fn test_parametric() {
+        let line = [(0.0, 1.0), (1.0, 3.0), (2.0, 5.0), (3.0, 7.0)];
+        let layers = Trend::linear().layers(&data(&line), "x", "y").unwrap();
+        assert_eq!(layers.len(), 1);
+        let values = fitted(&layers[0]);
+        assert_eq!(values.len(), SAMPLES);
+        assert!((values[SAMPLES - 1]["y"].as_f64().unwrap() - 7.0).abs() < 1e-9);
+
+        let parabola = (0..6)
+            .map(|x| (x as f64, (x * x) as f64 + 0.1 * (x % 2) as f64))
+            .collect::<Vec<_>>();
+        let layers = Trend::polynomial(2)
+            .confidence(0.95)
+            .layers(&data(&parabola), "x", "y")
+            .unwrap();
+        assert_eq!(layers.len(), 2);
+        let band = fitted(&layers[0]);
+        let middle = &band[SAMPLES / 2];
+        let (lower, y, upper) = (
+            middle["lower"].as_f64().unwrap(),
+            middle["y"].as_f64().unwrap(),
+            middle["upper"].as_f64().unwrap(),
+        );
+        assert!(lower < y && y < upper && upper - lower < 1.0);
+
+        let growth = (0..5)
+            .map(|x| (x as f64, 2.0 * (0.5 * x as f64).exp()))
+            .collect::<Vec<_>>();
+        let layers = Trend::exponential()
+            .layers(&data(&growth), "x", "y")
+            .unwrap();
+        assert!((fitted(&layers[0])[0]["y"].as_f64().unwrap() - 2.0).abs() < 1e-9);
+
+        assert!(Trend::exponential()
+            .layers(&data(&[(0.0, 1.0), (1.0, 0.0), (2.0, 2.0)]), "x", "y")
+            .is_err());
+        assert!(Trend::polynomial(3).layers(&data(&line), "x", "y").is_err());
+        let constant = [(1.0, 1.0), (1.0, 2.0), (1.0, 3.0), (1.0, 4.0)];
+        for trend in [Trend::linear(), Trend::polynomial(2), Trend::exponential()] {
+            assert!(matches!(
+                trend.layers(&data(&constant), "x", "y"),
+                Err(Error::Validation(_))
+            ));
+        }
+        assert!(Trend::linear()
+            .confidence(1.5)
+            .layers(&data(&line), "x", "y")
+            .is_err());
+    }
+
+    #[test]
+    fn test_loess() {
+        let points = (0..20)
+            .map(|x| (x as f64, (x as f64 / 3.0).sin()))
+            .collect::<Vec<_>>();
+        let layers = Trend::loess(0.3)
+            .confidence(0.9)
+            .layers(&data(&points), "x", "y")
+            .unwrap();
+        let band = fitted(&layers[0]);
+        assert_eq!(band.len(), 20);
+        for (row, (_, y)) in band.iter().zip(&points) {
+            assert!((row["y"].as_f64().unwrap() - y).abs() < 0.1);
+            assert!(row["lower"].as_f64().unwrap() < row["upper"].as_f64().unwrap());
+        }
+        assert!(Trend::loess(0.0).layers(&data(&points), "x", "y").is_err());
+    }
+
+    fn chart(spec: Value) -> Vegalite {
+        crate::with_big_stack(move || serde_json::from_value(spec).unwrap())
+    }
+
+    #[test]
+    fn test_add_trend() {
+        let mut chart = chart(json!({
+            "data": {"values": [{"a": 1, "b": 1}, {"a": 2, "b": 3}, {"a": 3, "b": 4}]},
+            "mark": "point",
+            "encoding": {
+                "x": {"field": "a", "type": "quantitative"},
+                "y": {"field": "b", "type": "quantitative"}
+            },
+            "width": 300
+        }));
+        chart
+            .add_trend(&Trend::linear().color("firebrick"))
+            .unwrap();
+        let chart = serde_json::to_value(chart).unwrap();
+        assert_eq!(chart["width"], json!(300.0));
+        assert!(chart["mark"].is_null() && chart["encoding"].is_null());
+        assert_eq!(chart["layer"][0]["mark"], json!("point"));
+        assert_eq!(
+            chart["layer"][1]["mark"],
+            json!({"type": "line", "color": "firebrick"})
+        );
+        assert_eq!(
+            chart["layer"][1]["encoding"]["y"],
+            json!({"field": "y", "type": "quantitative", "title": "b"})
+        );
+
+        let mut chart = self::chart(json!({"data": {"url": "data.csv"}, "mark": "point"}));
+        assert!(chart.add_trend(&Trend::linear()).is_err());
+    }
+
+    #[test]
+    fn test_trend_views() {
+        // the chart transforms apply to the data inherited by the layer, not to the data of
+        // the other layer
+        let mut chart = chart(json!({
+            "data": {"values": [{"a": 1, "b": 1}, {"a": 2, "b": 3}, {"a": 3, "b": 4}, {"a": 9}]},
+            "transform": [{"filter": "datum.a < 5"}],
+            "layer": [
+                {"data": {"values": []}, "mark": "rule"},
+                {
+                    "mark": "point",
+                    "encoding": {"y": {"field": "b", "type": "quantitative"}},
+                    "transform": [{"calculate": "datum.a * 2", "as": "upper"}]
+                }
+            ],
+            "encoding": {"x": {"field": "upper", "type": "quantitative"}}
+        }));
+        chart.add_trend(&Trend::linear().confidence(0.9)).unwrap();
+        let layers = chart.layer.unwrap();
+        assert_eq!(layers.len(), 4);
+        let band = fitted(&layers[2]);
+        assert_eq!(band[0]["x"], json!(2.0));
+        assert_eq!(band[SAMPLES - 1]["x"], json!(6.0));
+        assert!(band[0]["lower"].as_f64().unwrap() < band[0]["upper"].as_f64().unwrap());
+        assert_eq!(
+            serde_json::to_value(&layers[2].encoding).unwrap()["x"],
+            json!({"field": "x", "type": "quantitative", "title": "upper"})
+        );
+
+        let mut chart = self::chart(json!({
+            "data": {"values": [{"a": 1, "b": 1}]},
+            "layer": [{
+                "data": {"values": [{"a": 1, "b": 1}, {"a": 2, "b": 3}, {"a": 3, "b": 4}]},
+                "mark": "point",
+                "encoding": {
+                    "x": {"field": "a", "type": "quantitative"},
+                    "y": {"field": "b", "type": "quantitative"}
+                }
+            }]
+        }));
+        chart.add_trend(&Trend::linear()).unwrap();
+        assert_eq!(fitted(&chart.layer.unwrap()[1]).len(), SAMPLES);
+
+        let mut chart = self::chart(json!({
+            "data": {"values": [{"a": 1, "b": 1}, {"a": 2, "b": 3}, {"a": 3, "b": 4}]},
+            "transform": [{"filter": {"selection": "brush"}}],
+            "mark": "point",
+            "encoding": {
+                "x": {"field": "a", "type": "quantitative"},
+                "y": {"field": "b", "type": "quantitative"}
+            }
+        }));
+        assert!(chart.add_trend(&Trend::linear()).is_err());
+        assert!(chart.layer.is_none() && chart.mark.is_some());
+    }
+}
@@ -0,0 +1,209 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+//! Input elements filtering or highlighting the data of a chart, each one being a single
+//! [`NamedSelection`](../selection/struct.NamedSelection.html) of a field bound to the input.
+//!
+//! ```
+//! use vega_lite_3::selection::selections;
+//! use vega_lite_3::widgets;
+//! use vega_lite_3::*;
+//!
+//! let year = widgets::slider("Year", 1955.0, 2005.0, 5.0).init(2000.0);
+//! let origin = widgets::dropdown("Origin", vec!["Europe", "Japan", "USA"]).label("Origin ");
+//! let chart = VegaliteBuilder::default()
+//!     .transform(vec![TransformBuilder::default()
+//!         .filter(year.filter())
+//!         .build()
+//!         .unwrap()])
+//!     .encoding(
+//!         EncodingBuilder::default()
+//!             .color(
+//!                 DefWithConditionMarkPropFieldDefStringNullBuilder::default()
+//!                     .condition(origin.condition("steelblue"))
+//!                     .value("lightgray")
+//!                     .build()
+//!                     .unwrap(),
+//!             )
+//!             .build()
+//!             .unwrap(),
+//!     )
+//!     .selection(selections(vec![year.into(), origin.into()]))
+//!     .build()
+//!     .unwrap();
+//! ```
+
+use crate::predicate::SelectionPred;
+use crate::schema::*;
+use crate::selection::{Input, NamedSelection, SelectionCondition};
+
+/// An input element bound to a field, built with [`slider`](fn.slider.html),
+/// [`dropdown`](fn.dropdown.html), [`radio`](fn.radio.html) or [`checkbox`](fn.checkbox.html)
+#[derive(Debug, Clone)]
+pub struct Widget {
+    field: String,
+    selection: NamedSelection,
+    input: Input,
+}
+
+/// A slider selecting a number of a field between `min` and `max`
+pub fn slider(field: &str, min: f64, max: f64, step: f64) -> Widget {
+    Widget::new(field, Input::range(min, max).step(step))
+}
+
+/// A drop-down list selecting one of the values of a field
+pub fn dropdown<T: Into<serde_json::Value>>(field: &str, options: Vec<T>) -> Widget {
+    Widget::new(field, Input::select(options))
+}
+
+/// Radio buttons selecting one of the values of a field
+pub fn radio<T: Into<serde_json::Value>>(field: &str, options: Vec<T>) -> Widget {
+    Widget::new(field, Input::radio(options))
+}
+
+/// A checkbox selecting the data where a boolean field is checked or not
+pub fn checkbox(field: &str) -> Widget {
+    Widget::new(field, Input::checkbox())
+}
+
+impl Widget {
+    fn new(field: &str, input: Input) -> Self {
+        let name = field
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect::<String>();
+        Widget {
+            field: field.to_string(),
+            selection: NamedSelection::single(&format!("{}_input", name)).fields(vec![field]),
+            input,
+        }
+    }
+
+    /// The name of the selection, `{field}_input` by default
+    pub fn name(mut self, name: &str) -> Self {
+        self.selection = self.selection.renamed(name);
+        self
+    }
+
+    /// The label of the input element, the name of the field by default
+    pub fn label(mut self, label: &str) -> Self {
+        self.input = self.input.name(label);
+        self
+    }
+
+    /// The value selected before any interaction, all the data being selected otherwise
+    pub fn init(mut self, value: impl Into<InitSelectionInitMapping>) -> Self {
+        self.selection = self.selection.init(&self.field, value);
+        self
+    }
+
+    /// The name of the selection
+    pub fn selection_name(&self) -> &str {
+        self.selection.name()
+    }
+
+    /// A predicate keeping the selected data, for a filter transform
+    pub fn filter(&self) -> SelectionPred {
+        self.selection.pred()
+    }
+
+    /// A value of an encoding channel for the selected data
+    pub fn condition<V>(&self, value: V) -> SelectionCondition<V> {
+        self.selection.condition(value)
+    }
+}
+
+impl From<Widget> for NamedSelection {
+    fn from(v: Widget) -> Self {
+        v.selection.bind(v.input)
+    }
+}
+
+impl From<Widget> for SelectionDef {
+    fn from(v: Widget) -> Self {
+        NamedSelection::from(v).into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::selection::selections;
+    use serde_json::json;
+
+    #[test]
+    fn test_widgets() {
+        let year = slider("Year", 1955.0, 2005.0, 5.0).init(2000.0);
+        let origin = radio("Origin", vec!["Europe", "USA"]).name("origin");
+        let manual = checkbox("Is manual").label("Manual ");
+        let cylinders = dropdown("Cylinders", vec![4, 6, 8]);
+        let filter: PurpleLogicalOperandPredicate = (year.filter() & manual.filter()).into();
+        assert_eq!(
+            serde_json::to_value(filter).unwrap(),
+            json!({"selection": {"and": ["Year_input", "Is_manual_input"]}})
+        );
+        assert_eq!(
+            serde_json::to_value(ColorCondition::from(origin.condition("red"))).unwrap(),
+            json!({"selection": "origin", "value": "red"})
+        );
+        assert_eq!(
+            serde_json::to_value(selections(vec![
+                year.into(),
+                origin.into(),
+                manual.into(),
+                cylinders.into()
+            ]))
+            .unwrap(),
+            json!({
+                "Year_input": {
+                    "type": "single",
+                    "fields": ["Year"],
+                    "init": {"Year": 2000.0},
+                    "bind": {"input": "range", "min": 1955.0, "max": 2005.0, "step": 5.0}
+                },
+                "origin": {
+                    "type": "single",
+                    "fields": ["Origin"],
+                    "bind": {"input": "radio", "options": ["Europe", "USA"]}
+                },
+                "Is_manual_input": {
+                    "type": "single",
+                    "fields": ["Is manual"],
+                    "bind": {"input": "checkbox", "name": "Manual "}
+                },
+                "Cylinders_input": {
+                    "type": "single",
+                    "fields": ["Cylinders"],
+                    "bind": {"input": "select", "options": [4, 6, 8]}
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn test_init_then_name() {
+        let year = slider("Year", 1955.0, 2005.0, 5.0)
+            .init(2000.0)
+            .name("year");
+        assert_eq!(year.selection_name(), "year");
+        assert_eq!(
+            serde_json::to_value(selections(vec![year.into()])).unwrap(),
+            json!({
+                "year": {
+                    "type": "single",
+                    "fields": ["Year"],
+                    "init": {"Year": 2000.0},
+                    "bind": {"input": "range", "min": 1955.0, "max": 2005.0, "step": 5.0}
+                }
+            })
+        );
+    }
+}
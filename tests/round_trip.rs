@@ -0,0 +1,154 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Round-trip fidelity of the schema types.
+//!
+//! Every Vega-Lite v3 example spec vendored in `tests/specs` is deserialized into a `Vegalite`,
+//! serialized back and compared semantically to the original json. Specs that are known to lose
+//! information are listed in `KNOWN_LOSSY` with the json paths that differ, so that a regression
+//! or a fix in the schema shows up as a test failure.
+
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use vega_lite_3::Vegalite;
+
+/// Specs that don't survive a round-trip, with the json paths that are known to differ.
+const KNOWN_LOSSY: &[(&str, &[&str])] = &[
+    // `spacing` on a facet channel was dropped from the v3 schema, unknown fields are ignored
+    ("bar_grouped", &["/encoding/column/spacing"]),
+    // the generated `FacetFieldDef` is shared by `row`, `column` and `facet`, so it lacks the
+    // `columns` property of the wrapping `facet` channel
+    ("trellis_barley", &["/encoding/facet/columns"]),
+];
+
+fn spec_files() -> Vec<PathBuf> {
+    let mut files = fs::read_dir(Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/specs"))
+        .expect("tests/specs should exist")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.to_string_lossy().ends_with(".vl.json"))
+        .collect::<Vec<_>>();
+    files.sort();
+    files
+}
+
+fn spec_name(path: &Path) -> String {
+    path.file_name()
+        .unwrap()
+        .to_string_lossy()
+        .trim_end_matches(".vl.json")
+        .to_string()
+}
+
+/// Compare two json values, ignoring the integer / float distinction of numbers, and collect the
+/// paths where they differ.
+fn diff(path: &str, original: &Value, round_tripped: &Value, differences: &mut Vec<String>) {
+    match (original, round_tripped) {
+        (Value::Number(a), Value::Number(b)) => {
+            if a.as_f64() != b.as_f64() {
+                differences.push(path.to_string());
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            if a.len() != b.len() {
+                differences.push(path.to_string());
+                return;
+            }
+            for (i, (a, b)) in a.iter().zip(b.iter()).enumerate() {
+                diff(&format!("{}/{}", path, i), a, b, differences);
+            }
+        }
+        (Value::Object(a), Value::Object(b)) => {
+            let mut keys = a.keys().chain(b.keys()).collect::<Vec<_>>();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let path = format!("{}/{}", path, key);
+                match (a.get(key), b.get(key)) {
+                    (Some(a), Some(b)) => diff(&path, a, b, differences),
+                    _ => differences.push(path),
+                }
+            }
+        }
+        (a, b) => {
+            if a != b {
+                differences.push(path.to_string());
+            }
+        }
+    }
+}
+
+fn round_trip(path: &Path) -> Result<Vec<String>, String> {
+    let content = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let original: Value = serde_json::from_str(&content).map_err(|err| err.to_string())?;
+    let vegalite: Vegalite = serde_json::from_str(&content).map_err(|err| err.to_string())?;
+    let round_tripped = serde_json::to_value(&vegalite).map_err(|err| err.to_string())?;
+    let mut differences = vec![];
+    diff("", &original, &round_tripped, &mut differences);
+    Ok(differences)
+}
+
+#[test]
+fn specs_are_vendored() {
+    assert!(spec_files().len() >= 40);
+}
+
+#[test]
+fn specs_round_trip() {
+    // the schema structs are large (a `Vegalite` is over 100KiB), and deserializing them
+    // overflows the default 2MiB test thread stack in debug builds
+    std::thread::Builder::new()
+        .stack_size(64 * 1024 * 1024)
+        .spawn(check_all_specs)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+fn check_all_specs() {
+    let mut failures = vec![];
+    for path in spec_files() {
+        let name = spec_name(&path);
+        let expected = KNOWN_LOSSY
+            .iter()
+            .find(|(lossy, _)| *lossy == name)
+            .map(|(_, paths)| paths.to_vec())
+            .unwrap_or_default();
+        match round_trip(&path) {
+            Ok(differences) => {
+                if differences != expected {
+                    failures.push(format!(
+                        "{}: expected differences {:?}, got {:?}",
+                        name, expected, differences
+                    ));
+                }
+            }
+            Err(err) => failures.push(format!("{}: {}", name, err)),
+        }
+    }
+    assert!(failures.is_empty(), "\n{}\n", failures.join("\n"));
+}
+
+#[test]
+fn known_lossy_specs_exist() {
+    let names = spec_files()
+        .iter()
+        .map(|path| spec_name(path))
+        .collect::<Vec<_>>();
+    for (name, _) in KNOWN_LOSSY {
+        assert!(
+            names.contains(&name.to_string()),
+            "{} is not vendored",
+            name
+        );
+    }
+}
@@ -0,0 +1,70 @@
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     https://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! The default theme applies to every rendering of a chart.
+//!
+//! The default theme is global, so this lives in its own test binary: setting it can't leak into
+//! the other tests.
+
+use serde_json::Value;
+use std::fs;
+use vega_lite_3::*;
+
+/// The spec embedded in an html page
+fn embedded_spec(page: &str) -> Value {
+    let start = page.find("var spec = ").expect("the page has a spec") + "var spec = ".len();
+    let end = start + page[start..].find(";\n").expect("the spec ends the line");
+    serde_json::from_str(&page[start..end]).unwrap()
+}
+
+#[test]
+fn default_theme_applies_to_every_rendering() {
+    // the schema structs overflow the default test thread stack in debug builds
+    std::thread::Builder::new()
+        .stack_size(64 * 1024 * 1024)
+        .spawn(check_default_theme)
+        .unwrap()
+        .join()
+        .unwrap();
+}
+
+fn check_default_theme() {
+    let mut corporate = themes::quartz();
+    corporate.background = Some("#fafafa".to_string());
+    themes::register("corporate", corporate);
+    themes::set_default(Some("corporate")).unwrap();
+
+    let chart = VegaliteBuilder::default().mark(Mark::Bar).build().unwrap();
+    let dir = std::env::temp_dir().join(format!("vega_lite_3-themes-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    chart.save(dir.join("chart.json")).unwrap();
+    chart.save(dir.join("chart.html")).unwrap();
+    let json: Value =
+        serde_json::from_str(&fs::read_to_string(dir.join("chart.json")).unwrap()).unwrap();
+    let html = embedded_spec(&fs::read_to_string(dir.join("chart.html")).unwrap());
+    fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(json["config"]["background"], "#fafafa");
+    assert_eq!(json["config"], html["config"]);
+    let string: Value = serde_json::from_str(&chart.to_string().unwrap()).unwrap();
+    assert_eq!(string["config"], json["config"]);
+    #[cfg(feature = "show_vega")]
+    {
+        let content: Value =
+            serde_json::from_str(&chart.to_content_info().unwrap().content).unwrap();
+        assert_eq!(content["config"], json["config"]);
+    }
+
+    themes::set_default(None).unwrap();
+    let plain: Value = serde_json::from_str(&chart.to_string().unwrap()).unwrap();
+    assert!(plain.get("config").is_none());
+}